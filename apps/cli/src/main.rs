@@ -2,13 +2,15 @@ use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 use novel_core::{
-    NoteStatusFilter, Priority, Result, add_note_in, edit_note_text,
+    NoteStatusFilter, Priority, add_note_in, edit_note_text,
     storage::{
         DEFAULT_DB_FILE, list_notes_in, mark_note_done_in, remove_note_by_id_in,
         set_note_priority_in,
     },
 };
 
+mod vcs;
+
 #[derive(Parser)]
 #[command(name = "novel-cli")]
 struct Cli {
@@ -50,6 +52,14 @@ enum Commands {
         id: u64,
         priority: i64,
     },
+    // 프로젝트 폴더의 버전 관리(.novel) 명령
+    Vcs {
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+
+        #[command(subcommand)]
+        command: vcs::VcsCommands,
+    },
 }
 
 fn main() {
@@ -59,7 +69,7 @@ fn main() {
     }
 }
 
-fn run() -> Result<()> {
+fn run() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     let db_path = std::env::var_os("NOVEL_DB_PATH")
@@ -120,6 +130,7 @@ fn run() -> Result<()> {
             set_note_priority_in(&db_path, id, priority)?;
             println!("set priority of Note #{} to {}", id, priority.value());
         }
+        Commands::Vcs { root, command } => vcs::run(&root, command)?,
     }
 
     Ok(())
@@ -0,0 +1,339 @@
+use std::path::Path;
+
+use clap::Subcommand;
+
+// 암호화 저장소는 CLI를 실행할 때마다 이 환경 변수의 암호로 푼다
+const PASSPHRASE_ENV: &str = "NOVEL_PASSPHRASE";
+// rotate-key의 새 암호
+const NEW_PASSPHRASE_ENV: &str = "NOVEL_NEW_PASSPHRASE";
+
+#[derive(Subcommand)]
+pub enum VcsCommands {
+    // HEAD 이동 기록(최신 순)
+    Reflog,
+    // 마지막 HEAD 이동 되돌리기
+    Undo,
+    // 히스토리 그래프(git log --graph 형식)
+    Graph,
+    // 리비전(HEAD~1, 접두사, 태그 ...)을 노드 id로 풀어 출력
+    RevParse {
+        revision: String,
+    },
+    Checkout {
+        revision: String,
+    },
+    // 작업 트리와 리비전(기본 HEAD) 사이 바뀐 파일 목록. 아직 스냅샷에 없는 파일은 A
+    Status {
+        revision: Option<String>,
+    },
+    // 두 리비전 사이 바뀐 파일 목록(단어 수 변화 포함). --file이면 그 파일의 unified diff
+    Diff {
+        from: String,
+        #[arg(default_value = "HEAD")]
+        to: String,
+        #[arg(long)]
+        file: Option<String>,
+    },
+    // 이름 없이 실행하면 태그 목록
+    Tag {
+        name: Option<String>,
+        #[arg(default_value = "HEAD")]
+        revision: String,
+        #[arg(long, requires = "name")]
+        delete: bool,
+    },
+    // 모든 버전의 텍스트 파일에서 검색. 경로와 그 내용이 든 노드(최신부터), 문맥 한 줄
+    Search {
+        query: String,
+    },
+    // 문구 개수가 바뀐 노드(최신부터): 언제 들어오고 언제 잘렸는지. --path는 파일이나 디렉터리
+    Pickaxe {
+        phrase: String,
+        #[arg(long)]
+        path: Option<String>,
+    },
+    // HEAD까지의 원고 크기 변화(UTC 하루 단위, --week면 월요일 시작 주 단위)
+    Progress {
+        #[arg(long)]
+        week: bool,
+    },
+    // 암호화 저장소의 키를 바꾼다: NOVEL_PASSPHRASE(현재) -> NOVEL_NEW_PASSPHRASE(새 암호)
+    RotateKey,
+}
+
+pub fn run(root: &Path, command: VcsCommands) -> workspace_core::Result<()> {
+    let repo = workspace_core::Repository::open(root)?;
+
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV)
+        && repo.is_encrypted()?
+    {
+        repo.unlock_repo(&passphrase)?;
+    }
+
+    match command {
+        VcsCommands::Reflog => {
+            let entries = repo
+                .reflog()?
+                .into_iter()
+                .filter(|e| e.ref_name == "HEAD")
+                .collect::<Vec<_>>();
+
+            if entries.is_empty() {
+                println!("No HEAD movements recorded.");
+            }
+
+            for (n, entry) in entries.iter().enumerate() {
+                println!(
+                    "{} HEAD@{{{}}}: {}: {}",
+                    short(entry.new_node_id.as_deref()),
+                    n,
+                    entry.reason,
+                    entry.message
+                );
+            }
+        }
+        VcsCommands::Undo => {
+            let head = repo.undo_last_head_move()?;
+            println!("HEAD is now at {}", short(Some(&head)));
+        }
+        VcsCommands::Graph => {
+            let layout = repo.graph_layout()?;
+
+            if layout.rows.is_empty() {
+                println!("No snapshots yet.");
+            }
+
+            for line in render_graph(&layout) {
+                println!("{}", line.trim_end());
+            }
+        }
+        VcsCommands::RevParse { revision } => {
+            println!("{}", repo.resolve_revision(&revision)?);
+        }
+        VcsCommands::Checkout { revision } => {
+            repo.checkout(&revision)?;
+            let head = repo.repo_state()?.head;
+            println!("HEAD is now at {}", short(head.as_deref()));
+        }
+        VcsCommands::Status { revision } => {
+            let diff = repo.diff_workdir(revision.as_deref())?;
+
+            if diff.files.is_empty() {
+                println!("Working tree matches {}.", short(Some(&diff.from)));
+            }
+
+            for file in diff.files {
+                println!("{} {}", diff_mark(&file.kind), file.path);
+            }
+        }
+        VcsCommands::Diff {
+            from,
+            to,
+            file: Some(path),
+        } => match repo.diff_file(&from, &to, &path)? {
+            None => println!("No changes."),
+            Some(file) if file.is_binary => println!("Binary file {} differs.", file.path),
+            Some(file) => print!("{}", file.unified.unwrap_or_default()),
+        },
+        VcsCommands::Diff {
+            from,
+            to,
+            file: None,
+        } => {
+            let summary = repo.diff_summary(&from, &to)?;
+
+            if summary.files.is_empty() {
+                println!("No changes.");
+            }
+
+            for file in summary.files {
+                let mark = diff_mark(&file.kind);
+                if file.is_binary {
+                    println!("{} {} (binary)", mark, file.path);
+                } else {
+                    println!("{} {} ({:+} words)", mark, file.path, file.word_delta);
+                }
+            }
+        }
+        VcsCommands::Tag {
+            name,
+            revision,
+            delete,
+        } => match name {
+            Some(name) if delete => {
+                repo.tag_delete(&name)?;
+                println!("deleted tag {}", name);
+            }
+            Some(name) => {
+                let tag = repo.tag_create(&name, &revision)?;
+                println!("tagged {} as {}", short(Some(&tag.node_id)), tag.name);
+            }
+            None => {
+                for tag in repo.tags()? {
+                    println!("{} {}", short(Some(&tag.node_id)), tag.name);
+                }
+            }
+        },
+        VcsCommands::Search { query } => {
+            let hits = repo.search_history(&query)?;
+
+            if hits.is_empty() {
+                println!("No matches.");
+            }
+
+            for hit in hits {
+                let nodes = hit
+                    .nodes
+                    .iter()
+                    .map(|id| short(Some(id)))
+                    .collect::<Vec<_>>();
+                println!("{} ({})", hit.path, nodes.join(", "));
+                println!("    {}", hit.snippet);
+            }
+        }
+        VcsCommands::Pickaxe { phrase, path } => {
+            let hits = repo.pickaxe(&phrase, path.as_deref())?;
+
+            if hits.is_empty() {
+                println!("No matches.");
+            }
+
+            for hit in hits {
+                println!(
+                    "{} {} {} -> {}",
+                    short(Some(&hit.node_id)),
+                    hit.path,
+                    hit.before_count,
+                    hit.after_count
+                );
+            }
+        }
+        VcsCommands::Progress { week } => {
+            let bucket = if week {
+                workspace_core::ProgressBucket::Week
+            } else {
+                workspace_core::ProgressBucket::Day
+            };
+            let points = repo.progress_series(bucket)?;
+
+            if points.is_empty() {
+                println!("No snapshots.");
+            }
+
+            let mut previous = 0;
+            for point in points {
+                println!(
+                    "{} {} words ({:+}) {} chars {}",
+                    point.date,
+                    point.words,
+                    point.words - previous,
+                    point.chars,
+                    short(Some(&point.node_id))
+                );
+                previous = point.words;
+            }
+        }
+        VcsCommands::RotateKey => {
+            let (Ok(old), Ok(new)) = (
+                std::env::var(PASSPHRASE_ENV),
+                std::env::var(NEW_PASSPHRASE_ENV),
+            ) else {
+                return Err(workspace_core::WorkSpaceError::InvalidInput(format!(
+                    "set {} and {}",
+                    PASSPHRASE_ENV, NEW_PASSPHRASE_ENV
+                )));
+            };
+            repo.rotate_key(&old, &new)?;
+            println!("rotated encryption key");
+        }
+    }
+
+    Ok(())
+}
+
+// 열 하나를 두 글자로 그린다: 짝수 칸은 선/노드, 홀수 칸은 열 사이 사선
+fn render_graph(layout: &workspace_core::GraphLayout) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for row in &layout.rows {
+        let width = layout.lane_count * 2;
+        let lane = row.lane;
+
+        // 다른 열에서 이 노드로 모이는 선
+        if row.incoming.iter().any(|&i| i != lane) {
+            let mut cells = vertical_cells(width, &row.pass_through);
+            for &i in &row.incoming {
+                if i == lane {
+                    cells[2 * lane] = '|';
+                } else if i > lane {
+                    draw_run(&mut cells, 2 * lane + 1, 2 * i - 1, '/', true);
+                } else {
+                    draw_run(&mut cells, 2 * i + 1, 2 * lane - 1, '\\', false);
+                }
+            }
+            lines.push(cells.into_iter().collect());
+        }
+
+        let mut cells = vertical_cells(width, &row.pass_through);
+        cells[2 * lane] = '*';
+        lines.push(format!(
+            "{} {} {}",
+            cells.into_iter().collect::<String>().trim_end(),
+            short(Some(&row.node.id)),
+            row.node.message
+        ));
+
+        // 다른 열로 가는 부모 선
+        if row.parent_lanes.iter().any(|&p| p != lane) {
+            let mut cells = vertical_cells(width, &row.pass_through);
+            for &p in &row.parent_lanes {
+                if p == lane {
+                    cells[2 * lane] = '|';
+                } else if p > lane {
+                    draw_run(&mut cells, 2 * lane + 1, 2 * p - 1, '\\', false);
+                } else {
+                    draw_run(&mut cells, 2 * p + 1, 2 * lane - 1, '/', true);
+                }
+            }
+            lines.push(cells.into_iter().collect());
+        }
+    }
+
+    lines
+}
+
+fn vertical_cells(width: usize, pass_through: &[usize]) -> Vec<char> {
+    let mut cells = vec![' '; width];
+    for &i in pass_through {
+        cells[2 * i] = '|';
+    }
+    cells
+}
+
+// from..=to 구간을 '_'로 잇고, 사선은 오른쪽 끝(slash_at_end) 또는 왼쪽 끝에 놓는다.
+// 지나가는 세로선은 지우지 않는다
+fn draw_run(cells: &mut [char], from: usize, to: usize, slash: char, slash_at_end: bool) {
+    for (i, cell) in cells.iter_mut().enumerate().take(to + 1).skip(from) {
+        let is_slash = if slash_at_end { i == to } else { i == from };
+        if is_slash {
+            *cell = slash;
+        } else if *cell == ' ' {
+            *cell = '_';
+        }
+    }
+}
+
+fn diff_mark(kind: &workspace_core::DiffKind) -> &'static str {
+    match kind {
+        workspace_core::DiffKind::Added => "A",
+        workspace_core::DiffKind::Removed => "D",
+        workspace_core::DiffKind::Modified => "M",
+    }
+}
+
+pub fn short(id: Option<&str>) -> &str {
+    match id {
+        Some(id) => &id[..id.len().min(12)],
+        None => "(none)",
+    }
+}
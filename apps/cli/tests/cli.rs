@@ -6,6 +6,7 @@ use tempfile::tempdir;
 use diesel::prelude::*;
 use diesel::sql_query;
 use diesel::sqlite::SqliteConnection;
+use workspace_core::schema::nodes;
 
 fn run_in(dir: &Path, args: &[&str]) -> assert_cmd::assert::Assert {
     let mut cmd = cargo::cargo_bin_cmd!("novel-cli");
@@ -190,3 +191,248 @@ fn migrates_v1_db_on_first_run() {
             .and(predicate::str::contains("[ ] P0: 2 fresh task")),
     );
 }
+
+#[test]
+fn vcs_reflog_and_undo() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+
+    workspace_core::init_repo(root).unwrap();
+    std::fs::write(root.join("chapter.txt"), "first draft").unwrap();
+    let first = workspace_core::commit(root, "first").unwrap();
+    std::fs::write(root.join("chapter.txt"), "second draft").unwrap();
+    let second = workspace_core::commit(root, "second").unwrap();
+    workspace_core::checkout(root, &first).unwrap();
+
+    run_in(root, &["vcs", "reflog"]).success().stdout(
+        predicate::str::contains(format!(
+            "{} HEAD@{{0}}: checkout: moving from",
+            &first[..12]
+        ))
+        .and(predicate::str::contains(format!(
+            "{} HEAD@{{1}}: commit: second",
+            &second[..12]
+        ))),
+    );
+
+    run_in(root, &["vcs", "undo"])
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "HEAD is now at {}",
+            &second[..12]
+        )));
+
+    assert_eq!(
+        std::fs::read_to_string(root.join("chapter.txt")).unwrap(),
+        "second draft"
+    );
+}
+
+#[test]
+fn vcs_revisions_and_tags() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+
+    workspace_core::init_repo(root).unwrap();
+    std::fs::write(root.join("chapter.txt"), "first draft").unwrap();
+    let first = workspace_core::commit(root, "first").unwrap();
+    std::fs::write(root.join("chapter.txt"), "second draft").unwrap();
+    std::fs::write(root.join("notes.txt"), "notes").unwrap();
+    workspace_core::commit(root, "second").unwrap();
+
+    run_in(root, &["vcs", "rev-parse", "HEAD~1"])
+        .success()
+        .stdout(format!("{}\n", first));
+
+    run_in(root, &["vcs", "diff", "HEAD~1"]).success().stdout(
+        predicate::str::contains("M chapter.txt (+0 words)")
+            .and(predicate::str::contains("A notes.txt (+1 words)")),
+    );
+    std::fs::write(root.join("chapter.txt"), "third draft").unwrap();
+    std::fs::write(root.join("scratch.txt"), "idea").unwrap();
+    run_in(root, &["vcs", "status"]).success().stdout(
+        predicate::str::contains("M chapter.txt").and(predicate::str::contains("A scratch.txt")),
+    );
+    run_in(root, &["vcs", "diff", "HEAD~1", "--file", "chapter.txt"])
+        .success()
+        .stdout(
+            predicate::str::contains("-first draft").and(predicate::str::contains("+second draft")),
+        );
+
+    run_in(root, &["vcs", "tag", "draft-1", &first[..8]])
+        .success()
+        .stdout(predicate::str::contains("as draft-1"));
+    run_in(root, &["vcs", "tag"])
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "{} draft-1",
+            &first[..12]
+        )));
+
+    run_in(root, &["vcs", "checkout", "draft-1"])
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "HEAD is now at {}",
+            &first[..12]
+        )));
+    assert_eq!(
+        std::fs::read_to_string(root.join("chapter.txt")).unwrap(),
+        "first draft"
+    );
+
+    run_in(root, &["vcs", "rev-parse", "nope"])
+        .failure()
+        .stderr(predicate::str::contains("unknown revision: nope"));
+}
+
+#[test]
+fn vcs_unlocks_encrypted_repo_from_env() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+
+    workspace_core::init_repo_with_options(
+        root,
+        &workspace_core::InitOptions {
+            passphrase: Some("old secret".to_string()),
+        },
+    )
+    .unwrap();
+    std::fs::write(root.join("chapter.txt"), "first draft").unwrap();
+    workspace_core::commit(root, "first").unwrap();
+
+    run_in(root, &["vcs", "reflog"])
+        .failure()
+        .stderr(predicate::str::contains("repository is encrypted"));
+
+    let mut cmd = cargo::cargo_bin_cmd!("novel-cli");
+    cmd.current_dir(root)
+        .env("NOVEL_PASSPHRASE", "old secret")
+        .env("NOVEL_NEW_PASSPHRASE", "new secret")
+        .args(["vcs", "rotate-key"])
+        .assert()
+        .success();
+
+    let mut cmd = cargo::cargo_bin_cmd!("novel-cli");
+    cmd.current_dir(root)
+        .env("NOVEL_PASSPHRASE", "new secret")
+        .args(["vcs", "reflog"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("commit: first"));
+}
+
+#[test]
+fn vcs_search_finds_old_versions() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+
+    workspace_core::init_repo(root).unwrap();
+    std::fs::write(root.join("chapter.txt"), "그날 밤 등대가 꺼졌다.").unwrap();
+    let first = workspace_core::commit(root, "first").unwrap();
+    std::fs::write(root.join("chapter.txt"), "그날 밤은 조용했다.").unwrap();
+    workspace_core::commit(root, "second").unwrap();
+
+    run_in(root, &["vcs", "search", "등대"])
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "chapter.txt ({})",
+            &first[..12]
+        )))
+        .stdout(predicate::str::contains("그날 밤 등대가 꺼졌다."));
+
+    run_in(root, &["vcs", "search", "바다"])
+        .success()
+        .stdout(predicate::str::contains("No matches."));
+}
+
+#[test]
+fn vcs_pickaxe_finds_cut_sentence() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+
+    workspace_core::init_repo(root).unwrap();
+    std::fs::write(root.join("chapter.txt"), "그날 밤 등대가 꺼졌다.").unwrap();
+    workspace_core::commit(root, "first").unwrap();
+    std::fs::write(root.join("chapter.txt"), "그날 밤은 조용했다.").unwrap();
+    let cut = workspace_core::commit(root, "second").unwrap();
+
+    run_in(root, &["vcs", "pickaxe", "등대", "--path", "chapter.txt"])
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "{} chapter.txt 1 -> 0",
+            &cut[..12]
+        )));
+}
+
+#[test]
+fn vcs_progress_prints_daily_word_counts() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+
+    workspace_core::init_repo(root).unwrap();
+    std::fs::write(root.join("chapter.txt"), "그날 밤").unwrap();
+    let first = workspace_core::commit(root, "first").unwrap();
+    std::fs::write(root.join("chapter.txt"), "그날 밤 등대가 꺼졌다.").unwrap();
+    let second = workspace_core::commit(root, "second").unwrap();
+
+    // 2026-03-02, 2026-03-03 (UTC)
+    let db = root.join(".novel").join("vcs.db");
+    let mut conn = SqliteConnection::establish(db.to_str().unwrap()).unwrap();
+    for (id, created_at) in [(&first, 1_772_409_600_000i64), (&second, 1_772_496_000_000)] {
+        diesel::update(nodes::table.filter(nodes::id.eq(id)))
+            .set(nodes::created_at_unix_ms.eq(created_at))
+            .execute(&mut conn)
+            .unwrap();
+    }
+
+    run_in(root, &["vcs", "progress"])
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "2026-03-02 2 words (+2) 3 chars {}",
+            &first[..12]
+        )))
+        .stdout(predicate::str::contains(format!(
+            "2026-03-03 4 words (+2) 10 chars {}",
+            &second[..12]
+        )));
+}
+
+#[test]
+fn vcs_graph_draws_merges() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+
+    workspace_core::init_repo(root).unwrap();
+
+    // 머지 노드는 DB에 직접 넣는다: a <- b, a <- c, (b, c) <- m
+    let db = root.join(".novel").join("vcs.db");
+    let mut conn = SqliteConnection::establish(db.to_str().unwrap()).unwrap();
+    for (id, created_at, parents) in [
+        ("a", 1, vec![]),
+        ("b", 2, vec!["a"]),
+        ("c", 3, vec!["a"]),
+        ("m", 4, vec!["b", "c"]),
+    ] {
+        sql_query(format!(
+            "INSERT INTO nodes (id, message, created_at_unix_ms) VALUES ('{id}', 'node {id}', {created_at})"
+        ))
+        .execute(&mut conn)
+        .unwrap();
+        for (ord, parent) in parents.iter().enumerate() {
+            sql_query(format!(
+                "INSERT INTO node_parents (node_id, parent_id, ord) VALUES ('{id}', '{parent}', {ord})"
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        }
+    }
+
+    run_in(root, &["vcs", "graph"]).success().stdout(
+        "* m node m\n\
+         |\\\n\
+         | * c node c\n\
+         * | b node b\n\
+         |/\n\
+         * a node a\n",
+    );
+}
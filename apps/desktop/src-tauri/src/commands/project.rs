@@ -1,10 +1,11 @@
 use std::{fs, path::Path};
 
 use tauri::{AppHandle, Manager};
+use workspace_core::WorkSpaceError;
 
 #[tauri::command]
-pub fn open_project(root: String) -> Result<workspace_core::ProjectInfo, String> {
-    workspace_core::open_project(Path::new(&root)).map_err(|e| e.to_string())
+pub fn open_project(root: String) -> Result<workspace_core::ProjectInfo, WorkSpaceError> {
+    workspace_core::open_project(Path::new(&root))
 }
 
 #[tauri::command]
@@ -1,35 +1,655 @@
-use std::path::Path;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
 
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+// 에러는 { code, message } 객체로 직렬화돼 프론트엔드에 전달된다
+use workspace_core::{CancelToken, Progress, ProgressEvent, Repository, WorkSpaceError};
+
+// 오래 걸리는 작업(commit, checkout, gc, bundle)의 진행 상황 이벤트 이름
+const PROGRESS_EVENT: &str = "vcs-progress";
+
+// 진행 중인 작업의 취소 토큰. 키는 프론트엔드가 정한 operation_id
+#[derive(Default)]
+pub struct Operations(Mutex<HashMap<String, CancelToken>>);
+
+#[derive(Clone, Serialize)]
+struct ProgressPayload {
+    operation_id: String,
+    #[serde(flatten)]
+    event: ProgressEvent,
+}
+
+// operation_id가 있으면 진행 상황을 이벤트로 보내고 cancel_operation으로 취소할 수 있게 등록한다.
+// 작업은 블로킹 스레드에서 돌려 그동안 다른 커맨드(취소 포함)가 처리되게 한다.
+// 저장소 연결은 하나를 나눠 쓰고 다른 작업이 쓰는 동안 기다리므로, 저장소를 쓰는 커맨드는 모두 이것을 거친다
+async fn run_with_progress<T, F>(
+    app: AppHandle,
+    operation_id: Option<String>,
+    op: F,
+) -> Result<T, WorkSpaceError>
+where
+    T: Send + 'static,
+    F: FnOnce(&Progress) -> Result<T, WorkSpaceError> + Send + 'static,
+{
+    let progress = match &operation_id {
+        Some(id) => {
+            let token = CancelToken::new();
+            app.state::<Operations>()
+                .0
+                .lock()
+                .unwrap()
+                .insert(id.clone(), token.clone());
+
+            let emitter = app.clone();
+            let id = id.clone();
+            Progress::new(
+                move |event: &ProgressEvent| {
+                    let _ = emitter.emit(
+                        PROGRESS_EVENT,
+                        ProgressPayload {
+                            operation_id: id.clone(),
+                            event: event.clone(),
+                        },
+                    );
+                },
+                token,
+            )
+        }
+        None => Progress::default(),
+    };
+
+    let result = tauri::async_runtime::spawn_blocking(move || op(&progress))
+        .await
+        .map_err(|e| WorkSpaceError::Io(std::io::Error::other(e.to_string())));
+
+    if let Some(id) = &operation_id {
+        app.state::<Operations>().0.lock().unwrap().remove(id);
+    }
+    result?
+}
+
+// 프로젝트(canonical 루트)별 저장소 핸들. 처음 쓸 때 열고(마이그레이션 포함) 이후 연결을 다시 쓴다
+#[derive(Default)]
+pub struct Repositories(Mutex<HashMap<PathBuf, Arc<Repository>>>);
+
+fn repository(app: &AppHandle, root: &str) -> Result<Arc<Repository>, WorkSpaceError> {
+    let root = Path::new(root).canonicalize()?;
+    let repositories = app.state::<Repositories>();
+    let mut repositories = repositories.0.lock().unwrap();
+
+    if let Some(repo) = repositories.get(&root) {
+        return Ok(repo.clone());
+    }
+    let repo = Arc::new(Repository::open(&root)?);
+    repositories.insert(root, repo.clone());
+
+    Ok(repo)
+}
+
+// 이미 끝났거나 없는 작업이면 false
 #[tauri::command]
-pub fn init_repo(root: String) -> Result<(), String> {
-    workspace_core::init_repo(Path::new(&root)).map_err(|e| e.to_string())
+pub fn cancel_operation(app: AppHandle, operation_id: String) -> bool {
+    let operations = app.state::<Operations>();
+    let tokens = operations.0.lock().unwrap();
+    match tokens.get(&operation_id) {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
 }
 
+// passphrase가 있으면 암호화 저장소로 만든다(이미 커밋이 있으면 그 내용도 암호화)
 #[tauri::command]
-pub fn commit(root: String, message: String) -> Result<String, String> {
-    workspace_core::commit(Path::new(&root), &message).map_err(|e| e.to_string())
+pub fn init_repo(
+    app: AppHandle,
+    root: String,
+    passphrase: Option<String>,
+) -> Result<(), WorkSpaceError> {
+    let repo = Repository::init_with_options(
+        Path::new(&root),
+        &workspace_core::InitOptions { passphrase },
+    )?;
+    app.state::<Repositories>()
+        .0
+        .lock()
+        .unwrap()
+        .insert(repo.root().to_path_buf(), Arc::new(repo));
+
+    Ok(())
 }
 
 #[tauri::command]
-pub fn log(root: String) -> Result<Vec<workspace_core::VersionNode>, String> {
-    workspace_core::log(Path::new(&root)).map_err(|e| e.to_string())
+pub async fn is_encrypted(app: AppHandle, root: String) -> Result<bool, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.is_encrypted()).await
 }
 
+// 키는 앱이 끝나거나 forget_repo_key를 부를 때까지 기억된다
 #[tauri::command]
-pub fn repo_state(root: String) -> Result<workspace_core::RepoState, String> {
-    workspace_core::repo_state(Path::new(&root)).map_err(|e| e.to_string())
+pub async fn unlock_repo(
+    app: AppHandle,
+    root: String,
+    passphrase: String,
+) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.unlock_repo(&passphrase)).await
 }
 
 #[tauri::command]
-pub fn checkout(root: String, node_id: String) -> Result<(), String> {
-    workspace_core::checkout(Path::new(&root), &node_id).map_err(|e| e.to_string())
+pub async fn forget_repo_key(app: AppHandle, root: String) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.forget_repo_key()).await
 }
 
 #[tauri::command]
-pub fn diff_nodes(
+pub async fn rotate_key(
+    app: AppHandle,
+    root: String,
+    old_passphrase: String,
+    new_passphrase: String,
+) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| {
+        repo.rotate_key(&old_passphrase, &new_passphrase)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn commit(
+    app: AppHandle,
+    root: String,
+    message: String,
+    allow_empty: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<String, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    let options = workspace_core::CommitOptions {
+        allow_empty: allow_empty.unwrap_or(false),
+    };
+    run_with_progress(app, operation_id, move |progress| {
+        repo.commit_with_progress(&message, options, progress)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn stage(app: AppHandle, root: String, paths: Vec<String>) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| {
+        let paths = paths.iter().map(String::as_str).collect::<Vec<_>>();
+        repo.stage(&paths)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn unstage(
+    app: AppHandle,
+    root: String,
+    paths: Vec<String>,
+) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| {
+        let paths = paths.iter().map(String::as_str).collect::<Vec<_>>();
+        repo.unstage(&paths)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn staged(
+    app: AppHandle,
+    root: String,
+) -> Result<Vec<workspace_core::StagedFile>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.staged()).await
+}
+
+#[tauri::command]
+pub async fn commit_staged(
+    app: AppHandle,
+    root: String,
+    message: String,
+) -> Result<String, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.commit_staged(&message)).await
+}
+
+#[tauri::command]
+pub async fn log(
+    app: AppHandle,
+    root: String,
+) -> Result<Vec<workspace_core::VersionNode>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.log()).await
+}
+
+#[tauri::command]
+pub async fn node_notes(
+    app: AppHandle,
+    root: String,
+    node_id: String,
+) -> Result<Vec<workspace_core::NodeNote>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.node_notes(&node_id)).await
+}
+
+#[tauri::command]
+pub async fn note_add(
+    app: AppHandle,
+    root: String,
+    revision: String,
+    text: String,
+) -> Result<workspace_core::NodeNote, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.note_add(&revision, &text)).await
+}
+
+#[tauri::command]
+pub async fn note_edit(
+    app: AppHandle,
+    root: String,
+    note_id: i32,
+    text: String,
+) -> Result<workspace_core::NodeNote, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.note_edit(note_id, &text)).await
+}
+
+#[tauri::command]
+pub async fn note_remove(app: AppHandle, root: String, note_id: i32) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.note_remove(note_id)).await
+}
+
+// 처음 검색할 때 큰 파일을 색인하느라 오래 걸릴 수 있다
+#[tauri::command]
+pub async fn search_history(
+    app: AppHandle,
+    root: String,
+    query: String,
+) -> Result<Vec<workspace_core::SearchHit>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.search_history(&query)).await
+}
+
+// 전체 기록의 바뀐 파일을 읽으므로 블로킹 스레드에서 돌린다
+#[tauri::command]
+pub async fn pickaxe(
+    app: AppHandle,
+    root: String,
+    phrase: String,
+    path_filter: Option<String>,
+) -> Result<Vec<workspace_core::PickaxeHit>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| {
+        repo.pickaxe(&phrase, path_filter.as_deref())
+    })
+    .await
+}
+
+// 통계가 없는 오래된 노드는 처음 한 번 스냅샷을 다시 읽는다
+#[tauri::command]
+pub async fn progress_series(
+    app: AppHandle,
+    root: String,
+    bucket: workspace_core::ProgressBucket,
+) -> Result<Vec<workspace_core::ProgressPoint>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.progress_series(bucket)).await
+}
+
+#[tauri::command]
+pub async fn graph_layout(
+    app: AppHandle,
+    root: String,
+) -> Result<workspace_core::GraphLayout, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.graph_layout()).await
+}
+
+#[tauri::command]
+pub async fn repo_state(
+    app: AppHandle,
+    root: String,
+) -> Result<workspace_core::RepoState, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.repo_state()).await
+}
+
+#[tauri::command]
+pub async fn checkout(
+    app: AppHandle,
+    root: String,
+    node_id: String,
+    operation_id: Option<String>,
+) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, operation_id, move |progress| {
+        repo.checkout_with_progress(&node_id, progress)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn diff_nodes(
+    app: AppHandle,
     root: String,
     from: String,
     to: String,
-) -> Result<workspace_core::NodeDiff, String> {
-    workspace_core::diff_nodes(Path::new(&root), &from, &to).map_err(|e| e.to_string())
+) -> Result<workspace_core::NodeDiff, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.diff_nodes(&from, &to)).await
+}
+
+#[tauri::command]
+pub async fn diff_workdir(
+    app: AppHandle,
+    root: String,
+    revision: Option<String>,
+) -> Result<workspace_core::NodeDiff, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.diff_workdir(revision.as_deref())).await
+}
+
+#[tauri::command]
+pub async fn diff_summary(
+    app: AppHandle,
+    root: String,
+    from: String,
+    to: String,
+) -> Result<workspace_core::DiffSummary, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.diff_summary(&from, &to)).await
+}
+
+#[tauri::command]
+pub async fn diff_file(
+    app: AppHandle,
+    root: String,
+    from: String,
+    to: String,
+    path: String,
+) -> Result<Option<workspace_core::FileDiff>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.diff_file(&from, &to, &path)).await
+}
+
+#[tauri::command]
+pub async fn bundle_create(
+    app: AppHandle,
+    root: String,
+    out_path: String,
+    refs: Vec<String>,
+    base: Option<String>,
+    operation_id: Option<String>,
+) -> Result<workspace_core::BundleSummary, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, operation_id, move |progress| {
+        let refs = refs.iter().map(String::as_str).collect::<Vec<_>>();
+        repo.bundle_create_with_progress(Path::new(&out_path), &refs, base.as_deref(), progress)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn bundle_unbundle(
+    app: AppHandle,
+    root: String,
+    bundle_path: String,
+    operation_id: Option<String>,
+) -> Result<workspace_core::UnbundleSummary, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, operation_id, move |progress| {
+        repo.bundle_unbundle_with_progress(Path::new(&bundle_path), progress)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn remote_add(
+    app: AppHandle,
+    root: String,
+    name: String,
+    url: String,
+) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.remote_add(&name, &url)).await
+}
+
+#[tauri::command]
+pub async fn remote_remove(
+    app: AppHandle,
+    root: String,
+    name: String,
+) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.remote_remove(&name)).await
+}
+
+#[tauri::command]
+pub async fn remotes(
+    app: AppHandle,
+    root: String,
+) -> Result<Vec<workspace_core::Remote>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.remotes()).await
+}
+
+// 네트워크나 다른 저장소를 오가므로 블로킹 스레드에서 돌린다
+#[tauri::command]
+pub async fn fetch(
+    app: AppHandle,
+    root: String,
+    remote: String,
+) -> Result<workspace_core::FetchSummary, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.fetch(&remote)).await
+}
+
+#[tauri::command]
+pub async fn push(
+    app: AppHandle,
+    root: String,
+    remote: String,
+    force: bool,
+) -> Result<workspace_core::PushSummary, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.push(&remote, force)).await
+}
+
+// fetch 뒤에 작업 트리까지 바꿀 수 있다
+#[tauri::command]
+pub async fn pull(
+    app: AppHandle,
+    root: String,
+    remote: String,
+) -> Result<workspace_core::PullSummary, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.pull(&remote)).await
+}
+
+#[tauri::command]
+pub async fn remote_set_token(
+    app: AppHandle,
+    root: String,
+    name: String,
+    token: Option<String>,
+) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| {
+        repo.remote_set_token(&name, token.as_deref())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn reflog(
+    app: AppHandle,
+    root: String,
+) -> Result<Vec<workspace_core::ReflogEntry>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.reflog()).await
+}
+
+#[tauri::command]
+pub async fn undo_last_head_move(app: AppHandle, root: String) -> Result<String, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.undo_last_head_move()).await
+}
+
+#[tauri::command]
+pub async fn resolve_revision(
+    app: AppHandle,
+    root: String,
+    revision: String,
+) -> Result<String, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.resolve_revision(&revision)).await
+}
+
+#[tauri::command]
+pub async fn tag_create(
+    app: AppHandle,
+    root: String,
+    name: String,
+    revision: String,
+) -> Result<workspace_core::Tag, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.tag_create(&name, &revision)).await
+}
+
+#[tauri::command]
+pub async fn tag_delete(app: AppHandle, root: String, name: String) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.tag_delete(&name)).await
+}
+
+#[tauri::command]
+pub async fn tags(
+    app: AppHandle,
+    root: String,
+) -> Result<Vec<workspace_core::Tag>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.tags()).await
+}
+
+#[tauri::command]
+pub async fn merge_base(
+    app: AppHandle,
+    root: String,
+    a: String,
+    b: String,
+) -> Result<Option<String>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.merge_base(&a, &b)).await
+}
+
+#[tauri::command]
+pub async fn is_ancestor(
+    app: AppHandle,
+    root: String,
+    ancestor: String,
+    node: String,
+) -> Result<bool, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.is_ancestor(&ancestor, &node)).await
+}
+
+#[tauri::command]
+pub async fn ahead_behind(
+    app: AppHandle,
+    root: String,
+    a: String,
+    b: String,
+) -> Result<workspace_core::AheadBehind, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.ahead_behind(&a, &b)).await
+}
+
+#[tauri::command]
+pub async fn descendants(
+    app: AppHandle,
+    root: String,
+    node: String,
+) -> Result<Vec<String>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.descendants(&node)).await
+}
+
+#[tauri::command]
+pub async fn autosave(
+    app: AppHandle,
+    root: String,
+    policy: Option<workspace_core::AutosavePolicy>,
+) -> Result<Option<String>, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| {
+        repo.autosave(&policy.unwrap_or_default())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn thin_autosaves(
+    app: AppHandle,
+    root: String,
+) -> Result<workspace_core::ThinSummary, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.thin_autosaves()).await
+}
+
+#[tauri::command]
+pub async fn gc(
+    app: AppHandle,
+    root: String,
+    operation_id: Option<String>,
+) -> Result<workspace_core::GcSummary, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, operation_id, move |progress| {
+        repo.gc_with_progress(progress)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn plaintext_export_allowed(
+    app: AppHandle,
+    root: String,
+) -> Result<bool, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.plaintext_export_allowed()).await
+}
+
+#[tauri::command]
+pub async fn set_plaintext_export(
+    app: AppHandle,
+    root: String,
+    allowed: bool,
+) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.set_plaintext_export(allowed)).await
+}
+
+#[tauri::command]
+pub async fn large_blob_threshold(app: AppHandle, root: String) -> Result<u64, WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.large_blob_threshold()).await
+}
+
+#[tauri::command]
+pub async fn set_large_blob_threshold(
+    app: AppHandle,
+    root: String,
+    bytes: u64,
+) -> Result<(), WorkSpaceError> {
+    let repo = repository(&app, &root)?;
+    run_with_progress(app, None, move |_| repo.set_large_blob_threshold(bytes)).await
 }
@@ -47,6 +47,8 @@ fn list_notes(
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .manage(commands::vcs::Operations::default())
+        .manage(commands::vcs::Repositories::default())
         .invoke_handler(tauri::generate_handler![
             add_note,
             list_notes,
@@ -57,11 +59,57 @@ pub fn run() {
             commands::file::write_file,
             commands::file::create_file,
             commands::vcs::init_repo,
+            commands::vcs::is_encrypted,
+            commands::vcs::unlock_repo,
+            commands::vcs::forget_repo_key,
+            commands::vcs::rotate_key,
             commands::vcs::commit,
+            commands::vcs::stage,
+            commands::vcs::unstage,
+            commands::vcs::staged,
+            commands::vcs::commit_staged,
             commands::vcs::log,
+            commands::vcs::node_notes,
+            commands::vcs::note_add,
+            commands::vcs::note_edit,
+            commands::vcs::note_remove,
+            commands::vcs::search_history,
+            commands::vcs::pickaxe,
+            commands::vcs::progress_series,
+            commands::vcs::graph_layout,
             commands::vcs::repo_state,
             commands::vcs::checkout,
             commands::vcs::diff_nodes,
+            commands::vcs::diff_workdir,
+            commands::vcs::diff_summary,
+            commands::vcs::diff_file,
+            commands::vcs::bundle_create,
+            commands::vcs::bundle_unbundle,
+            commands::vcs::remote_add,
+            commands::vcs::remote_remove,
+            commands::vcs::remote_set_token,
+            commands::vcs::remotes,
+            commands::vcs::fetch,
+            commands::vcs::push,
+            commands::vcs::pull,
+            commands::vcs::reflog,
+            commands::vcs::undo_last_head_move,
+            commands::vcs::resolve_revision,
+            commands::vcs::tag_create,
+            commands::vcs::tag_delete,
+            commands::vcs::tags,
+            commands::vcs::merge_base,
+            commands::vcs::is_ancestor,
+            commands::vcs::ahead_behind,
+            commands::vcs::descendants,
+            commands::vcs::autosave,
+            commands::vcs::thin_autosaves,
+            commands::vcs::gc,
+            commands::vcs::plaintext_export_allowed,
+            commands::vcs::set_plaintext_export,
+            commands::vcs::large_blob_threshold,
+            commands::vcs::set_large_blob_threshold,
+            commands::vcs::cancel_operation,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
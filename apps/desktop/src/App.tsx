@@ -6,17 +6,35 @@ import { HistoryPanel } from "./components/layout/HistoryPanel";
 import { StatusBar } from "./components/layout/StatusBar";
 import { defaultWorkspaceRoot, openProject } from "./lib/api/projectApi";
 import { createFile, listFiles, readFile, writeFile } from "./lib/api/fileApi";
+import { errorMessage, hasErrorCode } from "./lib/api/errors";
 import {
+    autosave,
+    cancelOperation,
     checkoutSnapshot,
     commitSnapshot,
-    diffNodes,
-    fetchLog,
+    diffFile,
+    diffSummary,
+    fetchGraphLayout,
     fetchRepoState,
     initRepo,
+    newOperationId,
+    noteAdd,
+    onVcsProgress,
+    unlockRepo,
 } from "./lib/api/vcsApi";
 import type { ProjectInfo } from "./lib/api/projectApi";
 import type { FileEntry } from "./lib/api/fileApi";
-import type { NodeDiff, RepoState, VersionNode } from "./lib/api/vcsApi";
+import type {
+    DiffSummary,
+    GraphLayout,
+    NodeDiff,
+    ProgressEvent,
+    RepoState,
+    VersionNode,
+} from "./lib/api/vcsApi";
+
+// 자동 저장을 시도하는 주기. 실제 저장 간격은 AutosavePolicy.interval_ms가 정한다
+const AUTOSAVE_CHECK_MS = 60 * 1000;
 
 type CursorPosition = {
     line: number;
@@ -63,12 +81,16 @@ export default function App() {
     const [sidebarOpen, setSidebarOpen] = useState(true);
     const [cursor, setCursor] = useState<CursorPosition>({ line: 1, col: 1 });
     const [vcsState, setVcsState] = useState<RepoState | null>(null);
-    const [logNodes, setLogNodes] = useState<VersionNode[]>([]);
+    const [graphLayout, setGraphLayout] = useState<GraphLayout | null>(null);
     const [commitMessage, setCommitMessage] = useState("");
     const [vcsBusy, setVcsBusy] = useState(false);
+    // 진행 중인 commit/checkout의 operationId와 마지막으로 받은 진행 이벤트
+    const [operationId, setOperationId] = useState("");
+    const [progress, setProgress] = useState<ProgressEvent | null>(null);
     const [expandedNodeId, setExpandedNodeId] = useState("");
     const [loadingNodeId, setLoadingNodeId] = useState("");
-    const [nodeDiffCache, setNodeDiffCache] = useState<Record<string, NodeDiff>>({});
+    // 펼친 노드는 요약만 받고, 파일 내용 diff는 파일을 고를 때 받는다
+    const [nodeDiffCache, setNodeDiffCache] = useState<Record<string, DiffSummary>>({});
     const [diffResult, setDiffResult] = useState<NodeDiff | null>(null);
     const [selectedDiffPath, setSelectedDiffPath] = useState("");
 
@@ -106,11 +128,41 @@ export default function App() {
         return list;
     };
 
+    const loadVcs = async (rootPath: string) => {
+        const [state, layout] = await Promise.all([
+            fetchRepoState(rootPath),
+            fetchGraphLayout(rootPath),
+        ]);
+        setVcsState(state);
+        setGraphLayout(layout);
+    };
+
+    // 틀리면 다시 묻는다. 취소하면 locked 에러를 그대로 던진다
+    const promptUnlock = async (rootPath: string, locked: unknown) => {
+        for (;;) {
+            const passphrase = window.prompt(
+                "This project is encrypted. Enter its passphrase:",
+            );
+            if (passphrase === null) throw locked;
+            try {
+                await unlockRepo(rootPath, passphrase);
+                return;
+            } catch (e) {
+                if (!hasErrorCode(e, "wrong_passphrase")) throw e;
+            }
+        }
+    };
+
     const refreshVcs = async (rootPath: string) => {
         await initRepo(rootPath);
-        const [state, nodes] = await Promise.all([fetchRepoState(rootPath), fetchLog(rootPath)]);
-        setVcsState(state);
-        setLogNodes(nodes);
+        try {
+            await loadVcs(rootPath);
+        } catch (e) {
+            // 암호화 저장소는 세션마다 한 번 암호를 받아 푼다
+            if (!hasErrorCode(e, "key_required")) throw e;
+            await promptUnlock(rootPath, e);
+            await loadVcs(rootPath);
+        }
     };
 
     const loadProject = async (rootPath: string) => {
@@ -133,7 +185,7 @@ export default function App() {
                 }
             } catch (e) {
                 if (!cancelled) {
-                    setError(String(e));
+                    setError(errorMessage(e));
                 }
             }
         };
@@ -145,6 +197,53 @@ export default function App() {
         };
     }, []);
 
+    useEffect(() => {
+        const unlisten = onVcsProgress(setProgress);
+        return () => {
+            void unlisten.then((off) => off());
+        };
+    }, []);
+
+    // 오래 걸리는 VCS 작업에 operationId를 붙여 진행 상황을 받고, 끝나면 지운다
+    const runTracked = async <T,>(task: (id: string) => Promise<T>) => {
+        const id = newOperationId();
+        setOperationId(id);
+        setProgress(null);
+        try {
+            return await task(id);
+        } finally {
+            setOperationId("");
+            setProgress(null);
+        }
+    };
+
+    const onCancelOperation = () => {
+        if (operationId) {
+            void cancelOperation(operationId);
+        }
+    };
+
+    // 주기적으로 자동 저장을 시도한다. 간격/변경 여부 판단은 코어 정책이 한다
+    const projectRoot = project?.root;
+    useEffect(() => {
+        if (!projectRoot) return;
+
+        const timer = window.setInterval(async () => {
+            try {
+                const saved = await autosave(projectRoot);
+                if (saved) {
+                    await refreshVcs(projectRoot);
+                }
+            } catch (e) {
+                // CLI 등이 저장소를 잡고 있으면 다음 주기에 다시 시도한다
+                if (hasErrorCode(e, "repo_locked")) return;
+                setError(errorMessage(e));
+            }
+        }, AUTOSAVE_CHECK_MS);
+
+        return () => window.clearInterval(timer);
+    }, [projectRoot]);
+
     const onUseIsolatedRoot = async () => {
         setError("");
         try {
@@ -152,7 +251,7 @@ export default function App() {
             setInputRoot(root);
             await loadProject(root);
         } catch (e) {
-            setError(String(e));
+            setError(errorMessage(e));
         }
     };
 
@@ -161,7 +260,7 @@ export default function App() {
         try {
             await loadProject(inputRoot);
         } catch (e) {
-            setError(String(e));
+            setError(errorMessage(e));
         }
     };
 
@@ -171,7 +270,7 @@ export default function App() {
         try {
             await refreshWorkspaceFiles(project.root);
         } catch (e) {
-            setError(String(e));
+            setError(errorMessage(e));
         }
     };
 
@@ -183,7 +282,7 @@ export default function App() {
             await refreshVcs(project.root);
             clearHistoryDiffState();
         } catch (e) {
-            setError(String(e));
+            setError(errorMessage(e));
         } finally {
             setVcsBusy(false);
         }
@@ -204,7 +303,7 @@ export default function App() {
             setDiffResult(null);
             setSelectedDiffPath("");
         } catch (e) {
-            setError(String(e));
+            setError(errorMessage(e));
         }
     };
 
@@ -218,7 +317,7 @@ export default function App() {
             setContent(persisted);
             setSavedContent(persisted);
         } catch (e) {
-            setError(String(e));
+            setError(errorMessage(e));
         }
     };
 
@@ -250,7 +349,7 @@ export default function App() {
             setOpenTabs((prev) => (prev.includes(createdPath) ? prev : [...prev, createdPath]));
             setSidebarOpen(false);
         } catch (e) {
-            setError(String(e));
+            setError(errorMessage(e));
         }
     };
 
@@ -269,18 +368,39 @@ export default function App() {
                 await onSave();
             }
 
-            await commitSnapshot(project.root, message);
+            await runTracked((id) =>
+                commitSnapshot(project.root, message, false, id),
+            );
             setCommitMessage("");
             await refreshVcs(project.root);
             await refreshWorkspaceFiles(project.root);
             clearHistoryDiffState();
         } catch (e) {
-            setError(String(e));
+            // 사용자가 취소했으면 아무것도 쓰이지 않았으므로 알릴 필요가 없다
+            if (!hasErrorCode(e, "cancelled")) {
+                setError(errorMessage(e));
+            }
         } finally {
             setVcsBusy(false);
         }
     };
 
+    // 베타 리더 피드백처럼 나중에 받은 메모를 노드에 붙인다. 노드 id는 그대로다
+    const onAddNote = async (nodeId: string) => {
+        if (!project) return;
+
+        const text = window.prompt("Note for this snapshot:");
+        if (!text?.trim()) return;
+
+        setError("");
+        try {
+            await noteAdd(project.root, nodeId, text);
+            await refreshVcs(project.root);
+        } catch (e) {
+            setError(errorMessage(e));
+        }
+    };
+
     const onCheckoutSnapshot = async (nodeId: string) => {
         if (!project) return;
 
@@ -294,7 +414,9 @@ export default function App() {
         setError("");
         setVcsBusy(true);
         try {
-            await checkoutSnapshot(project.root, nodeId);
+            await runTracked((id) =>
+                checkoutSnapshot(project.root, nodeId, id),
+            );
             const list = await refreshWorkspaceFiles(project.root);
 
             const fileSet = new Set(list.filter((f) => !f.is_dir).map((f) => f.path));
@@ -310,7 +432,9 @@ export default function App() {
             await refreshVcs(project.root);
             clearHistoryDiffState();
         } catch (e) {
-            setError(String(e));
+            if (!hasErrorCode(e, "cancelled")) {
+                setError(errorMessage(e));
+            }
         } finally {
             setVcsBusy(false);
         }
@@ -342,21 +466,27 @@ export default function App() {
         setError("");
         setLoadingNodeId(node.id);
         try {
-            const result = await diffNodes(project.root, previousNodeId, node.id);
+            const result = await diffSummary(project.root, previousNodeId, node.id);
             setNodeDiffCache((prev) => ({ ...prev, [node.id]: result }));
         } catch (e) {
-            setError(String(e));
+            setError(errorMessage(e));
         } finally {
             setLoadingNodeId((prev) => (prev === node.id ? "" : prev));
         }
     };
 
-    const onSelectExpandedDiffFile = (nodeId: string, path: string) => {
-        const result = nodeDiffCache[nodeId];
-        if (!result) return;
+    const onSelectExpandedDiffFile = async (nodeId: string, path: string) => {
+        const summary = nodeDiffCache[nodeId];
+        if (!project || !summary) return;
 
-        setDiffResult(result);
-        setSelectedDiffPath(path);
+        setError("");
+        try {
+            const file = await diffFile(project.root, summary.from, summary.to, path);
+            setDiffResult({ from: summary.from, to: summary.to, files: file ? [file] : [] });
+            setSelectedDiffPath(path);
+        } catch (e) {
+            setError(errorMessage(e));
+        }
     };
 
     const onClearDiff = () => {
@@ -445,7 +575,7 @@ export default function App() {
                 {activeView === "history" ? (
                     <HistoryPanel
                         vcsState={vcsState}
-                        logNodes={logNodes}
+                        graphLayout={graphLayout}
                         commitMessage={commitMessage}
                         expandedNodeId={expandedNodeId}
                         expandedDiff={expandedDiff}
@@ -458,6 +588,7 @@ export default function App() {
                         onCommitSnapshot={onCommitSnapshot}
                         onRefreshVcs={onRefreshVcs}
                         onCheckoutSnapshot={onCheckoutSnapshot}
+                        onAddNote={onAddNote}
                         hasProject={project !== null}
                         vcsBusy={vcsBusy}
                     />
@@ -507,8 +638,12 @@ export default function App() {
                 cursor={cursor}
                 language={language}
                 canSave={selectedPath !== ""}
+                progress={
+                    progress?.operation_id === operationId ? progress : null
+                }
                 onRefreshFiles={onRefreshFiles}
                 onSave={onSave}
+                onCancelProgress={onCancelOperation}
             />
 
             {!!error && (
@@ -1,23 +1,29 @@
 import { useEffect, useMemo, useRef, useState } from "react";
 
 import { Button, Textarea } from "../ui";
-import type { NodeDiff, RepoState, VersionNode } from "../../lib/api/vcsApi";
+import type {
+    DiffSummary,
+    GraphLayout,
+    RepoState,
+    VersionNode,
+} from "../../lib/api/vcsApi";
 
 type HistoryPanelProps = {
     vcsState: RepoState | null;
-    logNodes: VersionNode[];
+    graphLayout: GraphLayout | null;
     commitMessage: string;
     expandedNodeId: string;
-    expandedDiff: NodeDiff | null;
+    expandedDiff: DiffSummary | null;
     loadingNodeId: string;
     selectedDiffPath: string;
     activeDiffToNodeId: string;
     onCommitMessageChange: (value: string) => void;
     onToggleNodeExpand: (node: VersionNode) => void | Promise<void>;
-    onSelectExpandedDiffFile: (nodeId: string, path: string) => void;
+    onSelectExpandedDiffFile: (nodeId: string, path: string) => void | Promise<void>;
     onCommitSnapshot: () => void | Promise<void>;
     onRefreshVcs: () => void | Promise<void>;
     onCheckoutSnapshot: (nodeId: string) => void | Promise<void>;
+    onAddNote: (nodeId: string) => void | Promise<void>;
     hasProject: boolean;
     vcsBusy: boolean;
 };
@@ -40,9 +46,10 @@ const EXPAND_EMPTY_HEIGHT_PX = 30;
 const EXPAND_LIST_ROW_HEIGHT_PX = 28;
 const EXPAND_LIST_VERTICAL_PADDING_PX = 4;
 const EXPAND_LIST_MAX_HEIGHT_PX = 180;
+const EXPAND_NOTE_ROW_HEIGHT_PX = 24;
 
 const CONTEXT_MENU_WIDTH_PX = 180;
-const CONTEXT_MENU_HEIGHT_PX = 40;
+const CONTEXT_MENU_HEIGHT_PX = 72;
 const CONTEXT_MENU_VIEWPORT_MARGIN_PX = 8;
 
 const COMMIT_ID_SHORT_LEN = 8;
@@ -51,78 +58,13 @@ function formatNodeTime(unixMs: number): string {
     return new Date(unixMs).toLocaleString();
 }
 
-type GraphRow = {
-    node: VersionNode;
-    lane: number;
-    parentLanes: number[];
-    laneCount: number;
-};
-
-function buildGraphRows(nodes: VersionNode[]): {
-    rows: GraphRow[];
-    maxLanes: number;
-} {
-    const rows: GraphRow[] = [];
-    let laneHeads: string[] = [];
-    let maxLanes = 1;
-
-    for (const node of nodes) {
-        let lane = laneHeads.indexOf(node.id);
-        if (lane === -1) {
-            laneHeads.push(node.id);
-            lane = laneHeads.length - 1;
-        }
-
-        const beforeCount = laneHeads.length;
-        let nextLaneHeads = [...laneHeads];
-
-        if (node.parents.length > 0) {
-            nextLaneHeads[lane] = node.parents[0];
-        } else {
-            nextLaneHeads.splice(lane, 1);
-        }
-
-        for (const parentId of node.parents.slice(1)) {
-            if (!nextLaneHeads.includes(parentId)) {
-                const insertAt = Math.min(lane + 1, nextLaneHeads.length);
-                nextLaneHeads.splice(insertAt, 0, parentId);
-            }
-        }
-
-        nextLaneHeads = nextLaneHeads.filter(
-            (id, idx) => nextLaneHeads.indexOf(id) === idx,
-        );
-
-        const parentLanes = node.parents
-            .map((parentId) => nextLaneHeads.indexOf(parentId))
-            .filter((idx) => idx >= 0);
-
-        rows.push({
-            node,
-            lane,
-            parentLanes,
-            laneCount: Math.max(1, beforeCount, nextLaneHeads.length),
-        });
-
-        maxLanes = Math.max(
-            maxLanes,
-            beforeCount,
-            nextLaneHeads.length,
-            lane + 1,
-        );
-        for (const parentLane of parentLanes) {
-            maxLanes = Math.max(maxLanes, parentLane + 1);
-        }
-
-        laneHeads = nextLaneHeads;
-    }
-
-    return { rows, maxLanes };
+function laneX(lane: number): number {
+    return lane * GRAPH_LANE_GAP_PX + GRAPH_LANE_X_OFFSET_PX;
 }
 
 export function HistoryPanel({
     vcsState,
-    logNodes,
+    graphLayout,
     commitMessage,
     expandedNodeId,
     expandedDiff,
@@ -135,13 +77,13 @@ export function HistoryPanel({
     onCommitSnapshot,
     onRefreshVcs,
     onCheckoutSnapshot,
+    onAddNote,
     hasProject,
     vcsBusy,
 }: HistoryPanelProps) {
-    const { rows: graphRows, maxLanes } = useMemo(
-        () => buildGraphRows(logNodes),
-        [logNodes],
-    );
+    // 열 배치는 workspace-core의 graph_layout이 정한다 (CLI의 vcs graph와 같은 그래프)
+    const graphRows = useMemo(() => graphLayout?.rows ?? [], [graphLayout]);
+    const maxLanes = Math.max(1, graphLayout?.lane_count ?? 1);
     const [contextMenu, setContextMenu] = useState<HistoryContextMenu | null>(
         null,
     );
@@ -178,7 +120,8 @@ export function HistoryPanel({
     }, [contextMenu]);
 
     const contextNode = contextMenu
-        ? (logNodes.find((n) => n.id === contextMenu.nodeId) ?? null)
+        ? (graphRows.find((r) => r.node.id === contextMenu.nodeId)?.node ??
+          null)
         : null;
     const contextNodeIsHead = contextNode
         ? vcsState?.head === contextNode.id
@@ -252,6 +195,8 @@ export function HistoryPanel({
                                     EXPAND_LIST_VERTICAL_PADDING_PX,
                             );
                         }
+                        expandPanelHeight +=
+                            node.notes.length * EXPAND_NOTE_ROW_HEIGHT_PX;
                     }
 
                     const totalHeight = GRAPH_ROW_HEIGHT_PX + expandPanelHeight;
@@ -304,47 +249,36 @@ export function HistoryPanel({
                                     height={totalHeight}
                                     viewBox={`0 0 ${graphWidth} ${totalHeight}`}
                                 >
-                                    {Array.from(
-                                        {
-                                            length: Math.max(
-                                                maxLanes,
-                                                row.laneCount,
-                                            ),
-                                        },
-                                        (_, idx) => {
-                                            const x =
-                                                idx * GRAPH_LANE_GAP_PX +
-                                                GRAPH_LANE_X_OFFSET_PX;
-                                            return (
-                                                <line
-                                                    key={`lane-${node.id}-${idx}`}
-                                                    className="history-graph-line"
-                                                    x1={x}
-                                                    y1={yTop}
-                                                    x2={x}
-                                                    y2={totalHeight}
-                                                />
-                                            );
-                                        },
-                                    )}
-                                    {row.parentLanes.map((parentLane, idx) => {
-                                        const x1 =
-                                            row.lane * GRAPH_LANE_GAP_PX +
-                                            GRAPH_LANE_X_OFFSET_PX;
-                                        const x2 =
-                                            parentLane * GRAPH_LANE_GAP_PX +
-                                            GRAPH_LANE_X_OFFSET_PX;
-                                        return (
-                                            <line
-                                                key={`branch-${node.id}-${idx}`}
-                                                className="history-graph-branch"
-                                                x1={x1}
-                                                y1={yNode}
-                                                x2={x2}
-                                                y2={GRAPH_ROW_HEIGHT_PX}
-                                            />
-                                        );
-                                    })}
+                                    {row.pass_through.map((lane) => (
+                                        <line
+                                            key={`lane-${node.id}-${lane}`}
+                                            className="history-graph-line"
+                                            x1={laneX(lane)}
+                                            y1={yTop}
+                                            x2={laneX(lane)}
+                                            y2={totalHeight}
+                                        />
+                                    ))}
+                                    {row.incoming.map((lane) => (
+                                        <line
+                                            key={`in-${node.id}-${lane}`}
+                                            className="history-graph-line"
+                                            x1={laneX(lane)}
+                                            y1={yTop}
+                                            x2={laneX(row.lane)}
+                                            y2={yNode}
+                                        />
+                                    ))}
+                                    {row.parent_lanes.map((parentLane, idx) => (
+                                        <line
+                                            key={`branch-${node.id}-${idx}`}
+                                            className="history-graph-branch"
+                                            x1={laneX(row.lane)}
+                                            y1={yNode}
+                                            x2={laneX(parentLane)}
+                                            y2={totalHeight}
+                                        />
+                                    ))}
                                     <circle
                                         className={[
                                             "history-graph-node",
@@ -352,10 +286,7 @@ export function HistoryPanel({
                                         ]
                                             .filter(Boolean)
                                             .join(" ")}
-                                        cx={
-                                            row.lane * GRAPH_LANE_GAP_PX +
-                                            GRAPH_LANE_X_OFFSET_PX
-                                        }
+                                        cx={laneX(row.lane)}
                                         cy={yNode}
                                         r={GRAPH_NODE_RADIUS_PX}
                                     />
@@ -380,6 +311,17 @@ export function HistoryPanel({
                                                 HEAD
                                             </span>
                                         )}
+                                        {node.notes.length > 0 && (
+                                            <span
+                                                className="history-note-count mono"
+                                                title={`${node.notes.length} note(s)`}
+                                            >
+                                                <span className="material-symbols-outlined">
+                                                    sticky_note_2
+                                                </span>
+                                                {node.notes.length}
+                                            </span>
+                                        )}
                                     </div>
                                 </div>
                                 {isExpanded && (
@@ -387,6 +329,20 @@ export function HistoryPanel({
                                         className="history-expand"
                                         onClick={(e) => e.stopPropagation()}
                                     >
+                                        {node.notes.map((note) => (
+                                            <div
+                                                key={`note-${note.id}`}
+                                                className="history-note"
+                                                title={formatNodeTime(note.updated_at_unix_ms)}
+                                            >
+                                                <span className="material-symbols-outlined">
+                                                    sticky_note_2
+                                                </span>
+                                                <span className="history-note-text">
+                                                    {note.text}
+                                                </span>
+                                            </div>
+                                        ))}
                                         {!hasPrevious && (
                                             <div className="history-expand-empty mono">
                                                 No previous commit to diff (root
@@ -446,10 +402,16 @@ export function HistoryPanel({
                                                                     <span className="history-expand-path mono">
                                                                         {f.path}
                                                                     </span>
-                                                                    {f.is_binary && (
+                                                                    {f.is_binary ? (
                                                                         <span className="history-expand-bin">
                                                                             BIN
                                                                         </span>
+                                                                    ) : (
+                                                                        f.word_delta !== 0 && (
+                                                                            <span className="history-expand-words mono">
+                                                                                {`${f.word_delta > 0 ? "+" : ""}${f.word_delta}w`}
+                                                                            </span>
+                                                                        )
                                                                     )}
                                                                 </button>
                                                             );
@@ -486,6 +448,18 @@ export function HistoryPanel({
                     >
                         Checkout Snapshot
                     </button>
+                    <button
+                        type="button"
+                        className="history-context-item"
+                        disabled={!contextNode || !hasProject || vcsBusy}
+                        onClick={() => {
+                            if (!contextNode) return;
+                            setContextMenu(null);
+                            void onAddNote(contextNode.id);
+                        }}
+                    >
+                        Add Note...
+                    </button>
                 </div>
             )}
         </>
@@ -1,10 +1,19 @@
 import { Button } from "../ui";
+import type { ProgressEvent, ProgressPhase } from "../../lib/api/vcsApi";
 
 type CursorPosition = {
     line: number;
     col: number;
 };
 
+const PHASE_LABELS: Record<ProgressPhase, string> = {
+    scan: "Scanning",
+    store: "Storing",
+    write: "Writing",
+    pack: "Packing",
+    sweep: "Cleaning",
+};
+
 type StatusBarProps = {
     headShort: string;
     isDirty: boolean;
@@ -12,8 +21,10 @@ type StatusBarProps = {
     cursor: CursorPosition;
     language: string;
     canSave: boolean;
+    progress: ProgressEvent | null;
     onRefreshFiles: () => void | Promise<void>;
     onSave: () => void | Promise<void>;
+    onCancelProgress: () => void;
 };
 
 export function StatusBar({
@@ -23,8 +34,10 @@ export function StatusBar({
     cursor,
     language,
     canSave,
+    progress,
     onRefreshFiles,
     onSave,
+    onCancelProgress,
 }: StatusBarProps) {
     return (
         <footer className="status-bar">
@@ -48,6 +61,16 @@ export function StatusBar({
                     <span>0</span>
                 </span>
                 <span className="status-item">{`#${nodeCount}`}</span>
+                {progress && (
+                    <>
+                        <span className="status-item">
+                            {`${PHASE_LABELS[progress.phase]} ${progress.done}/${progress.total}`}
+                        </span>
+                        <Button className="status-item status-button" unstyled onClick={onCancelProgress}>
+                            Cancel
+                        </Button>
+                    </>
+                )}
             </div>
 
             <div className="status-group">
@@ -0,0 +1,44 @@
+// 백엔드 WorkSpaceError가 직렬화된 형태. code는 WorkSpaceError::code()와 같다
+export type ErrorCode =
+    | "invalid_root"
+    | "path_escape"
+    | "io"
+    | "path_outside_root"
+    | "invalid_file_name"
+    | "non_fast_forward"
+    | "repo_locked"
+    | "nothing_to_commit"
+    | "ambiguous_revision"
+    | "hook_failed"
+    | "not_a_repository"
+    | "node_not_found"
+    | "invalid_revision"
+    | "empty_message"
+    | "dirty_working_tree"
+    | "conflict"
+    | "not_found"
+    | "invalid_input"
+    | "corrupt_data"
+    | "database"
+    | "migration"
+    | "cancelled"
+    | "key_required"
+    | "wrong_passphrase"
+    | "plaintext_export_denied"
+    | "snapshot_thinned"
+    | "access_denied";
+
+export type WorkspaceError = {
+    code: ErrorCode;
+    message: string;
+};
+
+export const isWorkspaceError = (e: unknown): e is WorkspaceError =>
+    typeof e === "object" && e !== null && "code" in e && "message" in e;
+
+export const hasErrorCode = (e: unknown, code: ErrorCode) =>
+    isWorkspaceError(e) && e.code === code;
+
+// invoke가 던진 값을 화면에 띄울 문자열로 바꾼다(Tauri 자체 에러는 문자열로 온다)
+export const errorMessage = (e: unknown) =>
+    isWorkspaceError(e) ? e.message : String(e);
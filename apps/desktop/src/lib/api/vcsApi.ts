@@ -1,4 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 
 export type RepoState = {
     head: string | null;
@@ -10,8 +11,21 @@ export type VersionNode = {
     parents: string[];
     message: string;
     created_at_unix_ms: number;
+    kind: NodeKind;
+    notes: NodeNote[];
 };
 
+// 노드에 나중에 붙인 메모. 고쳐도 노드 id는 바뀌지 않는다
+export type NodeNote = {
+    id: number;
+    node_id: string;
+    text: string;
+    created_at_unix_ms: number;
+    updated_at_unix_ms: number;
+};
+
+export type NodeKind = "manual" | "autosave";
+
 export enum DiffKind {
     Added = "added",
     Removed = "removed",
@@ -33,7 +47,26 @@ export type NodeDiff = {
     files: FileDiff[];
 };
 
-export const initRepo = (root: string) => invoke<void>("init_repo", { root });
+// passphrase를 넘기면 암호화 저장소로 만든다
+export const initRepo = (root: string, passphrase?: string) =>
+    invoke<void>("init_repo", { root, passphrase: passphrase ?? null });
+
+export const isEncrypted = (root: string) =>
+    invoke<boolean>("is_encrypted", { root });
+
+// 암호화 저장소는 잠겨 있으면 code가 "key_required"인 에러를 낸다.
+// 암호가 틀리면 "wrong_passphrase"
+export const unlockRepo = (root: string, passphrase: string) =>
+    invoke<void>("unlock_repo", { root, passphrase });
+
+export const forgetRepoKey = (root: string) =>
+    invoke<void>("forget_repo_key", { root });
+
+export const rotateKey = (
+    root: string,
+    oldPassphrase: string,
+    newPassphrase: string,
+) => invoke<void>("rotate_key", { root, oldPassphrase, newPassphrase });
 
 export const fetchRepoState = (root: string) =>
     invoke<RepoState>("repo_state", { root });
@@ -41,11 +74,357 @@ export const fetchRepoState = (root: string) =>
 export const fetchLog = (root: string) =>
     invoke<VersionNode[]>("log", { root });
 
-export const commitSnapshot = (root: string, message: string) =>
-    invoke<string>("commit", { root, message });
+export const fetchNodeNotes = (root: string, nodeId: string) =>
+    invoke<NodeNote[]>("node_notes", { root, nodeId });
+
+export const noteAdd = (root: string, revision: string, text: string) =>
+    invoke<NodeNote>("note_add", { root, revision, text });
+
+export const noteEdit = (root: string, noteId: number, text: string) =>
+    invoke<NodeNote>("note_edit", { root, noteId, text });
+
+export const noteRemove = (root: string, noteId: number) =>
+    invoke<void>("note_remove", { root, noteId });
+
+// 같은 내용이 같은 경로로 들어 있는 노드들(최신 노드부터)
+export type SearchHit = {
+    path: string;
+    blob_id: string;
+    snippet: string;
+    nodes: string[];
+};
+
+export const searchHistory = (root: string, query: string) =>
+    invoke<SearchHit[]>("search_history", { root, query });
+
+// node_id에서 path 안의 문구 개수가 바뀐 곳(병합 노드는 첫 부모 기준)
+export type PickaxeHit = {
+    node_id: string;
+    path: string;
+    before_count: number;
+    after_count: number;
+};
+
+export const pickaxe = (root: string, phrase: string, pathFilter?: string) =>
+    invoke<PickaxeHit[]>("pickaxe", { root, phrase, pathFilter });
+
+// UTC 기준 하루/주(월요일 시작) 단위
+export type ProgressBucket = "day" | "week";
+
+// 구간 마지막 노드 시점의 원고 크기. chars는 공백 제외
+export type ProgressPoint = {
+    bucket_start_unix_ms: number;
+    date: string;
+    node_id: string;
+    words: number;
+    chars: number;
+    snapshots: number;
+};
+
+export const progressSeries = (root: string, bucket: ProgressBucket) =>
+    invoke<ProgressPoint[]>("progress_series", { root, bucket });
+
+export type GraphRow = {
+    node: VersionNode;
+    lane: number;
+    incoming: number[];
+    pass_through: number[];
+    parent_lanes: number[];
+    lane_count: number;
+};
+
+export type GraphLayout = {
+    rows: GraphRow[];
+    lane_count: number;
+};
+
+export const fetchGraphLayout = (root: string) =>
+    invoke<GraphLayout>("graph_layout", { root });
 
-export const checkoutSnapshot = (root: string, nodeId: string) =>
-    invoke<void>("checkout", { root, nodeId });
+export const commitSnapshot = (
+    root: string,
+    message: string,
+    allowEmpty = false,
+    operationId?: string,
+) =>
+    invoke<string>("commit", {
+        root,
+        message,
+        allowEmpty,
+        operationId: operationId ?? null,
+    });
+
+// staging 영역: commitStaged는 HEAD에 올려 둔 변경만 더해 스냅샷을 만든다.
+// commitSnapshot은 여전히 작업 트리 전체를 저장하고 staging 영역을 비운다
+export type StagedFile = {
+    path: string;
+    kind: DiffKind;
+};
+
+export const stagePaths = (root: string, paths: string[]) =>
+    invoke<void>("stage", { root, paths });
+
+export const unstagePaths = (root: string, paths: string[]) =>
+    invoke<void>("unstage", { root, paths });
+
+export const fetchStaged = (root: string) =>
+    invoke<StagedFile[]>("staged", { root });
+
+export const commitStaged = (root: string, message: string) =>
+    invoke<string>("commit_staged", { root, message });
+
+export const checkoutSnapshot = (
+    root: string,
+    nodeId: string,
+    operationId?: string,
+) =>
+    invoke<void>("checkout", {
+        root,
+        nodeId,
+        operationId: operationId ?? null,
+    });
 
 export const diffNodes = (root: string, from: string, to: string) =>
     invoke<NodeDiff>("diff_nodes", { root, from, to });
+
+// 작업 트리와 노드(생략하면 HEAD) 비교. 결과의 to는 "WORKDIR"
+export const diffWorkdir = (root: string, revision?: string) =>
+    invoke<NodeDiff>("diff_workdir", { root, revision: revision ?? null });
+
+export type FileChange = {
+    path: string;
+    kind: DiffKind;
+    before_size: number | null;
+    after_size: number | null;
+    before_words: number | null;
+    after_words: number | null;
+    word_delta: number;
+    is_binary: boolean;
+};
+
+export type DiffSummary = {
+    from: string;
+    to: string;
+    files: FileChange[];
+};
+
+// 내용 없이 바뀐 경로와 크기/단어 수만 받는다
+export const diffSummary = (root: string, from: string, to: string) =>
+    invoke<DiffSummary>("diff_summary", { root, from, to });
+
+// 내용이 같으면 null
+export const diffFile = (
+    root: string,
+    from: string,
+    to: string,
+    path: string,
+) => invoke<FileDiff | null>("diff_file", { root, from, to, path });
+
+export type BundleRef = {
+    name: string;
+    node_id: string;
+};
+
+export type BundleSummary = {
+    refs: BundleRef[];
+    prerequisites: string[];
+    node_count: number;
+    blob_count: number;
+};
+
+export type UnbundleSummary = {
+    refs: BundleRef[];
+    imported_nodes: number;
+    imported_blobs: number;
+};
+
+export const createBundle = (
+    root: string,
+    outPath: string,
+    refs: string[],
+    base: string | null = null,
+    operationId?: string,
+) =>
+    invoke<BundleSummary>("bundle_create", {
+        root,
+        outPath,
+        refs,
+        base,
+        operationId: operationId ?? null,
+    });
+
+export const unbundle = (
+    root: string,
+    bundlePath: string,
+    operationId?: string,
+) =>
+    invoke<UnbundleSummary>("bundle_unbundle", {
+        root,
+        bundlePath,
+        operationId: operationId ?? null,
+    });
+
+export type Remote = {
+    name: string;
+    url: string;
+    head: string | null;
+};
+
+export type FetchSummary = {
+    remote: string;
+    head: string | null;
+    imported_nodes: number;
+    imported_blobs: number;
+};
+
+export type PushSummary = {
+    remote: string;
+    old_head: string | null;
+    new_head: string;
+    pushed_nodes: number;
+    pushed_blobs: number;
+    forced: boolean;
+};
+
+export type PullSummary = {
+    remote: string;
+    old_head: string | null;
+    new_head: string | null;
+    imported_nodes: number;
+    imported_blobs: number;
+};
+
+export const addRemote = (root: string, name: string, url: string) =>
+    invoke<void>("remote_add", { root, name, url });
+
+export const removeRemote = (root: string, name: string) =>
+    invoke<void>("remote_remove", { root, name });
+
+export const fetchRemotes = (root: string) =>
+    invoke<Remote[]>("remotes", { root });
+
+export const fetchRemote = (root: string, remote: string) =>
+    invoke<FetchSummary>("fetch", { root, remote });
+
+export const pushRemote = (root: string, remote: string, force = false) =>
+    invoke<PushSummary>("push", { root, remote, force });
+
+export const pullRemote = (root: string, remote: string) =>
+    invoke<PullSummary>("pull", { root, remote });
+
+export const setRemoteToken = (
+    root: string,
+    name: string,
+    token: string | null,
+) => invoke<void>("remote_set_token", { root, name, token });
+
+export type ReflogEntry = {
+    id: number;
+    ref_name: string;
+    old_node_id: string | null;
+    new_node_id: string | null;
+    reason: string;
+    message: string;
+    created_at_unix_ms: number;
+};
+
+export const fetchReflog = (root: string) =>
+    invoke<ReflogEntry[]>("reflog", { root });
+
+export const undoLastHeadMove = (root: string) =>
+    invoke<string>("undo_last_head_move", { root });
+
+export const resolveRevision = (root: string, revision: string) =>
+    invoke<string>("resolve_revision", { root, revision });
+
+export type Tag = {
+    name: string;
+    node_id: string;
+};
+
+export const createTag = (root: string, name: string, revision: string) =>
+    invoke<Tag>("tag_create", { root, name, revision });
+
+export const deleteTag = (root: string, name: string) =>
+    invoke<void>("tag_delete", { root, name });
+
+export const fetchTags = (root: string) => invoke<Tag[]>("tags", { root });
+
+export const mergeBase = (root: string, a: string, b: string) =>
+    invoke<string | null>("merge_base", { root, a, b });
+
+export const isAncestor = (root: string, ancestor: string, node: string) =>
+    invoke<boolean>("is_ancestor", { root, ancestor, node });
+
+export type AheadBehind = {
+    ahead: number;
+    behind: number;
+};
+
+export const aheadBehind = (root: string, a: string, b: string) =>
+    invoke<AheadBehind>("ahead_behind", { root, a, b });
+
+export const fetchDescendants = (root: string, node: string) =>
+    invoke<string[]>("descendants", { root, node });
+
+export type AutosavePolicy = {
+    interval_ms: number;
+    only_when_changed: boolean;
+    message_template: string;
+};
+
+// policy를 생략하면 코어 기본값(10분, 바뀐 경우만)을 쓴다
+export const autosave = (root: string, policy?: AutosavePolicy) =>
+    invoke<string | null>("autosave", { root, policy: policy ?? null });
+
+export type ThinSummary = {
+    removed_nodes: string[];
+    thinned_nodes: string[];
+    kept_autosaves: number;
+};
+
+export const thinAutosaves = (root: string) =>
+    invoke<ThinSummary>("thin_autosaves", { root });
+
+export type GcSummary = {
+    removed_blobs: number;
+    removed_objects: number;
+};
+
+export const gc = (root: string, operationId?: string) =>
+    invoke<GcSummary>("gc", { root, operationId: operationId ?? null });
+
+// 번들/push/pull의 pack은 평문이다. 암호화 저장소는 허용하기 전까지 "plaintext_export_denied"
+export const fetchPlaintextExportAllowed = (root: string) =>
+    invoke<boolean>("plaintext_export_allowed", { root });
+
+export const setPlaintextExport = (root: string, allowed: boolean) =>
+    invoke<void>("set_plaintext_export", { root, allowed });
+
+// 이 크기(바이트)보다 큰 파일은 .novel/objects에 따로 저장된다
+export const fetchLargeBlobThreshold = (root: string) =>
+    invoke<number>("large_blob_threshold", { root });
+
+export const setLargeBlobThreshold = (root: string, bytes: number) =>
+    invoke<void>("set_large_blob_threshold", { root, bytes });
+
+export type ProgressPhase = "scan" | "store" | "write" | "pack" | "sweep";
+
+// commit/checkout/gc/bundle에 operationId를 넘기면 "vcs-progress" 이벤트로 온다.
+// done/total은 파일(blob) 수, bytes는 지금까지 처리한 크기
+export type ProgressEvent = {
+    operation_id: string;
+    phase: ProgressPhase;
+    done: number;
+    total: number;
+    bytes: number;
+};
+
+export const onVcsProgress = (handler: (event: ProgressEvent) => void) =>
+    listen<ProgressEvent>("vcs-progress", (e) => handler(e.payload));
+
+export const newOperationId = () => crypto.randomUUID();
+
+// 취소된 작업은 code가 "cancelled"인 에러로 끝난다. 이미 끝난 작업이면 false
+export const cancelOperation = (operationId: string) =>
+    invoke<boolean>("cancel_operation", { operationId });
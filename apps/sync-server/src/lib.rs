@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use tiny_http::{Method, Request, Response, Server};
+use workspace_core::{
+    SYNC_ADVERTISE_PATH, SYNC_RECEIVE_PACK_PATH, SYNC_UPLOAD_PACK_PATH, SyncRequest, sync_serve,
+    sync_status_code,
+};
+
+// 요청 본문(push하는 pack) 크기 기본 상한
+pub const DEFAULT_MAX_BODY_BYTES: u64 = 512 * 1024 * 1024;
+
+// 서버 설정: 인증 토큰, "이름 -> 저장소 루트" 목록, 요청 본문 크기 상한(바이트)
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub token: String,
+    pub repos: HashMap<String, PathBuf>,
+    pub max_body_bytes: u64,
+}
+
+// 백그라운드 스레드에서 요청을 처리하는 동기화 서버. drop 되면 멈춘다.
+pub struct SyncServer {
+    server: Arc<Server>,
+    addr: SocketAddr,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl SyncServer {
+    pub fn bind(addr: &str, config: ServerConfig) -> std::io::Result<SyncServer> {
+        let server = Server::http(addr).map_err(std::io::Error::other)?;
+        let addr = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| std::io::Error::other("server is not bound to an ip address"))?;
+
+        let server = Arc::new(server);
+        let worker_server = Arc::clone(&server);
+        let worker = std::thread::spawn(move || {
+            // 저장소 DB 동시 접근을 피하려고 요청을 하나씩 처리한다
+            for request in worker_server.incoming_requests() {
+                handle(request, &config);
+            }
+        });
+
+        Ok(SyncServer {
+            server,
+            addr,
+            worker: Some(worker),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    // 서버가 멈출 때까지 기다린다
+    pub fn join(mut self) {
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for SyncServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn handle(mut request: Request, config: &ServerConfig) {
+    let (status, body) = route(&mut request, config);
+    let response = Response::from_data(body).with_status_code(status);
+    let _ = request.respond(response);
+}
+
+fn route(request: &mut Request, config: &ServerConfig) -> (u16, Vec<u8>) {
+    if !authorized(request, &config.token) {
+        return (401, b"unauthorized".to_vec());
+    }
+
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let force = query.split('&').any(|pair| pair == "force=1");
+
+    let Some((repo, action)) = path.trim_start_matches('/').split_once('/') else {
+        return (404, b"not found".to_vec());
+    };
+    let Some(root) = config.repos.get(repo) else {
+        return (404, format!("unknown repository: {}", repo).into_bytes());
+    };
+
+    // 본문을 다 읽기 전에 메모리를 다 쓰지 않도록 상한까지만 읽는다
+    let too_large = || {
+        (
+            413,
+            format!("request body exceeds {} bytes", config.max_body_bytes).into_bytes(),
+        )
+    };
+    if request
+        .body_length()
+        .is_some_and(|len| len as u64 > config.max_body_bytes)
+    {
+        return too_large();
+    }
+    let mut body = Vec::new();
+    if let Err(e) = request
+        .as_reader()
+        .take(config.max_body_bytes + 1)
+        .read_to_end(&mut body)
+    {
+        return (400, e.to_string().into_bytes());
+    }
+    if body.len() as u64 > config.max_body_bytes {
+        return too_large();
+    }
+
+    let sync_request = match (request.method(), action) {
+        (Method::Get, SYNC_ADVERTISE_PATH) => SyncRequest::Advertise,
+        (Method::Post, SYNC_UPLOAD_PACK_PATH) => SyncRequest::UploadPack(&body),
+        (Method::Post, SYNC_RECEIVE_PACK_PATH) => SyncRequest::ReceivePack { pack: &body, force },
+        _ => return (404, b"not found".to_vec()),
+    };
+
+    match sync_serve(root, sync_request) {
+        Ok(body) => (200, body),
+        Err(e) => (sync_status_code(&e), e.to_string().into_bytes()),
+    }
+}
+
+fn authorized(request: &Request, token: &str) -> bool {
+    let expected = format!("Bearer {}", token);
+
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .is_some_and(|h| constant_time_eq(h.value.as_str().as_bytes(), expected.as_bytes()))
+}
+
+// 토큰 비교 시간이 일치하는 접두사 길이에 따라 달라지지 않도록 한다
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
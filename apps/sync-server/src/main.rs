@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use clap::Parser;
+use novel_sync_server::{DEFAULT_MAX_BODY_BYTES, ServerConfig, SyncServer};
+
+#[derive(Parser)]
+#[command(name = "novel-sync-server")]
+struct Cli {
+    #[arg(long, default_value = "127.0.0.1:7878")]
+    bind: String,
+
+    // 생략하면 NOVEL_SYNC_TOKEN 환경 변수를 쓴다
+    #[arg(long)]
+    token: Option<String>,
+
+    // name=path 형식, 여러 번 지정 가능
+    #[arg(long = "repo", required = true)]
+    repos: Vec<String>,
+
+    // 요청 본문(push하는 pack) 크기 상한, 바이트
+    #[arg(long, default_value_t = DEFAULT_MAX_BODY_BYTES)]
+    max_body_bytes: u64,
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let cli = Cli::parse();
+
+    let token = cli
+        .token
+        .or_else(|| std::env::var("NOVEL_SYNC_TOKEN").ok())
+        .filter(|t| !t.is_empty())
+        .ok_or("missing token: pass --token or set NOVEL_SYNC_TOKEN")?;
+
+    let mut repos = HashMap::new();
+    for spec in cli.repos {
+        let (name, path) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("invalid --repo value (expected name=path): {}", spec))?;
+
+        let path = PathBuf::from(path);
+        std::fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+        workspace_core::init_repo(&path).map_err(|e| e.to_string())?;
+
+        repos.insert(name.to_string(), path);
+    }
+
+    let config = ServerConfig {
+        token,
+        repos,
+        max_body_bytes: cli.max_body_bytes,
+    };
+    let server = SyncServer::bind(&cli.bind, config).map_err(|e| e.to_string())?;
+    println!("novel-sync-server listening on {}", server.local_addr());
+    server.join();
+
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use novel_sync_server::{DEFAULT_MAX_BODY_BYTES, ServerConfig, SyncServer};
+use workspace_core::{
+    Result, WorkSpaceError, commit, fetch, init_repo, pull, push, remote_add, remote_set_token,
+    repo_state,
+};
+
+const TOKEN: &str = "secret-token";
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+fn start_server(repos: &[(&str, &std::path::Path)]) -> SyncServer {
+    start_server_with_limit(repos, DEFAULT_MAX_BODY_BYTES)
+}
+
+fn start_server_with_limit(repos: &[(&str, &std::path::Path)], max_body_bytes: u64) -> SyncServer {
+    let repos = repos
+        .iter()
+        .map(|(name, path)| {
+            init_repo(path).unwrap();
+            (name.to_string(), path.to_path_buf())
+        })
+        .collect::<HashMap<_, _>>();
+
+    SyncServer::bind(
+        "127.0.0.1:0",
+        ServerConfig {
+            token: TOKEN.to_string(),
+            repos,
+            max_body_bytes,
+        },
+    )
+    .unwrap()
+}
+
+fn add_http_remote(root: &std::path::Path, server: &SyncServer, repo: &str, token: &str) {
+    init_repo(root).unwrap();
+    let url = format!("http://{}/{}", server.local_addr(), repo);
+    remote_add(root, "origin", &url).unwrap();
+    remote_set_token(root, "origin", Some(token)).unwrap();
+}
+
+#[test]
+fn push_and_pull_through_loopback_server() -> Result<()> {
+    let (_server_td, server_repo) = setup();
+    let (_laptop_td, laptop) = setup();
+    let (_desktop_td, desktop) = setup();
+
+    let server = start_server(&[("book", &server_repo)]);
+    add_http_remote(&laptop, &server, "book", TOKEN);
+    add_http_remote(&desktop, &server, "book", TOKEN);
+
+    std::fs::write(laptop.join("chapter1.txt"), "draft")?;
+    let first = commit(&laptop, "first")?;
+
+    let pushed = push(&laptop, "origin", false)?;
+    assert_eq!(pushed.new_head, first);
+    assert_eq!(pushed.pushed_nodes, 1);
+    assert_eq!(pushed.pushed_blobs, 1);
+    assert_eq!(repo_state(&server_repo)?.head, Some(first.clone()));
+
+    let pulled = pull(&desktop, "origin")?;
+    assert_eq!(pulled.new_head, Some(first.clone()));
+    assert_eq!(
+        std::fs::read_to_string(desktop.join("chapter1.txt"))?,
+        "draft"
+    );
+
+    std::fs::write(desktop.join("chapter1.txt"), "revised on desktop")?;
+    let second = commit(&desktop, "second")?;
+    let pushed = push(&desktop, "origin", false)?;
+    assert_eq!(pushed.old_head, Some(first));
+    assert_eq!(pushed.pushed_nodes, 1);
+
+    let fetched = fetch(&laptop, "origin")?;
+    assert_eq!(fetched.head, Some(second.clone()));
+    assert_eq!(fetched.imported_nodes, 1);
+    assert_eq!(fetched.imported_blobs, 1);
+
+    pull(&laptop, "origin")?;
+    assert_eq!(
+        std::fs::read_to_string(laptop.join("chapter1.txt"))?,
+        "revised on desktop"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn server_rejects_wrong_token() -> Result<()> {
+    let (_server_td, server_repo) = setup();
+    let (_local_td, local) = setup();
+
+    let server = start_server(&[("book", &server_repo)]);
+    add_http_remote(&local, &server, "book", "wrong-token");
+
+    let err = fetch(&local, "origin").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::AccessDenied(_)));
+
+    Ok(())
+}
+
+#[test]
+fn server_rejects_non_fast_forward_push_unless_forced() -> Result<()> {
+    let (_server_td, server_repo) = setup();
+    let (_a_td, a) = setup();
+    let (_b_td, b) = setup();
+
+    let server = start_server(&[("book", &server_repo)]);
+    add_http_remote(&a, &server, "book", TOKEN);
+    add_http_remote(&b, &server, "book", TOKEN);
+
+    std::fs::write(a.join("a.txt"), "a")?;
+    commit(&a, "a")?;
+    push(&a, "origin", false)?;
+
+    std::fs::write(b.join("b.txt"), "b")?;
+    let b_head = commit(&b, "b")?;
+
+    let err = push(&b, "origin", false).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NonFastForward(_)));
+
+    let forced = push(&b, "origin", true)?;
+    assert!(forced.forced);
+    assert_eq!(repo_state(&server_repo)?.head, Some(b_head));
+
+    Ok(())
+}
+
+#[test]
+fn server_serves_multiple_repositories() -> Result<()> {
+    let (_novel_td, novel) = setup();
+    let (_essay_td, essay) = setup();
+    let (_local_td, local) = setup();
+
+    let server = start_server(&[("novel", &novel), ("essay", &essay)]);
+    add_http_remote(&local, &server, "essay", TOKEN);
+
+    std::fs::write(local.join("essay.txt"), "essay")?;
+    let head = commit(&local, "essay")?;
+    push(&local, "origin", false)?;
+
+    assert_eq!(repo_state(&essay)?.head, Some(head));
+    assert_eq!(repo_state(&novel)?.head, None);
+
+    let (_other_td, other) = setup();
+    add_http_remote(&other, &server, "missing", TOKEN);
+    let err = fetch(&other, "origin").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NotFound(_)));
+
+    Ok(())
+}
+
+#[test]
+fn server_rejects_oversized_push() -> Result<()> {
+    let (_server_td, server_repo) = setup();
+    let (_local_td, local) = setup();
+
+    let server = start_server_with_limit(&[("book", &server_repo)], 64);
+    add_http_remote(&local, &server, "book", TOKEN);
+
+    std::fs::write(local.join("chapter1.txt"), "x".repeat(1024))?;
+    commit(&local, "long chapter")?;
+
+    let err = push(&local, "origin", false).unwrap_err();
+    assert!(
+        matches!(&err, WorkSpaceError::InvalidInput(msg) if msg.contains("exceeds 64 bytes")),
+        "{:?}",
+        err
+    );
+    assert_eq!(repo_state(&server_repo)?.head, None);
+
+    Ok(())
+}
+
+#[test]
+fn token_is_sealed_in_encrypted_repository() -> Result<()> {
+    let (_server_td, server_repo) = setup();
+    let (_local_td, local) = setup();
+
+    let server = start_server(&[("book", &server_repo)]);
+    workspace_core::init_repo_with_options(
+        &local,
+        &workspace_core::InitOptions {
+            passphrase: Some("secret".to_string()),
+        },
+    )?;
+    add_http_remote(&local, &server, "book", TOKEN);
+
+    let db = std::fs::read(local.join(".novel").join("vcs.db"))?;
+    let wal = std::fs::read(local.join(".novel").join("vcs.db-wal")).unwrap_or_default();
+    for bytes in [&db, &wal] {
+        assert!(!bytes.windows(TOKEN.len()).any(|w| w == TOKEN.as_bytes()));
+    }
+
+    // pack은 평문이므로 내보내기를 허용하기 전에는 보내지 않는다
+    std::fs::write(local.join("chapter1.txt"), "draft")?;
+    let head = commit(&local, "first")?;
+    let err = push(&local, "origin", false).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::PlaintextExportDenied));
+    assert_eq!(repo_state(&server_repo)?.head, None);
+
+    // 봉인된 토큰으로도 서버에 인증된다
+    workspace_core::set_plaintext_export(&local, true)?;
+    push(&local, "origin", false)?;
+    assert_eq!(repo_state(&server_repo)?.head, Some(head));
+
+    // 키를 바꾸면 토큰도 새 키로 다시 봉인된다
+    workspace_core::rotate_key(&local, "secret", "new secret")?;
+    std::fs::write(local.join("chapter1.txt"), "revised")?;
+    let head = commit(&local, "second")?;
+    push(&local, "origin", false)?;
+    assert_eq!(repo_state(&server_repo)?.head, Some(head));
+
+    Ok(())
+}
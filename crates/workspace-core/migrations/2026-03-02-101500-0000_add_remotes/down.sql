@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS refs;
+DROP TABLE IF EXISTS remotes;
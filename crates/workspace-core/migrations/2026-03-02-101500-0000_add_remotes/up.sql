@@ -0,0 +1,10 @@
+CREATE TABLE remotes (
+  name TEXT PRIMARY KEY NOT NULL,
+  url TEXT NOT NULL
+);
+
+CREATE TABLE refs (
+  name TEXT PRIMARY KEY NOT NULL,
+  node_id TEXT NOT NULL,
+  FOREIGN KEY (node_id) REFERENCES nodes(id) ON DELETE CASCADE
+);
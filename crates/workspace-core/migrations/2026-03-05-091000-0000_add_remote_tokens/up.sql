@@ -0,0 +1 @@
+ALTER TABLE remotes ADD COLUMN token TEXT NULL;
@@ -0,0 +1,11 @@
+CREATE TABLE reflog (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  ref_name TEXT NOT NULL,
+  old_node_id TEXT NULL,
+  new_node_id TEXT NULL,
+  reason TEXT NOT NULL,
+  message TEXT NOT NULL,
+  created_at_unix_ms BIGINT NOT NULL
+);
+
+CREATE INDEX idx_reflog_ref_name ON reflog(ref_name, id);
@@ -0,0 +1 @@
+ALTER TABLE nodes ADD COLUMN tree_id TEXT NULL;
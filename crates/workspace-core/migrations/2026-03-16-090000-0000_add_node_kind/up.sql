@@ -0,0 +1 @@
+ALTER TABLE nodes ADD COLUMN kind TEXT NOT NULL DEFAULT 'manual';
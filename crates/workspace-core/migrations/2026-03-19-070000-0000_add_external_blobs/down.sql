@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS config;
+ALTER TABLE blobs DROP COLUMN external;
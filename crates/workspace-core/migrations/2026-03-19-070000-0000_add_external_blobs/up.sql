@@ -0,0 +1,7 @@
+-- external = 1이면 내용은 .novel/objects/<id 앞 2자리>/<나머지>에 있고 content는 비어 있다
+ALTER TABLE blobs ADD COLUMN external BOOLEAN NOT NULL DEFAULT 0;
+
+CREATE TABLE config (
+  key TEXT PRIMARY KEY NOT NULL,
+  value TEXT NOT NULL
+);
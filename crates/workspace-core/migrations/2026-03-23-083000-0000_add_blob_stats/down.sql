@@ -0,0 +1,2 @@
+ALTER TABLE blobs DROP COLUMN word_count;
+ALTER TABLE blobs DROP COLUMN size;
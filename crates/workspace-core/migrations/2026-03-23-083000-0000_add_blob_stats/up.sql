@@ -0,0 +1,4 @@
+-- 요약 diff용 blob 통계. size가 NULL이면 아직 계산하지 않은 것이고(처음 필요할 때 채운다),
+-- size가 있는데 word_count가 NULL이면 텍스트가 아닌 blob이다
+ALTER TABLE blobs ADD COLUMN size BIGINT NULL;
+ALTER TABLE blobs ADD COLUMN word_count BIGINT NULL;
@@ -0,0 +1,6 @@
+-- commit_staged가 HEAD 위에 덮어쓸 변경. blob_id가 NULL이면 삭제를 올려 둔 것이다.
+-- blob 내용은 stage할 때 이미 blobs에 넣어 둔다
+CREATE TABLE staged_files (
+  path TEXT PRIMARY KEY NOT NULL,
+  blob_id TEXT NULL REFERENCES blobs(id)
+);
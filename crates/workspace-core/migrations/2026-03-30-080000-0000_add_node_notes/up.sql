@@ -0,0 +1,10 @@
+-- 노드에 나중에 붙이는 메모. 노드 id 해시에 들어가지 않으므로 고쳐도 노드는 그대로다
+CREATE TABLE node_notes (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  node_id TEXT NOT NULL REFERENCES nodes(id),
+  text TEXT NOT NULL,
+  created_at_unix_ms BIGINT NOT NULL,
+  updated_at_unix_ms BIGINT NOT NULL
+);
+
+CREATE INDEX idx_node_notes_node_id ON node_notes(node_id, id);
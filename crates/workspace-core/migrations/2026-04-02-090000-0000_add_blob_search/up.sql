@@ -0,0 +1,15 @@
+-- 텍스트 blob 전문 검색. trigram이라 띄어쓰기 없는 한국어도 부분 문자열로 찾는다.
+-- 암호화 저장소는 평문이 남지 않게 색인하지 않는다
+CREATE VIRTUAL TABLE blob_search USING fts5(
+  blob_id UNINDEXED,
+  content,
+  tokenize = 'trigram'
+);
+
+-- DB 안에 있는 텍스트 blob을 채운다. objects 파일에 있는 큰 blob은 처음 검색할 때 채운다
+INSERT INTO blob_search (blob_id, content)
+SELECT id, CAST(content AS TEXT)
+FROM blobs
+WHERE external = 0
+  AND instr(content, x'00') = 0
+  AND NOT EXISTS (SELECT 1 FROM config WHERE key = 'encryption.key');
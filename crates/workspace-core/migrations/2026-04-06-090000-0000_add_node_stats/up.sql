@@ -0,0 +1,6 @@
+-- 노드 스냅샷 전체의 단어/글자 수. 커밋하거나 번들/pull로 받을 때 채우고, 그 전에 만든 노드는 progress_series가 그때그때 센다
+CREATE TABLE node_stats (
+  node_id TEXT PRIMARY KEY NOT NULL REFERENCES nodes(id),
+  word_count BIGINT NOT NULL,
+  char_count BIGINT NOT NULL
+);
@@ -0,0 +1,2 @@
+-- thin_autosaves가 솎아낸 자동 저장. 파일 목록(node_files)은 지우고 id를 검증할 tree_id와 통계만 남긴다
+ALTER TABLE nodes ADD COLUMN thinned BOOLEAN NOT NULL DEFAULT 0;
@@ -36,6 +36,138 @@ pub enum WorkSpaceError {
 
     #[error("invalid file name: {0}")]
     InvalidFileName(String),
+
+    #[error("non-fast-forward: {0}")]
+    NonFastForward(String),
+
+    #[error("repository is busy: another process holds {0}")]
+    RepoLocked(PathBuf),
+
+    #[error("nothing to commit: working tree matches {0}")]
+    NothingToCommit(String),
+
+    #[error("ambiguous revision {revision}: could be {}", .candidates.join(", "))]
+    AmbiguousRevision {
+        revision: String,
+        candidates: Vec<String>,
+    },
+
+    #[error("{hook} hook failed (exit code {}): {output}", .code.map_or("none".to_string(), |c| c.to_string()))]
+    HookFailed {
+        hook: String,
+        code: Option<i32>,
+        output: String,
+    },
+
+    #[error("not a novel repository: {0}")]
+    NotARepository(PathBuf),
+
+    #[error("unknown revision: {0}")]
+    NodeNotFound(String),
+
+    #[error("invalid revision {revision}: {reason}")]
+    InvalidRevision { revision: String, reason: String },
+
+    #[error("empty commit message")]
+    EmptyMessage,
+
+    #[error("working tree has uncommitted changes: {}", .0.join(", "))]
+    DirtyWorkingTree(Vec<String>),
+
+    // 이미 있는 태그/원격처럼 기존 상태와 충돌하는 요청
+    #[error("{0}")]
+    Conflict(String),
+
+    // 태그/원격/blob처럼 노드가 아닌 대상을 찾지 못한 경우
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{0}")]
+    InvalidInput(String),
+
+    // 번들/저널/objects 파일처럼 저장된 데이터가 깨진 경우
+    #[error("{0}")]
+    CorruptData(String),
+
+    #[error("database error: {0}")]
+    Database(String),
+
+    #[error("migration failed: {0}")]
+    Migration(String),
+
+    #[error("operation cancelled")]
+    Cancelled,
+
+    // 암호화된 저장소를 unlock_repo 없이 열려고 한 경우
+    #[error("repository is encrypted; unlock it first: {0}")]
+    KeyRequired(PathBuf),
+
+    #[error("wrong passphrase")]
+    WrongPassphrase,
+
+    // 암호화 저장소에서 set_plaintext_export(true) 없이 번들/pack을 내보내려 한 경우
+    #[error(
+        "repository is encrypted; bundles and sync packs are plaintext, enable set_plaintext_export first"
+    )]
+    PlaintextExportDenied,
+
+    // thin_autosaves가 파일 목록을 지운 노드를 체크아웃하거나 비교하려 한 경우
+    #[error("snapshot was thinned and its files are gone: {0}")]
+    SnapshotThinned(String),
+
+    // 동기화 서버가 토큰을 받아주지 않았거나 요청을 거절한 경우(401, 403)
+    #[error("access denied by sync server: {0}")]
+    AccessDenied(String),
+}
+
+impl WorkSpaceError {
+    // 프론트엔드가 분기할 때 쓰는 고정 코드. 메시지는 바뀔 수 있어도 코드는 바꾸지 않는다
+    pub fn code(&self) -> &'static str {
+        match self {
+            WorkSpaceError::InvalidRoot(_) => "invalid_root",
+            WorkSpaceError::PathEscape(_) => "path_escape",
+            WorkSpaceError::Io(_) => "io",
+            WorkSpaceError::PathOutsideRoot(_) => "path_outside_root",
+            WorkSpaceError::InvalidFileName(_) => "invalid_file_name",
+            WorkSpaceError::NonFastForward(_) => "non_fast_forward",
+            WorkSpaceError::RepoLocked(_) => "repo_locked",
+            WorkSpaceError::NothingToCommit(_) => "nothing_to_commit",
+            WorkSpaceError::AmbiguousRevision { .. } => "ambiguous_revision",
+            WorkSpaceError::HookFailed { .. } => "hook_failed",
+            WorkSpaceError::NotARepository(_) => "not_a_repository",
+            WorkSpaceError::NodeNotFound(_) => "node_not_found",
+            WorkSpaceError::InvalidRevision { .. } => "invalid_revision",
+            WorkSpaceError::EmptyMessage => "empty_message",
+            WorkSpaceError::DirtyWorkingTree(_) => "dirty_working_tree",
+            WorkSpaceError::Conflict(_) => "conflict",
+            WorkSpaceError::NotFound(_) => "not_found",
+            WorkSpaceError::InvalidInput(_) => "invalid_input",
+            WorkSpaceError::CorruptData(_) => "corrupt_data",
+            WorkSpaceError::Database(_) => "database",
+            WorkSpaceError::Migration(_) => "migration",
+            WorkSpaceError::Cancelled => "cancelled",
+            WorkSpaceError::KeyRequired(_) => "key_required",
+            WorkSpaceError::WrongPassphrase => "wrong_passphrase",
+            WorkSpaceError::PlaintextExportDenied => "plaintext_export_denied",
+            WorkSpaceError::SnapshotThinned(_) => "snapshot_thinned",
+            WorkSpaceError::AccessDenied(_) => "access_denied",
+        }
+    }
+}
+
+// Tauri 커맨드가 에러를 그대로 돌려줄 수 있도록 { code, message } 객체로 직렬화한다
+impl Serialize for WorkSpaceError {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("WorkSpaceError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
 }
 
 pub type Result<T> = std::result::Result<T, WorkSpaceError>;
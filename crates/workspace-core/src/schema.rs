@@ -4,6 +4,16 @@ diesel::table! {
     blobs (id) {
         id -> Text,
         content -> Binary,
+        external -> Bool,
+        size -> Nullable<BigInt>,
+        word_count -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    config (key) {
+        key -> Text,
+        value -> Text,
     }
 }
 
@@ -22,6 +32,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    node_notes (id) {
+        id -> Integer,
+        node_id -> Text,
+        text -> Text,
+        created_at_unix_ms -> BigInt,
+        updated_at_unix_ms -> BigInt,
+    }
+}
+
 diesel::table! {
     node_parents (node_id, ord) {
         node_id -> Text,
@@ -30,16 +50,78 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    node_stats (node_id) {
+        node_id -> Text,
+        word_count -> BigInt,
+        char_count -> BigInt,
+    }
+}
+
 diesel::table! {
     nodes (id) {
         id -> Text,
         message -> Text,
         created_at_unix_ms -> BigInt,
+        tree_id -> Nullable<Text>,
+        kind -> Text,
+        thinned -> Bool,
+    }
+}
+
+diesel::table! {
+    reflog (id) {
+        id -> Integer,
+        ref_name -> Text,
+        old_node_id -> Nullable<Text>,
+        new_node_id -> Nullable<Text>,
+        reason -> Text,
+        message -> Text,
+        created_at_unix_ms -> BigInt,
+    }
+}
+
+diesel::table! {
+    refs (name) {
+        name -> Text,
+        node_id -> Text,
+    }
+}
+
+diesel::table! {
+    remotes (name) {
+        name -> Text,
+        url -> Text,
+        token -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    staged_files (path) {
+        path -> Text,
+        blob_id -> Nullable<Text>,
     }
 }
 
 diesel::joinable!(head -> nodes (node_id));
 diesel::joinable!(node_files -> blobs (blob_id));
 diesel::joinable!(node_files -> nodes (node_id));
+diesel::joinable!(node_notes -> nodes (node_id));
+diesel::joinable!(node_stats -> nodes (node_id));
+diesel::joinable!(refs -> nodes (node_id));
+diesel::joinable!(staged_files -> blobs (blob_id));
 
-diesel::allow_tables_to_appear_in_same_query!(blobs, head, node_files, node_parents, nodes,);
+diesel::allow_tables_to_appear_in_same_query!(
+    blobs,
+    config,
+    head,
+    node_files,
+    node_notes,
+    node_parents,
+    node_stats,
+    nodes,
+    reflog,
+    refs,
+    remotes,
+    staged_files,
+);
@@ -0,0 +1,441 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use diesel::prelude::*;
+
+use crate::schema::node_parents::dsl as node_parents_dsl;
+use crate::schema::nodes::dsl as nodes_dsl;
+use crate::vcs::commit::{changed_paths, collect_snapshot, now_unix_ms, write_node};
+use crate::vcs::db::to_db;
+use crate::vcs::lock::RepoLock;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::progress::Progress;
+use crate::vcs::repo::current_head;
+use crate::vcs::repository::Repository;
+use crate::vcs::search::prune_index;
+use crate::vcs::timeline::{manuscript_counts, save_node_stats};
+use crate::vcs::tree::node_tree_id;
+use crate::{AutosavePolicy, GcSummary, NodeId, NodeKind, Result, ThinSummary, WorkSpaceError};
+
+const HOUR_MS: i64 = 60 * 60 * 1000;
+const DAY_MS: i64 = 24 * HOUR_MS;
+const WEEK_MS: i64 = 7 * DAY_MS;
+// 이 기간 안의 자동 저장은 하루 단위로, 더 오래된 것은 주 단위로 하나씩 남긴다
+const DAILY_RETENTION_MS: i64 = 30 * DAY_MS;
+
+impl Repository {
+    // 자동 저장 API. 앱이 주기적으로 호출한다.
+    // 마지막 스냅샷(HEAD)에서 policy.interval_ms가 지나지 않았거나, only_when_changed인데
+    // 작업 트리가 HEAD와 같으면 아무것도 하지 않고 None을 돌려준다.
+    // 자동 저장이 막히면 안 되므로 pre-snapshot 훅은 실행하지 않는다.
+    pub fn autosave(&self, policy: &AutosavePolicy) -> Result<Option<NodeId>> {
+        let root = self.root();
+        let _lock = RepoLock::acquire(root)?;
+
+        let conn = &mut *self.conn()?;
+
+        let now = now_unix_ms();
+        let head = current_head(conn)?;
+
+        if let Some(head) = &head {
+            let head_created_at = nodes_dsl::nodes
+                .filter(nodes_dsl::id.eq(head))
+                .select(nodes_dsl::created_at_unix_ms)
+                .first::<i64>(conn)
+                .map_err(to_db)?;
+
+            if now - head_created_at < policy.interval_ms {
+                return Ok(None);
+            }
+        }
+
+        let snapshot = collect_snapshot(root, &Progress::default())?;
+        let changed = changed_paths(conn, head.as_deref(), &snapshot)?.len();
+        if policy.only_when_changed && changed == 0 {
+            return Ok(None);
+        }
+
+        let message = policy
+            .message_template
+            .replace("{time}", &format_utc_minutes(now))
+            .replace("{files}", &changed.to_string());
+        let message = match message.trim() {
+            "" => "autosave".to_string(),
+            text => text.to_string(),
+        };
+
+        let store = BlobStore::open(root, conn)?;
+        let id = write_node(
+            conn,
+            &store,
+            &message,
+            &snapshot,
+            NodeKind::Autosave,
+            "autosave",
+            &Progress::default(),
+        )?;
+        Ok(Some(id))
+    }
+
+    // 오래된 자동 저장을 솎아낸다: 최근 하루는 시간마다, 30일까지는 하루마다, 그 뒤로는 주마다
+    // 가장 최근 자동 저장 하나만 남긴다. 직접 만든 노드, HEAD, 태그 등 ref가 가리키는 노드는 건드리지 않는다.
+    //
+    // 노드 id에는 부모가 들어가므로 남는 노드의 부모는 바꾸지 않는다. 남는 노드가 하나도 딛고 있지 않은
+    // 자동 저장(체크아웃으로 버려진 가지 끝의 줄)은 지우고, 남는 노드의 조상인 자동 저장은 노드를 두고
+    // 파일 목록만 지운다(thinned). 솎아낸 노드는 log에 나오지 않고 체크아웃/비교할 수 없다.
+    // 더 이상 쓰이지 않는 blob은 gc로 정리한다.
+    pub fn thin_autosaves(&self) -> Result<ThinSummary> {
+        use crate::schema::node_notes::dsl as notes_dsl;
+        use crate::schema::refs::dsl as refs_dsl;
+
+        let root = self.root();
+        let _lock = RepoLock::acquire(root)?;
+
+        let conn = &mut *self.conn()?;
+
+        let now = now_unix_ms();
+
+        conn.transaction::<ThinSummary, WorkSpaceError, _>(|tx| {
+            let mut protected = refs_dsl::refs
+                .select(refs_dsl::node_id)
+                .load::<String>(tx)
+                .map_err(to_db)?
+                .into_iter()
+                .collect::<HashSet<_>>();
+            protected.extend(current_head(tx)?);
+            // 메모를 붙인 자동 저장은 누군가 다시 볼 노드다
+            protected.extend(
+                notes_dsl::node_notes
+                    .select(notes_dsl::node_id)
+                    .load::<String>(tx)
+                    .map_err(to_db)?,
+            );
+
+            let autosaves = nodes_dsl::nodes
+                .filter(nodes_dsl::kind.eq(NodeKind::Autosave.as_str()))
+                .select((
+                    nodes_dsl::id,
+                    nodes_dsl::created_at_unix_ms,
+                    nodes_dsl::thinned,
+                ))
+                .order((nodes_dsl::created_at_unix_ms.desc(), nodes_dsl::id.desc()))
+                .load::<(String, i64, bool)>(tx)
+                .map_err(to_db)?;
+
+            // 최신 순으로 보면서 구간마다 처음 본 것(가장 최근 것)을 남긴다.
+            // 이미 솎아낸 노드는 구간을 채우지 않고, 자식이 모두 지워지면 함께 지운다
+            let mut filled = HashSet::new();
+            let mut candidates = Vec::new();
+            let mut already_thinned = HashSet::new();
+            for (id, created_at, thinned) in &autosaves {
+                if *thinned {
+                    already_thinned.insert(id.clone());
+                    if !protected.contains(id) {
+                        candidates.push(id.clone());
+                    }
+                    continue;
+                }
+                let first_in_bucket = filled.insert(retention_bucket(now, *created_at));
+                if !first_in_bucket && !protected.contains(id) {
+                    candidates.push(id.clone());
+                }
+            }
+
+            let mut children: HashMap<String, Vec<String>> = HashMap::new();
+            for (child, parent) in node_parents_dsl::node_parents
+                .select((node_parents_dsl::node_id, node_parents_dsl::parent_id))
+                .load::<(String, String)>(tx)
+                .map_err(to_db)?
+            {
+                children.entry(parent).or_default().push(child);
+            }
+
+            // 자식이 모두 지워질 노드인 후보만 지운다. 자식부터 지워지므로 한 바퀴에 끝나지 않으면 다시 돈다
+            let mut removed = HashSet::new();
+            let mut removed_nodes = Vec::new();
+            loop {
+                let before = removed_nodes.len();
+                for id in &candidates {
+                    let leaf = children
+                        .get(id)
+                        .is_none_or(|c| c.iter().all(|child| removed.contains(child)));
+                    if !removed.contains(id) && leaf {
+                        remove_node(tx, id)?;
+                        removed.insert(id.clone());
+                        removed_nodes.push(id.clone());
+                    }
+                }
+                if removed_nodes.len() == before {
+                    break;
+                }
+            }
+            forget_removed_in_reflog(tx, &removed_nodes)?;
+
+            // 남은 후보는 남는 노드의 조상이다: id는 그대로 두고 파일 목록만 지운다
+            let mut store = None;
+            let mut thinned_nodes = Vec::new();
+            for id in &candidates {
+                if removed.contains(id) || already_thinned.contains(id) {
+                    continue;
+                }
+                thin_node(tx, root, &mut store, id)?;
+                thinned_nodes.push(id.clone());
+            }
+
+            Ok(ThinSummary {
+                kept_autosaves: autosaves.len()
+                    - removed_nodes.len()
+                    - thinned_nodes.len()
+                    - already_thinned.difference(&removed).count(),
+                removed_nodes,
+                thinned_nodes,
+            })
+        })
+    }
+
+    // 어떤 노드도 참조하지 않는 blob과, blobs에 행이 없는 외부 objects 파일을 지운다
+    pub fn gc(&self) -> Result<GcSummary> {
+        self.gc_with_progress(&Progress::default())
+    }
+
+    // objects 파일을 지울 때(Sweep) 진행 상황을 알린다. 중간에 취소해도 이미 지운 것만 지워진 상태로 남는다
+    pub fn gc_with_progress(&self, progress: &Progress) -> Result<GcSummary> {
+        use crate::schema::blobs::dsl as blobs_dsl;
+        use crate::schema::node_files::dsl as node_files_dsl;
+        use crate::schema::staged_files::dsl as staged_dsl;
+
+        let root = self.root();
+        let _lock = RepoLock::acquire(root)?;
+
+        let conn = &mut *self.conn()?;
+
+        progress.checkpoint()?;
+        // stage만 해 둔 blob도 남긴다
+        let removed_blobs = diesel::delete(
+            blobs_dsl::blobs
+                .filter(
+                    blobs_dsl::id
+                        .ne_all(node_files_dsl::node_files.select(node_files_dsl::blob_id)),
+                )
+                .filter(
+                    blobs_dsl::id.ne_all(
+                        staged_dsl::staged_files
+                            .filter(staged_dsl::blob_id.is_not_null())
+                            .select(staged_dsl::blob_id.assume_not_null()),
+                    ),
+                ),
+        )
+        .execute(conn)
+        .map_err(to_db)?;
+
+        prune_index(conn)?;
+
+        let removed_objects = BlobStore::open(root, conn)?.sweep(conn, progress)?;
+
+        Ok(GcSummary {
+            removed_blobs,
+            removed_objects,
+        })
+    }
+}
+
+pub fn autosave(root: &Path, policy: &AutosavePolicy) -> Result<Option<NodeId>> {
+    Repository::open(root)?.autosave(policy)
+}
+
+pub fn thin_autosaves(root: &Path) -> Result<ThinSummary> {
+    Repository::open(root)?.thin_autosaves()
+}
+
+pub fn gc(root: &Path) -> Result<GcSummary> {
+    Repository::open(root)?.gc()
+}
+
+pub fn gc_with_progress(root: &Path, progress: &Progress) -> Result<GcSummary> {
+    Repository::open(root)?.gc_with_progress(progress)
+}
+
+// (단위, 구간 번호): 단위가 다르면 다른 구간이다
+fn retention_bucket(now: i64, created_at: i64) -> (i64, i64) {
+    let age = now - created_at;
+    let unit = if age < DAY_MS {
+        HOUR_MS
+    } else if age < DAILY_RETENTION_MS {
+        DAY_MS
+    } else {
+        WEEK_MS
+    };
+
+    (unit, created_at.div_euclid(unit))
+}
+
+// 자식이 없는(또는 함께 지워진) 노드를 지운다. 다른 노드의 부모 목록은 건드리지 않는다
+fn remove_node(conn: &mut SqliteConnection, node_id: &str) -> Result<()> {
+    use crate::schema::node_files::dsl as node_files_dsl;
+    use crate::schema::node_stats::dsl as stats_dsl;
+
+    diesel::delete(node_parents_dsl::node_parents.filter(node_parents_dsl::node_id.eq(node_id)))
+        .execute(conn)
+        .map_err(to_db)?;
+    diesel::delete(node_files_dsl::node_files.filter(node_files_dsl::node_id.eq(node_id)))
+        .execute(conn)
+        .map_err(to_db)?;
+    diesel::delete(stats_dsl::node_stats.filter(stats_dsl::node_id.eq(node_id)))
+        .execute(conn)
+        .map_err(to_db)?;
+    diesel::delete(nodes_dsl::nodes.filter(nodes_dsl::id.eq(node_id)))
+        .execute(conn)
+        .map_err(to_db)?;
+
+    Ok(())
+}
+
+// 지운 노드를 가리키던 reflog 기록은 지우고, 지운 노드에서 출발한 기록은 출발점을 비운다
+fn forget_removed_in_reflog(conn: &mut SqliteConnection, removed: &[NodeId]) -> Result<()> {
+    use crate::schema::reflog::dsl as reflog_dsl;
+
+    diesel::delete(reflog_dsl::reflog.filter(reflog_dsl::new_node_id.eq_any(removed)))
+        .execute(conn)
+        .map_err(to_db)?;
+    diesel::update(reflog_dsl::reflog.filter(reflog_dsl::old_node_id.eq_any(removed)))
+        .set(reflog_dsl::old_node_id.eq(None::<String>))
+        .execute(conn)
+        .map_err(to_db)?;
+
+    Ok(())
+}
+
+// 노드의 파일 목록을 지우고 thinned로 표시한다. id를 검증할 tree_id와 진행 통계는 그 전에 채워 둔다
+fn thin_node(
+    conn: &mut SqliteConnection,
+    root: &Path,
+    store: &mut Option<BlobStore>,
+    node_id: &str,
+) -> Result<()> {
+    use crate::schema::node_files::dsl as node_files_dsl;
+    use crate::schema::node_stats::dsl as stats_dsl;
+
+    let tree_id = node_tree_id(conn, node_id)?;
+
+    let has_stats = stats_dsl::node_stats
+        .filter(stats_dsl::node_id.eq(node_id))
+        .count()
+        .get_result::<i64>(conn)
+        .map_err(to_db)?
+        > 0;
+    if !has_stats {
+        let store = match store {
+            Some(store) => store,
+            None => store.insert(BlobStore::open(root, conn)?),
+        };
+        let contents = store.node_contents(conn, node_id)?;
+        save_node_stats(
+            conn,
+            &node_id.to_string(),
+            manuscript_counts(contents.iter().map(|(_, c)| c.as_slice())),
+        )?;
+    }
+
+    diesel::delete(node_files_dsl::node_files.filter(node_files_dsl::node_id.eq(node_id)))
+        .execute(conn)
+        .map_err(to_db)?;
+    diesel::update(nodes_dsl::nodes.filter(nodes_dsl::id.eq(node_id)))
+        .set((nodes_dsl::tree_id.eq(&tree_id), nodes_dsl::thinned.eq(true)))
+        .execute(conn)
+        .map_err(to_db)?;
+
+    Ok(())
+}
+
+// 노드의 파일 목록을 읽기 전에 부른다: 없는 노드는 NodeNotFound, 솎아낸 노드는 SnapshotThinned
+pub(crate) fn require_snapshot(conn: &mut SqliteConnection, node_id: &str) -> Result<()> {
+    let thinned = nodes_dsl::nodes
+        .filter(nodes_dsl::id.eq(node_id))
+        .select(nodes_dsl::thinned)
+        .first::<bool>(conn)
+        .optional()
+        .map_err(to_db)?;
+
+    match thinned {
+        None => Err(WorkSpaceError::NodeNotFound(node_id.to_string())),
+        Some(true) => Err(WorkSpaceError::SnapshotThinned(node_id.to_string())),
+        Some(false) => Ok(()),
+    }
+}
+
+// 솎아낸 노드 -> 그 부모들
+pub(crate) fn thinned_parents(conn: &mut SqliteConnection) -> Result<HashMap<NodeId, Vec<NodeId>>> {
+    let thinned = nodes_dsl::nodes
+        .filter(nodes_dsl::thinned.eq(true))
+        .select(nodes_dsl::id)
+        .load::<String>(conn)
+        .map_err(to_db)?;
+
+    let mut parents: HashMap<NodeId, Vec<NodeId>> =
+        thinned.iter().map(|id| (id.clone(), Vec::new())).collect();
+    for (node_id, parent_id) in node_parents_dsl::node_parents
+        .filter(node_parents_dsl::node_id.eq_any(&thinned))
+        .order((node_parents_dsl::node_id, node_parents_dsl::ord))
+        .select((node_parents_dsl::node_id, node_parents_dsl::parent_id))
+        .load::<(String, String)>(conn)
+        .map_err(to_db)?
+    {
+        parents.entry(node_id).or_default().push(parent_id);
+    }
+
+    Ok(parents)
+}
+
+// 부모 중 솎아낸 노드는 그 위에서 처음 만나는 남은 조상으로 바꾼다(log, pickaxe에서 쓴다)
+pub(crate) fn visible_parents(
+    parents: &[NodeId],
+    thinned: &HashMap<NodeId, Vec<NodeId>>,
+) -> Vec<NodeId> {
+    let mut out = Vec::new();
+    let mut seen = HashSet::new();
+    let mut stack = parents.iter().rev().cloned().collect::<Vec<_>>();
+
+    while let Some(id) = stack.pop() {
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+        match thinned.get(&id) {
+            Some(grandparents) => stack.extend(grandparents.iter().rev().cloned()),
+            None => out.push(id),
+        }
+    }
+
+    out
+}
+
+// Unix ms -> "YYYY-MM-DD HH:MM" (UTC)
+pub(crate) fn format_utc_minutes(unix_ms: i64) -> String {
+    let minutes = unix_ms.div_euclid(60_000);
+    let days = minutes.div_euclid(24 * 60);
+    let minute_of_day = minutes.rem_euclid(24 * 60);
+
+    // Howard Hinnant의 civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        minute_of_day / 60,
+        minute_of_day % 60
+    )
+}
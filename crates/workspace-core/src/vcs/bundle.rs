@@ -0,0 +1,723 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use diesel::prelude::*;
+
+use crate::schema::{blobs, node_files, node_parents, nodes};
+use crate::vcs::commit::{legacy_node_id, new_node_id};
+use crate::vcs::crypto::{check_plaintext_export, open_message, seal_message};
+use crate::vcs::db::to_db;
+use crate::vcs::graph::reachable_from;
+use crate::vcs::lock::RepoLock;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::progress::{Progress, ProgressPhase};
+use crate::vcs::repository::Repository;
+use crate::vcs::revision::resolve;
+use crate::vcs::snapshot::{blob_id_for_content, is_safe_rel_path};
+use crate::vcs::timeline::{manuscript_counts, save_node_stats};
+use crate::vcs::tree::tree_id_for_entries;
+use crate::{BundleRef, BundleSummary, NodeId, NodeKind, Result, UnbundleSummary, WorkSpaceError};
+
+const BUNDLE_MAGIC: &[u8] = b"NOVELBUNDLE\n";
+const BUNDLE_VERSION: u32 = 1;
+const CHECKSUM_LEN: usize = 32;
+// collect_pack이 blob을 한 번에 읽는 개수. 이 단위로 진행 상황을 알리고 취소를 확인한다
+const PACK_CHUNK: usize = 64;
+
+// 번들/동기화가 주고받는 노드 한 개(메타 + 부모 + 파일 테이블)
+#[derive(Debug, Clone)]
+pub(crate) struct PackNode {
+    pub id: NodeId,
+    pub message: String,
+    pub created_at_unix_ms: i64,
+    pub parents: Vec<NodeId>,
+    pub kind: NodeKind,
+    pub files: Vec<(String, String)>,
+    // 솎아낸 노드는 파일 목록 대신 트리 id만 보낸다
+    pub thinned_tree_id: Option<String>,
+}
+
+// 다른 저장소로 옮길 객체 묶음. 번들 파일은 이것을 직렬화한 것이다.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ObjectPack {
+    pub refs: Vec<BundleRef>,
+    // 번들에 없지만 받는 쪽에 이미 있어야 하는 부모 노드
+    pub prerequisites: Vec<NodeId>,
+    // 부모가 항상 자식보다 먼저 오도록 정렬되어 있다
+    pub nodes: Vec<PackNode>,
+    pub blobs: Vec<(String, Vec<u8>)>,
+}
+
+impl Repository {
+    // 번들 생성 API: refs(리비전: HEAD, 태그, 노드 id ...)에서 도달 가능한 전체 히스토리를 담는다
+    pub fn bundle_create(&self, out_path: &Path, refs: &[&str]) -> Result<BundleSummary> {
+        self.write_bundle(out_path, refs, None, &Progress::default())
+    }
+
+    // base 노드(와 그 조상)에 이미 있는 노드/blob은 빼고 담는 증분 번들
+    pub fn bundle_create_since(
+        &self,
+        out_path: &Path,
+        refs: &[&str],
+        base: &str,
+    ) -> Result<BundleSummary> {
+        self.write_bundle(out_path, refs, Some(base), &Progress::default())
+    }
+
+    // blob을 읽을 때(Pack) 진행 상황을 알린다. base가 있으면 증분 번들. 취소되면 파일을 쓰지 않는다
+    pub fn bundle_create_with_progress(
+        &self,
+        out_path: &Path,
+        refs: &[&str],
+        base: Option<&str>,
+        progress: &Progress,
+    ) -> Result<BundleSummary> {
+        self.write_bundle(out_path, refs, base, progress)
+    }
+
+    // 번들 가져오기 API: 이미 있는 객체는 건너뛰므로 여러 번 실행해도 결과가 같다
+    pub fn bundle_unbundle(&self, bundle_path: &Path) -> Result<UnbundleSummary> {
+        self.bundle_unbundle_with_progress(bundle_path, &Progress::default())
+    }
+
+    // blob을 넣을 때(Store) 진행 상황을 알린다. 취소되면 트랜잭션이 롤백되어 아무것도 들어가지 않는다
+    pub fn bundle_unbundle_with_progress(
+        &self,
+        bundle_path: &Path,
+        progress: &Progress,
+    ) -> Result<UnbundleSummary> {
+        let root = self.root();
+        let bytes = fs::read(bundle_path)?;
+        let pack = decode_pack(&bytes)?;
+
+        let _lock = RepoLock::acquire(root)?;
+        let conn = &mut *self.conn()?;
+
+        let store = BlobStore::open(root, conn)?;
+        let (imported_nodes, imported_blobs) = apply_pack(conn, &store, &pack, progress)?;
+
+        Ok(UnbundleSummary {
+            refs: pack.refs,
+            imported_nodes,
+            imported_blobs,
+        })
+    }
+
+    fn write_bundle(
+        &self,
+        out_path: &Path,
+        refs: &[&str],
+        base: Option<&str>,
+        progress: &Progress,
+    ) -> Result<BundleSummary> {
+        let root = self.root();
+        if refs.is_empty() {
+            return Err(WorkSpaceError::InvalidInput(
+                "bundle needs at least one ref".to_string(),
+            ));
+        }
+
+        let conn = &mut *self.conn()?;
+
+        let mut bundle_refs = Vec::with_capacity(refs.len());
+        for name in refs {
+            let node_id = resolve(conn, name)?;
+            bundle_refs.push(BundleRef {
+                name: name.to_string(),
+                node_id,
+            });
+        }
+
+        let bases = match base {
+            Some(base) => vec![resolve(conn, base)?],
+            None => Vec::new(),
+        };
+
+        let tips = bundle_refs
+            .iter()
+            .map(|r| r.node_id.clone())
+            .collect::<Vec<_>>();
+        let store = BlobStore::open(root, conn)?;
+        let mut pack = collect_pack(conn, &store, &tips, &bases, progress)?;
+        pack.refs = bundle_refs;
+
+        let bytes = encode_pack(&pack);
+        fs::write(out_path, bytes)?;
+
+        Ok(BundleSummary {
+            refs: pack.refs,
+            prerequisites: pack.prerequisites,
+            node_count: pack.nodes.len(),
+            blob_count: pack.blobs.len(),
+        })
+    }
+}
+
+pub fn bundle_create(root: &Path, out_path: &Path, refs: &[&str]) -> Result<BundleSummary> {
+    Repository::open(root)?.bundle_create(out_path, refs)
+}
+
+pub fn bundle_create_since(
+    root: &Path,
+    out_path: &Path,
+    refs: &[&str],
+    base: &str,
+) -> Result<BundleSummary> {
+    Repository::open(root)?.bundle_create_since(out_path, refs, base)
+}
+
+pub fn bundle_create_with_progress(
+    root: &Path,
+    out_path: &Path,
+    refs: &[&str],
+    base: Option<&str>,
+    progress: &Progress,
+) -> Result<BundleSummary> {
+    Repository::open(root)?.bundle_create_with_progress(out_path, refs, base, progress)
+}
+
+pub fn bundle_unbundle(root: &Path, bundle_path: &Path) -> Result<UnbundleSummary> {
+    Repository::open(root)?.bundle_unbundle(bundle_path)
+}
+
+pub fn bundle_unbundle_with_progress(
+    root: &Path,
+    bundle_path: &Path,
+    progress: &Progress,
+) -> Result<UnbundleSummary> {
+    Repository::open(root)?.bundle_unbundle_with_progress(bundle_path, progress)
+}
+
+// tips에서 도달 가능하지만 bases에서는 도달 불가능한 노드와, 그 노드들만 참조하는 blob을 모은다.
+// pack은 평문이므로 암호화 저장소면 set_plaintext_export로 허용해 둬야 한다
+pub(crate) fn collect_pack(
+    conn: &mut SqliteConnection,
+    store: &BlobStore,
+    tips: &[NodeId],
+    bases: &[NodeId],
+    progress: &Progress,
+) -> Result<ObjectPack> {
+    check_plaintext_export(conn, store.cipher())?;
+
+    let wanted = reachable_from(conn, tips)?;
+    let known = reachable_from(conn, bases)?;
+
+    let ids = wanted.difference(&known).cloned().collect::<Vec<_>>();
+
+    let node_rows = nodes::dsl::nodes
+        .filter(nodes::dsl::id.eq_any(&ids))
+        .select((
+            nodes::dsl::id,
+            nodes::dsl::message,
+            nodes::dsl::created_at_unix_ms,
+            nodes::dsl::tree_id,
+            nodes::dsl::thinned,
+            nodes::dsl::kind,
+        ))
+        .load::<(String, String, i64, Option<String>, bool, String)>(conn)
+        .map_err(to_db)?;
+
+    let mut parents: HashMap<String, Vec<String>> = HashMap::new();
+    for (node_id, parent_id) in node_parents::dsl::node_parents
+        .filter(node_parents::dsl::node_id.eq_any(&ids))
+        .order((node_parents::dsl::node_id, node_parents::dsl::ord.asc()))
+        .select((node_parents::dsl::node_id, node_parents::dsl::parent_id))
+        .load::<(String, String)>(conn)
+        .map_err(to_db)?
+    {
+        parents.entry(node_id).or_default().push(parent_id);
+    }
+
+    let mut files: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for (node_id, path, blob_id) in node_files::dsl::node_files
+        .filter(node_files::dsl::node_id.eq_any(&ids))
+        .order((node_files::dsl::node_id, node_files::dsl::path))
+        .select((
+            node_files::dsl::node_id,
+            node_files::dsl::path,
+            node_files::dsl::blob_id,
+        ))
+        .load::<(String, String, String)>(conn)
+        .map_err(to_db)?
+    {
+        files.entry(node_id).or_default().push((path, blob_id));
+    }
+
+    let mut pack_nodes = node_rows
+        .into_iter()
+        .map(
+            |(id, message, created_at_unix_ms, tree_id, thinned, kind)| {
+                // 번들에는 평문을 담고(내보내기를 허용한 경우만), 받는 저장소가 자기 키로 다시 암호화한다
+                let message = open_message(store.cipher(), id.as_bytes(), message)?;
+                Ok(PackNode {
+                    parents: parents.remove(&id).unwrap_or_default(),
+                    kind: NodeKind::from_db(&kind),
+                    files: files.remove(&id).unwrap_or_default(),
+                    // 솎아낼 때 tree_id를 채워 둔다
+                    thinned_tree_id: if thinned { tree_id } else { None },
+                    id,
+                    message,
+                    created_at_unix_ms,
+                })
+            },
+        )
+        .collect::<Result<Vec<_>>>()?;
+    sort_parents_first(&mut pack_nodes);
+
+    let included = ids.iter().cloned().collect::<HashSet<_>>();
+    let mut prerequisites = pack_nodes
+        .iter()
+        .flat_map(|n| n.parents.iter())
+        .filter(|p| !included.contains(*p))
+        .cloned()
+        .collect::<Vec<_>>();
+    prerequisites.sort();
+    prerequisites.dedup();
+
+    let known_ids = known.into_iter().collect::<Vec<_>>();
+    let known_blobs = node_files::dsl::node_files
+        .filter(node_files::dsl::node_id.eq_any(&known_ids))
+        .select(node_files::dsl::blob_id)
+        .distinct()
+        .load::<String>(conn)
+        .map_err(to_db)?
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    let mut blob_ids = pack_nodes
+        .iter()
+        .flat_map(|n| n.files.iter().map(|(_, blob_id)| blob_id.clone()))
+        .filter(|id| !known_blobs.contains(id))
+        .collect::<Vec<_>>();
+    blob_ids.sort();
+    blob_ids.dedup();
+
+    let mut pack_blobs = Vec::with_capacity(blob_ids.len());
+    let mut bytes = 0;
+    for chunk in blob_ids.chunks(PACK_CHUNK) {
+        progress.checkpoint()?;
+        for (id, content) in store.load(conn, chunk)? {
+            bytes += content.len() as u64;
+            pack_blobs.push((id, content));
+        }
+        progress.report(ProgressPhase::Pack, pack_blobs.len(), blob_ids.len(), bytes);
+    }
+    pack_blobs.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(ObjectPack {
+        refs: Vec::new(),
+        prerequisites,
+        nodes: pack_nodes,
+        blobs: pack_blobs,
+    })
+}
+
+// 부모 -> 자식 순서(위상 정렬, Kahn). 함께 준비된 노드끼리는 생성 시각, id 순으로 고정한다.
+fn sort_parents_first(pack_nodes: &mut Vec<PackNode>) {
+    let mut slots = std::mem::take(pack_nodes)
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+    let index_of = slots
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.as_ref().unwrap().id.clone(), i))
+        .collect::<HashMap<_, _>>();
+
+    // 묶음 안에 있는 부모 수와, 부모 -> 자식 목록
+    let mut in_degree = vec![0usize; slots.len()];
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); slots.len()];
+    for (i, node) in slots.iter().enumerate() {
+        for parent in &node.as_ref().unwrap().parents {
+            if let Some(&p) = index_of.get(parent) {
+                in_degree[i] += 1;
+                children[p].push(i);
+            }
+        }
+    }
+
+    let key = |slots: &[Option<PackNode>], i: usize| {
+        let node = slots[i].as_ref().unwrap();
+        (node.created_at_unix_ms, node.id.clone(), i)
+    };
+    let mut ready = (0..slots.len())
+        .filter(|&i| in_degree[i] == 0)
+        .map(|i| key(&slots, i))
+        .collect::<BTreeSet<_>>();
+
+    while let Some(entry) = ready.pop_first() {
+        let i = entry.2;
+        for &child in &children[i] {
+            in_degree[child] -= 1;
+            if in_degree[child] == 0 {
+                ready.insert(key(&slots, child));
+            }
+        }
+        pack_nodes.push(slots[i].take().unwrap());
+    }
+
+    // 순환은 생길 수 없지만, 생기더라도 빠뜨리지 않고 남은 순서대로 내보낸다
+    let mut rest = slots.into_iter().flatten().collect::<Vec<_>>();
+    rest.sort_by(|a, b| (a.created_at_unix_ms, &a.id).cmp(&(b.created_at_unix_ms, &b.id)));
+    pack_nodes.extend(rest);
+}
+
+// 들어온 노드의 경로가 작업 트리 안에 있고, id가 내용(메시지/첫 부모/트리/시각)에서 나온 것인지 본다.
+// tree_id가 id에 들어가기 전의 노드는 v1 id와 맞춰 본다. 솎아낸 노드는 보낸 트리 id로 맞춰 본다
+fn verify_pack_node(node: &PackNode) -> Result<String> {
+    if node.thinned_tree_id.is_some() && !node.files.is_empty() {
+        return Err(WorkSpaceError::CorruptData(format!(
+            "thinned node has files: {}",
+            node.id
+        )));
+    }
+    if let Some((path, _)) = node.files.iter().find(|(path, _)| !is_safe_rel_path(path)) {
+        return Err(WorkSpaceError::CorruptData(format!(
+            "unsafe path in node {}: {}",
+            node.id, path
+        )));
+    }
+
+    let tree_id = match &node.thinned_tree_id {
+        Some(tree_id) => tree_id.clone(),
+        None => tree_id_for_entries(node.files.iter().map(|(p, b)| (p.as_str(), b.as_str())))?,
+    };
+    let parent = node.parents.first().map(String::as_str);
+    let matches = new_node_id(&node.message, parent, &tree_id, node.created_at_unix_ms) == node.id
+        || legacy_node_id(&node.message, parent, node.created_at_unix_ms) == node.id;
+    if !matches {
+        return Err(WorkSpaceError::CorruptData(format!(
+            "node content does not match id: {}",
+            node.id
+        )));
+    }
+
+    Ok(tree_id)
+}
+
+// 받는 쪽 DB에 pack을 한 트랜잭션으로 넣는다. (새로 들어간 노드 수, blob 수)를 돌려준다
+pub(crate) fn apply_pack(
+    conn: &mut SqliteConnection,
+    store: &BlobStore,
+    pack: &ObjectPack,
+    progress: &Progress,
+) -> Result<(usize, usize)> {
+    for (id, content) in &pack.blobs {
+        if blob_id_for_content(content) != *id {
+            return Err(WorkSpaceError::CorruptData(format!(
+                "blob content does not match id: {}",
+                id
+            )));
+        }
+    }
+
+    conn.transaction::<(usize, usize), WorkSpaceError, _>(|tx| {
+        for prerequisite in &pack.prerequisites {
+            let present = nodes::dsl::nodes
+                .filter(nodes::dsl::id.eq(prerequisite))
+                .count()
+                .get_result::<i64>(tx)
+                .map_err(to_db)?;
+
+            if present == 0 {
+                return Err(WorkSpaceError::NotFound(format!(
+                    "missing prerequisite node: {}",
+                    prerequisite
+                )));
+            }
+        }
+
+        let mut imported_blobs = 0;
+        let mut bytes = 0;
+        for (index, (id, content)) in pack.blobs.iter().enumerate() {
+            progress.checkpoint()?;
+            imported_blobs += store.put(tx, id, content)?;
+            bytes += content.len() as u64;
+            progress.report(ProgressPhase::Store, index + 1, pack.blobs.len(), bytes);
+        }
+
+        let mut imported_nodes = 0;
+        let mut blob_counts: HashMap<String, (i64, i64)> = HashMap::new();
+        for node in &pack.nodes {
+            let tree_id = verify_pack_node(node)?;
+            let inserted = diesel::insert_into(nodes::dsl::nodes)
+                .values((
+                    nodes::dsl::id.eq(&node.id),
+                    nodes::dsl::message.eq(seal_message(
+                        store.cipher(),
+                        node.id.as_bytes(),
+                        &node.message,
+                    )),
+                    nodes::dsl::created_at_unix_ms.eq(node.created_at_unix_ms),
+                    nodes::dsl::tree_id.eq(&tree_id),
+                    nodes::dsl::thinned.eq(node.thinned_tree_id.is_some()),
+                    nodes::dsl::kind.eq(node.kind.as_str()),
+                ))
+                .on_conflict(nodes::dsl::id)
+                .do_nothing()
+                .execute(tx)
+                .map_err(to_db)?;
+
+            // 이미 있는 노드는 부모/파일 테이블도 이미 있다
+            if inserted == 0 {
+                continue;
+            }
+            imported_nodes += 1;
+
+            for (ord, parent_id) in node.parents.iter().enumerate() {
+                diesel::insert_into(node_parents::dsl::node_parents)
+                    .values((
+                        node_parents::dsl::node_id.eq(&node.id),
+                        node_parents::dsl::parent_id.eq(parent_id),
+                        node_parents::dsl::ord.eq(ord as i32),
+                    ))
+                    .execute(tx)
+                    .map_err(to_db)?;
+            }
+
+            for (path, blob_id) in &node.files {
+                let blob_present = blobs::dsl::blobs
+                    .filter(blobs::dsl::id.eq(blob_id))
+                    .count()
+                    .get_result::<i64>(tx)
+                    .map_err(to_db)?;
+
+                if blob_present == 0 {
+                    return Err(WorkSpaceError::NotFound(format!(
+                        "missing blob {} for {}",
+                        blob_id, path
+                    )));
+                }
+
+                diesel::insert_into(node_files::dsl::node_files)
+                    .values((
+                        node_files::dsl::node_id.eq(&node.id),
+                        node_files::dsl::path.eq(path),
+                        node_files::dsl::blob_id.eq(blob_id),
+                    ))
+                    .execute(tx)
+                    .map_err(to_db)?;
+            }
+
+            // 커밋처럼 진행 통계를 남긴다. 노드끼리 겹치는 blob은 한 번만 센다
+            if node.thinned_tree_id.is_none() {
+                let mut totals = (0, 0);
+                for (_, blob_id) in &node.files {
+                    let counts = match blob_counts.get(blob_id) {
+                        Some(counts) => *counts,
+                        None => {
+                            let loaded = store.load(tx, std::slice::from_ref(blob_id))?;
+                            let counts =
+                                manuscript_counts(loaded.iter().map(|(_, c)| c.as_slice()));
+                            blob_counts.insert(blob_id.clone(), counts);
+                            counts
+                        }
+                    };
+                    totals = (totals.0 + counts.0, totals.1 + counts.1);
+                }
+                save_node_stats(tx, &node.id, totals)?;
+            }
+        }
+
+        Ok((imported_nodes, imported_blobs))
+    })
+}
+
+// 번들 바이너리 포맷:
+// magic, version, refs, prerequisites, nodes, blobs, 마지막 32바이트는 앞 전체의 sha256
+pub(crate) fn encode_pack(pack: &ObjectPack) -> Vec<u8> {
+    use sha2::{Digest, Sha256};
+
+    let mut w = PackWriter::default();
+    w.buf.extend_from_slice(BUNDLE_MAGIC);
+    w.put_u32(BUNDLE_VERSION);
+
+    w.put_len(pack.refs.len());
+    for r in &pack.refs {
+        w.put_str(&r.name);
+        w.put_str(&r.node_id);
+    }
+
+    w.put_len(pack.prerequisites.len());
+    for id in &pack.prerequisites {
+        w.put_str(id);
+    }
+
+    w.put_len(pack.nodes.len());
+    for node in &pack.nodes {
+        w.put_str(&node.id);
+        w.put_str(&node.message);
+        w.put_i64(node.created_at_unix_ms);
+        w.put_len(node.parents.len());
+        for parent in &node.parents {
+            w.put_str(parent);
+        }
+        w.put_str(node.kind.as_str());
+        w.put_len(node.files.len());
+        for (path, blob_id) in &node.files {
+            w.put_str(path);
+            w.put_str(blob_id);
+        }
+        // 솎아내지 않은 노드는 빈 문자열
+        w.put_str(node.thinned_tree_id.as_deref().unwrap_or(""));
+    }
+
+    w.put_len(pack.blobs.len());
+    for (id, content) in &pack.blobs {
+        w.put_str(id);
+        w.put_bytes(content);
+    }
+
+    let checksum = Sha256::digest(&w.buf);
+    w.buf.extend_from_slice(&checksum);
+    w.buf
+}
+
+pub(crate) fn decode_pack(bytes: &[u8]) -> Result<ObjectPack> {
+    use sha2::{Digest, Sha256};
+
+    if bytes.len() < BUNDLE_MAGIC.len() + CHECKSUM_LEN || !bytes.starts_with(BUNDLE_MAGIC) {
+        return Err(invalid_bundle("not a bundle file"));
+    }
+
+    let (body, checksum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+    if Sha256::digest(body).as_slice() != checksum {
+        return Err(invalid_bundle("checksum mismatch"));
+    }
+
+    let mut r = PackReader {
+        buf: &body[BUNDLE_MAGIC.len()..],
+    };
+
+    let version = r.u32()?;
+    if version != BUNDLE_VERSION {
+        return Err(invalid_bundle(&format!(
+            "unsupported bundle version: {}",
+            version
+        )));
+    }
+
+    let mut pack = ObjectPack::default();
+
+    for _ in 0..r.u32()? {
+        pack.refs.push(BundleRef {
+            name: r.string()?,
+            node_id: r.string()?,
+        });
+    }
+
+    for _ in 0..r.u32()? {
+        pack.prerequisites.push(r.string()?);
+    }
+
+    for _ in 0..r.u32()? {
+        let id = r.string()?;
+        let message = r.string()?;
+        let created_at_unix_ms = r.i64()?;
+
+        let mut parents = Vec::new();
+        for _ in 0..r.u32()? {
+            parents.push(r.string()?);
+        }
+        let kind = NodeKind::from_db(&r.string()?);
+
+        let mut files = Vec::new();
+        for _ in 0..r.u32()? {
+            files.push((r.string()?, r.string()?));
+        }
+        let thinned_tree_id = Some(r.string()?).filter(|t| !t.is_empty());
+
+        pack.nodes.push(PackNode {
+            id,
+            message,
+            created_at_unix_ms,
+            parents,
+            kind,
+            files,
+            thinned_tree_id,
+        });
+    }
+
+    for _ in 0..r.u32()? {
+        let id = r.string()?;
+        let content = r.bytes()?.to_vec();
+        pack.blobs.push((id, content));
+    }
+
+    if !r.buf.is_empty() {
+        return Err(invalid_bundle("trailing data"));
+    }
+
+    Ok(pack)
+}
+
+fn invalid_bundle(reason: &str) -> WorkSpaceError {
+    WorkSpaceError::CorruptData(format!("invalid bundle: {}", reason))
+}
+
+#[derive(Default)]
+struct PackWriter {
+    buf: Vec<u8>,
+}
+
+impl PackWriter {
+    fn put_u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn put_len(&mut self, len: usize) {
+        self.put_u32(len as u32);
+    }
+
+    fn put_i64(&mut self, v: i64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn put_bytes(&mut self, bytes: &[u8]) {
+        self.buf
+            .extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn put_str(&mut self, s: &str) {
+        self.put_bytes(s.as_bytes());
+    }
+}
+
+struct PackReader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> PackReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.buf.len() < n {
+            return Err(invalid_bundle("unexpected end of data"));
+        }
+        let (head, rest) = self.buf.split_at(n);
+        self.buf = rest;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn i64(&mut self) -> Result<i64> {
+        let bytes = self.take(8)?;
+        Ok(i64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.take(8)?;
+        let len = u64::from_le_bytes(len.try_into().unwrap());
+        let len = usize::try_from(len).map_err(|_| invalid_bundle("length overflow"))?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let bytes = self.bytes()?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid_bundle("invalid utf-8 string"))
+    }
+}
@@ -1,73 +1,168 @@
-use std::fs;
+use std::collections::HashSet;
 use std::path::Path;
 
-use diesel::{dsl::exists, prelude::*, select};
-use diesel_migrations::MigrationHarness;
+use diesel::prelude::*;
 
-use crate::vcs::db::{MIGRATIONS, open_connection, to_io};
-use crate::vcs::snapshot::{collect_files_in_workspace, normalize_rel_path};
+use crate::vcs::db::meta_dir;
+use crate::vcs::hooks::{POST_CHECKOUT, run_hook};
+use crate::vcs::journal::CheckoutTxn;
+use crate::vcs::lock::RepoLock;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::progress::{Progress, ProgressPhase};
+use crate::vcs::reflog::{move_head, short_id};
+use crate::vcs::repo::current_head;
+use crate::vcs::repository::Repository;
+use crate::vcs::revision::resolve;
+use crate::vcs::snapshot::{collect_files_in_workspace, is_safe_rel_path, normalize_rel_path};
 use crate::{Result, WorkSpaceError};
 
-// 체크아웃 API 스텁
-pub fn checkout(root: &Path, target_node_id: &str) -> Result<()> {
-    use crate::schema::blobs::dsl as blobs_dsl;
-    use crate::schema::head::dsl as head_dsl;
-    use crate::schema::node_files::dsl as node_files_dsl;
-    use crate::schema::nodes::dsl as nodes_dsl;
-
-    let mut conn = open_connection(root)?;
-    conn.run_pending_migrations(MIGRATIONS).map_err(to_io)?;
-
-    let node_exists = select(exists(diesel::QueryDsl::filter(
-        nodes_dsl::nodes,
-        nodes_dsl::id.eq(target_node_id),
-    )))
-    .get_result::<bool>(&mut conn)
-    .map_err(to_io)?;
-
-    if !node_exists {
-        return Err(WorkSpaceError::Io(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            format!("node not found: {}", target_node_id),
-        )));
+impl Repository {
+    // 체크아웃 API: revision은 resolve_revision이 받는 모든 형식을 쓸 수 있다
+    pub fn checkout(&self, revision: &str) -> Result<()> {
+        self.checkout_with_reason(revision, "checkout", &Progress::default())
+    }
+
+    // 파일을 stage에 쓸 때(Write) 진행 상황을 알린다.
+    // 취소되면 작업 트리를 바꾸기 전에 멈추므로 작업 트리와 HEAD는 그대로다
+    pub fn checkout_with_progress(&self, revision: &str, progress: &Progress) -> Result<()> {
+        self.checkout_with_reason(revision, "checkout", progress)
     }
 
-    let rows = node_files_dsl::node_files
-        .inner_join(blobs_dsl::blobs.on(node_files_dsl::blob_id.eq(blobs_dsl::id)))
-        .filter(node_files_dsl::node_id.eq(target_node_id))
-        .select((node_files_dsl::path, blobs_dsl::content))
-        .load::<(String, Vec<u8>)>(&mut conn)
-        .map_err(to_io)?;
+    // reason은 reflog에 남는 HEAD 이동 사유(checkout, pull, undo ...).
+    // 끝나면 post-checkout 훅을 (이전 노드, 새 노드) 인자로 실행한다. 이전 노드가 없으면 빈 문자열
+    pub(crate) fn checkout_with_reason(
+        &self,
+        revision: &str,
+        reason: &str,
+        progress: &Progress,
+    ) -> Result<()> {
+        let _lock = RepoLock::acquire(self.root())?;
+        let conn = &mut *self.conn()?;
+
+        self.checkout_in(conn, revision, reason, progress)
+    }
+
+    // checkout_with_reason 본체. 호출하는 쪽이 저장소 잠금과 연결을 잡고 있다
+    pub(crate) fn checkout_in(
+        &self,
+        conn: &mut SqliteConnection,
+        revision: &str,
+        reason: &str,
+        progress: &Progress,
+    ) -> Result<()> {
+        let root = self.root();
+
+        // 리비전(접두사, HEAD~1, 태그 ...)을 노드 id로 바꾼다
+        let target_node_id = &resolve(conn, revision)?;
+
+        // 없는 노드(NodeNotFound)나 솎아낸 노드(SnapshotThinned)는 여기서 거부된다.
+        // 빈 트리로 보고 체크아웃하면 추적 중인 파일이 모두 지워진다
+        let rows = BlobStore::open(root, conn)?.node_contents(conn, target_node_id)?;
+
+        // 잠금을 잡고 있으므로 트랜잭션 밖에서 읽어도 HEAD가 바뀌지 않는다
+        let old_head = current_head(conn)?;
+        let changed = write_work_tree(
+            root,
+            target_node_id,
+            old_head.as_deref(),
+            &rows,
+            progress,
+            || {
+                conn.transaction::<_, WorkSpaceError, _>(|tx| {
+                    move_head(
+                        tx,
+                        target_node_id,
+                        reason,
+                        &format!(
+                            "moving from {} to {}",
+                            short_id(old_head.as_deref()),
+                            short_id(Some(target_node_id))
+                        ),
+                    )
+                })
+                .map(|_| ())
+            },
+        )?;
+
+        // 체크아웃은 이미 끝났으므로 훅이 실패해도(실행조차 못 해도) 결과는 바뀌지 않는다
+        let _ = run_hook(
+            root,
+            POST_CHECKOUT,
+            &[old_head.as_deref().unwrap_or(""), target_node_id],
+            &changed,
+        );
+        Ok(())
+    }
+}
+
+// 작업 트리를 rows(경로, 내용)로 바꾸고 move_head로 HEAD를 옮긴다. 호출하는 쪽이 저장소 잠금을 잡는다.
+// 바뀔 파일을 모두 stage에 쓴 뒤 journal을 남기고 한꺼번에 바꿔 넣는다. 중간에 실패하면 원래 작업 트리로 돌아간다.
+// 돌려주는 값은 post-checkout 훅에 넘길 목록: 지워지거나 내용이 달라지는 경로
+pub(crate) fn write_work_tree(
+    root: &Path,
+    target_node_id: &str,
+    old_head: Option<&str>,
+    rows: &[(String, Vec<u8>)],
+    progress: &Progress,
+    move_head: impl FnOnce() -> Result<()>,
+) -> Result<Vec<String>> {
+    // 번들/동기화로 들어온 노드일 수 있으니 작업 트리 밖 경로는 읽기 전에 거른다
+    if let Some((rel, _)) = rows.iter().find(|(rel, _)| !is_safe_rel_path(rel)) {
+        return Err(WorkSpaceError::CorruptData(format!(
+            "unsafe path in snapshot of {}: {}",
+            target_node_id, rel
+        )));
+    }
 
     let canonical_root = root.canonicalize()?;
     let current = collect_files_in_workspace(&canonical_root)?
         .into_iter()
         .map(|p| normalize_rel_path(&p))
-        .collect::<std::collections::HashSet<_>>();
-
-    let target = rows
-        .iter()
-        .map(|(p, _)| p.clone())
-        .collect::<std::collections::HashSet<_>>();
+        .collect::<HashSet<_>>();
 
-    for rel in current.difference(&target) {
-        let abs = canonical_root.join(rel);
-        fs::remove_file(abs)?;
-    }
+    let target = rows.iter().map(|(p, _)| p.clone()).collect::<HashSet<_>>();
 
+    let mut changed = current.difference(&target).cloned().collect::<Vec<_>>();
     for (rel, content) in rows {
-        let abs = canonical_root.join(rel);
+        if !current.contains(rel) || std::fs::read(canonical_root.join(rel))? != *content {
+            changed.push(rel.clone());
+        }
+    }
+    changed.sort();
 
-        if let Some(parent) = abs.parent() {
-            fs::create_dir_all(parent)?;
+    let mut txn = CheckoutTxn::begin(&canonical_root, &meta_dir(root)?, target_node_id, old_head)?;
+    let staged = (|| -> Result<()> {
+        for rel in current.difference(&target) {
+            txn.stage_remove(rel)?;
         }
-        fs::write(abs, content)?;
+        let mut bytes = 0;
+        for (index, (rel, content)) in rows.iter().enumerate() {
+            progress.checkpoint()?;
+            txn.stage_write(rel, content)?;
+            bytes += content.len() as u64;
+            progress.report(ProgressPhase::Write, index + 1, rows.len(), bytes);
+        }
+        txn.apply()
+    })();
+    if let Err(e) = staged {
+        txn.abort();
+        return Err(e);
+    }
+
+    if let Err(e) = move_head() {
+        txn.abort();
+        return Err(e);
     }
 
-    diesel::update(head_dsl::head)
-        .set(head_dsl::node_id.eq(Some(target_node_id.to_string())))
-        .execute(&mut conn)
-        .map_err(to_io)?;
+    txn.mark_committed()?;
+    txn.finish()?;
+    Ok(changed)
+}
+
+pub fn checkout(root: &Path, revision: &str) -> Result<()> {
+    Repository::open(root)?.checkout(revision)
+}
 
-    Ok(())
+pub fn checkout_with_progress(root: &Path, revision: &str, progress: &Progress) -> Result<()> {
+    Repository::open(root)?.checkout_with_progress(revision, progress)
 }
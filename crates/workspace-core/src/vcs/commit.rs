@@ -1,41 +1,133 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
 use diesel::prelude::*;
-use diesel_migrations::MigrationHarness;
 
-use crate::vcs::db::{MIGRATIONS, open_connection, to_io};
+use crate::vcs::crypto::{REFLOG_AAD, seal_message};
+use crate::vcs::db::to_db;
+use crate::vcs::hooks::{PRE_SNAPSHOT, run_required_hook};
+use crate::vcs::lock::RepoLock;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::progress::{Progress, ProgressPhase};
+use crate::vcs::reflog::move_head;
+use crate::vcs::repo::current_head;
+use crate::vcs::repository::Repository;
 use crate::vcs::snapshot::{
     SnapshotFile, blob_id_for_content, collect_files_in_workspace, normalize_rel_path,
 };
-use crate::{NodeId, Result, WorkSpaceError};
+use crate::vcs::stage::clear_staged;
+use crate::vcs::timeline::{manuscript_counts, save_node_stats};
+use crate::vcs::tree::{backfill_tree_id, tree_id_for_entries};
+use crate::{CommitOptions, NodeId, NodeKind, Result, WorkSpaceError};
 
-pub fn commit(root: &Path, message: &str) -> Result<NodeId> {
-    use crate::schema::head::dsl as head_dsl;
-    use crate::schema::node_parents::dsl as node_parents_dsl;
-    use crate::schema::nodes::dsl as nodes_dsl;
+impl Repository {
+    pub fn commit(&self, message: &str) -> Result<NodeId> {
+        self.commit_with_options(message, CommitOptions::default())
+    }
+
+    // 작업 트리가 HEAD와 같으면 NothingToCommit으로 거부한다 (options.allow_empty로 허용)
+    pub fn commit_with_options(&self, message: &str, options: CommitOptions) -> Result<NodeId> {
+        self.commit_with_progress(message, options, &Progress::default())
+    }
+
+    // 파일을 읽을 때(Scan)와 blob을 넣을 때(Store) 진행 상황을 알린다.
+    // 취소되면 노드를 쓰는 트랜잭션이 롤백되어 아무것도 남지 않는다
+    pub fn commit_with_progress(
+        &self,
+        message: &str,
+        options: CommitOptions,
+        progress: &Progress,
+    ) -> Result<NodeId> {
+        let root = self.root();
+        let message_text = message.trim();
 
-    let message_text = message.trim();
+        if message_text.is_empty() {
+            return Err(WorkSpaceError::EmptyMessage);
+        }
 
-    if message_text.is_empty() {
-        return Err(WorkSpaceError::Io(std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            "empty commit message",
-        )));
+        let _lock = RepoLock::acquire(root)?;
+
+        let mut guard = self.conn()?;
+        let conn = &mut *guard;
+
+        // 잠긴 암호화 저장소면 파일을 읽기 전에 KeyRequired로 끝낸다
+        let store = BlobStore::open(root, conn)?;
+        let snapshot = collect_snapshot(root, progress)?;
+
+        // 잠금을 잡고 있으므로 트랜잭션 밖에서 비교해도 HEAD가 바뀌지 않는다.
+        // 비교하면서 채운 HEAD의 tree_id는 커밋이 거부돼도 남는다
+        let head = current_head(conn)?;
+        if !options.allow_empty
+            && let Some(head) = &head
+            && backfill_tree_id(conn, head)? == snapshot.tree_id
+        {
+            return Err(WorkSpaceError::NothingToCommit(head.clone()));
+        }
+
+        // 훅이 실패하면 아무것도 쓰지 않는다
+        let changed = changed_paths(conn, head.as_deref(), &snapshot)?;
+        // 훅이 도는 동안에는 연결을 놓아 다른 스레드의 읽기가 기다리지 않게 한다.
+        // 쓰기는 저장소 잠금이 막으므로 HEAD는 그대로다
+        drop(guard);
+        run_required_hook(root, PRE_SNAPSHOT, &[], &changed)?;
+
+        let conn = &mut *self.conn()?;
+        conn.transaction::<NodeId, WorkSpaceError, _>(|tx| {
+            let node_id = write_node(
+                tx,
+                &store,
+                message_text,
+                &snapshot,
+                NodeKind::Manual,
+                "commit",
+                progress,
+            )?;
+            clear_staged(tx)?;
+
+            Ok(node_id)
+        })
     }
+}
+
+pub fn commit(root: &Path, message: &str) -> Result<NodeId> {
+    Repository::open(root)?.commit(message)
+}
 
-    let mut conn = open_connection(root)?;
-    conn.run_pending_migrations(MIGRATIONS).map_err(to_io)?;
+pub fn commit_with_options(root: &Path, message: &str, options: CommitOptions) -> Result<NodeId> {
+    Repository::open(root)?.commit_with_options(message, options)
+}
+
+pub fn commit_with_progress(
+    root: &Path,
+    message: &str,
+    options: CommitOptions,
+    progress: &Progress,
+) -> Result<NodeId> {
+    Repository::open(root)?.commit_with_progress(message, options, progress)
+}
 
+// 작업 트리 전체를 읽은 스냅샷
+pub(crate) struct Snapshot {
+    pub files: Vec<SnapshotFile>,
+    pub tree_id: String,
+}
+
+pub(crate) fn collect_snapshot(root: &Path, progress: &Progress) -> Result<Snapshot> {
     let files = collect_files_in_workspace(root)?;
-    let mut snapshot_files = Vec::with_capacity(files.len());
+    let total = files.len();
+    let mut snapshot_files = Vec::with_capacity(total);
+    let mut bytes = 0;
 
-    for rel in files {
+    for (index, rel) in files.into_iter().enumerate() {
+        progress.checkpoint()?;
         let abs = root.join(&rel);
 
-        let content = fs::read(&abs).map_err(to_io)?;
+        let content = fs::read(&abs)?;
 
         let blob_id = blob_id_for_content(&content);
+        bytes += content.len() as u64;
+        progress.report(ProgressPhase::Scan, index + 1, total, bytes);
 
         snapshot_files.push(SnapshotFile {
             path: normalize_rel_path(&rel),
@@ -44,70 +136,138 @@ pub fn commit(root: &Path, message: &str) -> Result<NodeId> {
         });
     }
 
-    let new_id = conn
-        .transaction::<NodeId, diesel::result::Error, _>(|tx| {
-            let created_at_ms = now_unix_ms();
+    let tree_id = tree_id_for_entries(
+        snapshot_files
+            .iter()
+            .map(|f| (f.path.as_str(), f.blob_id.as_str())),
+    )?;
+
+    Ok(Snapshot {
+        files: snapshot_files,
+        tree_id,
+    })
+}
+
+// HEAD와 비교해 추가/삭제/수정된 경로 (정렬됨)
+pub(crate) fn changed_paths(
+    conn: &mut SqliteConnection,
+    head: Option<&str>,
+    snapshot: &Snapshot,
+) -> Result<Vec<String>> {
+    use crate::schema::node_files::dsl as node_files_dsl;
+
+    let mut before = match head {
+        Some(head) => node_files_dsl::node_files
+            .filter(node_files_dsl::node_id.eq(head))
+            .select((node_files_dsl::path, node_files_dsl::blob_id))
+            .load::<(String, String)>(conn)
+            .map_err(to_db)?
+            .into_iter()
+            .collect::<HashMap<_, _>>(),
+        None => HashMap::new(),
+    };
+
+    let mut changed = Vec::new();
+    for file in &snapshot.files {
+        match before.remove(&file.path) {
+            Some(blob_id) if blob_id == file.blob_id => {}
+            _ => changed.push(file.path.clone()),
+        }
+    }
+    changed.extend(before.into_keys());
+    changed.sort();
+
+    Ok(changed)
+}
+
+// 스냅샷을 HEAD의 자식 노드로 저장하고 HEAD를 옮긴다. reason은 reflog에 남는 사유
+pub(crate) fn write_node(
+    conn: &mut SqliteConnection,
+    store: &BlobStore,
+    message_text: &str,
+    snapshot: &Snapshot,
+    kind: NodeKind,
+    reason: &str,
+    progress: &Progress,
+) -> Result<NodeId> {
+    use crate::schema::head::dsl as head_dsl;
+    use crate::schema::node_parents::dsl as node_parents_dsl;
+    use crate::schema::nodes::dsl as nodes_dsl;
+
+    conn.transaction::<NodeId, WorkSpaceError, _>(|tx| {
+        let created_at_ms = now_unix_ms();
+
+        let current_head = head_dsl::head
+            .select(head_dsl::node_id)
+            .first::<Option<String>>(tx)
+            .optional()?
+            .flatten();
 
-            let current_head = head_dsl::head
-                .select(head_dsl::node_id)
-                .first::<Option<String>>(tx)
-                .optional()?
-                .flatten();
+        let new_id = new_node_id(
+            message_text,
+            current_head.as_deref(),
+            &snapshot.tree_id,
+            created_at_ms,
+        );
 
-            let new_id = new_node_id(message_text, current_head.as_deref(), created_at_ms);
+        diesel::insert_into(nodes_dsl::nodes)
+            .values((
+                nodes_dsl::id.eq(&new_id),
+                nodes_dsl::message.eq(seal_message(
+                    store.cipher(),
+                    new_id.as_bytes(),
+                    message_text,
+                )),
+                nodes_dsl::created_at_unix_ms.eq(created_at_ms),
+                nodes_dsl::tree_id.eq(&snapshot.tree_id),
+                nodes_dsl::kind.eq(kind.as_str()),
+            ))
+            .execute(tx)?;
 
-            diesel::insert_into(nodes_dsl::nodes)
+        if let Some(parent_id) = current_head {
+            diesel::insert_into(node_parents_dsl::node_parents)
                 .values((
-                    nodes_dsl::id.eq(&new_id),
-                    nodes_dsl::message.eq(message_text),
-                    nodes_dsl::created_at_unix_ms.eq(created_at_ms),
+                    node_parents_dsl::node_id.eq(&new_id),
+                    node_parents_dsl::parent_id.eq(parent_id),
+                    node_parents_dsl::ord.eq(0),
                 ))
                 .execute(tx)?;
+        }
 
-            if let Some(parent_id) = current_head {
-                diesel::insert_into(node_parents_dsl::node_parents)
-                    .values((
-                        node_parents_dsl::node_id.eq(&new_id),
-                        node_parents_dsl::parent_id.eq(parent_id),
-                        node_parents_dsl::ord.eq(0),
-                    ))
-                    .execute(tx)?;
-            }
-
-            diesel::update(head_dsl::head)
-                .set(head_dsl::node_id.eq(Some(new_id.clone())))
+        move_head(
+            tx,
+            &new_id,
+            reason,
+            &seal_message(store.cipher(), REFLOG_AAD, message_text),
+        )?;
+
+        use crate::schema::node_files::dsl as node_files_dsl;
+
+        let total = snapshot.files.len();
+        let mut bytes = 0;
+        for (index, file) in snapshot.files.iter().enumerate() {
+            progress.checkpoint()?;
+            store.put(tx, &file.blob_id, &file.content)?;
+            bytes += file.content.len() as u64;
+            progress.report(ProgressPhase::Store, index + 1, total, bytes);
+
+            diesel::insert_into(node_files_dsl::node_files)
+                .values((
+                    node_files_dsl::node_id.eq(&new_id),
+                    node_files_dsl::path.eq(&file.path),
+                    node_files_dsl::blob_id.eq(&file.blob_id),
+                ))
                 .execute(tx)?;
+        }
 
-            use crate::schema::blobs::dsl as blobs_dsl;
-            use crate::schema::node_files::dsl as node_files_dsl;
-
-            for file in &snapshot_files {
-                diesel::insert_into(blobs_dsl::blobs)
-                    .values((
-                        blobs_dsl::id.eq(&file.blob_id),
-                        blobs_dsl::content.eq(&file.content),
-                    ))
-                    .on_conflict(blobs_dsl::id)
-                    .do_nothing()
-                    .execute(tx)?;
-
-                diesel::insert_into(node_files_dsl::node_files)
-                    .values((
-                        node_files_dsl::node_id.eq(&new_id),
-                        node_files_dsl::path.eq(&file.path),
-                        node_files_dsl::blob_id.eq(&file.blob_id),
-                    ))
-                    .execute(tx)?;
-            }
-
-            Ok(new_id)
-        })
-        .map_err(to_io)?;
+        let counts = manuscript_counts(snapshot.files.iter().map(|f| f.content.as_slice()));
+        save_node_stats(tx, &new_id, counts)?;
 
-    Ok(new_id)
+        Ok(new_id)
+    })
 }
 
-fn now_unix_ms() -> i64 {
+pub(crate) fn now_unix_ms() -> i64 {
     use std::time::{SystemTime, UNIX_EPOCH};
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -115,7 +275,36 @@ fn now_unix_ms() -> i64 {
         .as_millis() as i64
 }
 
-fn new_node_id(message_text: &str, parent: Option<&str>, created_at_ms: i64) -> NodeId {
+pub(crate) fn new_node_id(
+    message_text: &str,
+    parent: Option<&str>,
+    tree_id: &str,
+    created_at_ms: i64,
+) -> NodeId {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update("v2\n");
+    hasher.update(tree_id.as_bytes());
+    hasher.update("\n");
+    hasher.update(message_text.as_bytes());
+    hasher.update("\n");
+    hasher.update(created_at_ms.to_string().as_bytes());
+    hasher.update("\n");
+
+    if let Some(parent_id) = parent {
+        hasher.update(parent_id.as_bytes());
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+// tree_id가 id에 들어가기 전(v1)의 노드 id. 예전 노드를 검증할 때만 쓴다
+pub(crate) fn legacy_node_id(
+    message_text: &str,
+    parent: Option<&str>,
+    created_at_ms: i64,
+) -> NodeId {
     use sha2::{Digest, Sha256};
 
     let mut hasher = Sha256::new();
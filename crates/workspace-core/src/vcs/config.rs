@@ -0,0 +1,29 @@
+use diesel::prelude::*;
+
+use crate::Result;
+use crate::schema::config::dsl as config_dsl;
+use crate::vcs::db::to_db;
+
+// 저장소 설정(config 테이블) 읽기. 없으면 None
+pub(crate) fn get_config(conn: &mut SqliteConnection, key: &str) -> Result<Option<String>> {
+    let value = config_dsl::config
+        .filter(config_dsl::key.eq(key))
+        .select(config_dsl::value)
+        .first::<String>(conn)
+        .optional()
+        .map_err(to_db)?;
+
+    Ok(value)
+}
+
+pub(crate) fn set_config(conn: &mut SqliteConnection, key: &str, value: &str) -> Result<()> {
+    diesel::insert_into(config_dsl::config)
+        .values((config_dsl::key.eq(key), config_dsl::value.eq(value)))
+        .on_conflict(config_dsl::key)
+        .do_update()
+        .set(config_dsl::value.eq(value))
+        .execute(conn)
+        .map_err(to_db)?;
+
+    Ok(())
+}
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use diesel::prelude::*;
+
+use crate::schema::node_notes::dsl as notes_dsl;
+use crate::schema::nodes::dsl as nodes_dsl;
+use crate::schema::reflog::dsl as reflog_dsl;
+use crate::schema::remotes::dsl as remotes_dsl;
+use crate::vcs::config::{get_config, set_config};
+use crate::vcs::lock::RepoLock;
+use crate::vcs::notes::note_aad;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::remote::remote_token_aad;
+use crate::vcs::repository::Repository;
+use crate::vcs::search::clear_index;
+use crate::{Result, WorkSpaceError};
+
+// config 키: Argon2 salt(hex)와 암호에서 만든 키로 감싼 데이터 키(hex)
+const SALT_KEY: &str = "encryption.salt";
+const WRAPPED_KEY: &str = "encryption.key";
+// "true"면 암호화 저장소에서도 평문 번들/pack을 내보낸다
+const PLAINTEXT_EXPORT_KEY: &str = "encryption.plaintext_export";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const DATA_KEY_AAD: &[u8] = b"novel data key";
+// 암호화된 메시지(nodes.message, reflog.message, node_notes.text, remotes.token) 앞에 붙는 표시
+const SEALED_PREFIX: &str = "enc:";
+pub(crate) const REFLOG_AAD: &[u8] = b"reflog";
+
+// 이번 프로세스에서 풀어 둔 데이터 키. 키는 canonical 루트 경로
+static SESSION_KEYS: LazyLock<Mutex<HashMap<PathBuf, [u8; 32]>>> = LazyLock::new(Default::default);
+
+// blob 내용과 메시지를 암호화하는 데이터 키
+#[derive(Clone)]
+pub(crate) struct Cipher(XChaCha20Poly1305);
+
+impl Cipher {
+    fn from_key(key: &[u8; 32]) -> Cipher {
+        Cipher(XChaCha20Poly1305::new(Key::from_slice(key)))
+    }
+
+    // nonce(24바이트) + 암호문. aad는 암호문을 다른 자리로 옮겨 쓰지 못하게 묶는 값(blob id 등)
+    pub(crate) fn seal(&self, aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .0
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .expect("in-memory encryption does not fail");
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        sealed
+    }
+
+    // 키나 aad가 다르거나 내용이 바뀌었으면 None
+    pub(crate) fn open(&self, aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        self.0
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .ok()
+    }
+}
+
+// 암호화 저장소면 "enc:" + hex(nonce + 암호문), 아니면 그대로
+pub(crate) fn seal_message(cipher: Option<&Cipher>, aad: &[u8], text: &str) -> String {
+    match cipher {
+        Some(cipher) => format!(
+            "{}{}",
+            SEALED_PREFIX,
+            hex::encode(cipher.seal(aad, text.as_bytes()))
+        ),
+        None => text.to_string(),
+    }
+}
+
+// "enc:"가 없는 메시지(암호화 전에 쓴 reflog 등)는 그대로 돌려준다
+pub(crate) fn open_message(cipher: Option<&Cipher>, aad: &[u8], stored: String) -> Result<String> {
+    let Some(encoded) = stored.strip_prefix(SEALED_PREFIX) else {
+        return Ok(stored);
+    };
+    let undecryptable = || WorkSpaceError::CorruptData("cannot decrypt message".to_string());
+
+    let cipher = cipher.ok_or_else(undecryptable)?;
+    let sealed = hex::decode(encoded).map_err(|_| undecryptable())?;
+    let plaintext = cipher.open(aad, &sealed).ok_or_else(undecryptable)?;
+    String::from_utf8(plaintext).map_err(|_| undecryptable())
+}
+
+// 저장소의 데이터 키. 암호화되지 않았으면 None, 암호화됐는데 unlock_repo 전이면 KeyRequired
+pub(crate) fn repo_cipher(root: &Path, conn: &mut SqliteConnection) -> Result<Option<Cipher>> {
+    if get_config(conn, WRAPPED_KEY)?.is_none() {
+        return Ok(None);
+    }
+
+    let root = root.canonicalize()?;
+    match SESSION_KEYS.lock().unwrap().get(&root) {
+        Some(key) => Ok(Some(Cipher::from_key(key))),
+        None => Err(WorkSpaceError::KeyRequired(root)),
+    }
+}
+
+impl Repository {
+    pub fn is_encrypted(&self) -> Result<bool> {
+        let conn = &mut *self.conn()?;
+
+        is_encrypted_conn(conn)
+    }
+
+    // 암호로 데이터 키를 풀어 이 프로세스가 끝나거나 forget_repo_key를 부를 때까지 기억한다
+    pub fn unlock_repo(&self, passphrase: &str) -> Result<()> {
+        let root = self.root();
+        let conn = &mut *self.conn()?;
+
+        let key = unwrap_data_key(conn, passphrase)?;
+        remember(root, key)
+    }
+
+    // 기억해 둔 키를 버린다. 다음 접근부터 다시 KeyRequired
+    pub fn forget_repo_key(&self) -> Result<()> {
+        SESSION_KEYS.lock().unwrap().remove(self.root());
+
+        Ok(())
+    }
+
+    // 새 데이터 키를 만들어 모든 blob과 메시지를 다시 암호화하고 new_passphrase로 감싼다
+    pub fn rotate_key(&self, old_passphrase: &str, new_passphrase: &str) -> Result<()> {
+        let root = self.root();
+        let _lock = RepoLock::acquire(root)?;
+        let conn = &mut *self.conn()?;
+
+        let old_key = unwrap_data_key(conn, old_passphrase)?;
+        let new_key = reencrypt(root, conn, Some(&old_key), new_passphrase)?;
+        remember(root, new_key)
+    }
+
+    // 번들, push, 동기화 서버의 upload-pack은 평문을 담는다. 암호화 저장소는 이것을 켜야 내보낼 수 있다
+    pub fn plaintext_export_allowed(&self) -> Result<bool> {
+        let conn = &mut *self.conn()?;
+
+        Ok(get_config(conn, PLAINTEXT_EXPORT_KEY)?.as_deref() == Some("true"))
+    }
+
+    pub fn set_plaintext_export(&self, allowed: bool) -> Result<()> {
+        let conn = &mut *self.conn()?;
+
+        set_config(
+            conn,
+            PLAINTEXT_EXPORT_KEY,
+            if allowed { "true" } else { "false" },
+        )
+    }
+
+    // init_repo_with_options에서 부른다. 이미 커밋이 있으면 그 내용도 암호화한다
+    pub(crate) fn enable_encryption(&self, passphrase: &str) -> Result<()> {
+        let root = self.root();
+        let _lock = RepoLock::acquire(root)?;
+        let conn = &mut *self.conn()?;
+
+        if get_config(conn, WRAPPED_KEY)?.is_some() {
+            return Err(WorkSpaceError::Conflict(
+                "repository is already encrypted".to_string(),
+            ));
+        }
+
+        let key = reencrypt(root, conn, None, passphrase)?;
+        remember(root, key)
+    }
+}
+
+pub fn is_encrypted(root: &Path) -> Result<bool> {
+    Repository::open(root)?.is_encrypted()
+}
+
+pub fn unlock_repo(root: &Path, passphrase: &str) -> Result<()> {
+    Repository::open(root)?.unlock_repo(passphrase)
+}
+
+// 저장소를 열지 않는다(.novel이 없어도 된다)
+pub fn forget_repo_key(root: &Path) -> Result<()> {
+    let root = root.canonicalize()?;
+    SESSION_KEYS.lock().unwrap().remove(&root);
+
+    Ok(())
+}
+
+pub fn rotate_key(root: &Path, old_passphrase: &str, new_passphrase: &str) -> Result<()> {
+    Repository::open(root)?.rotate_key(old_passphrase, new_passphrase)
+}
+
+pub fn plaintext_export_allowed(root: &Path) -> Result<bool> {
+    Repository::open(root)?.plaintext_export_allowed()
+}
+
+pub fn set_plaintext_export(root: &Path, allowed: bool) -> Result<()> {
+    Repository::open(root)?.set_plaintext_export(allowed)
+}
+
+// 평문 pack을 만들기 전에 부른다. cipher는 내보내는 저장소의 키
+pub(crate) fn check_plaintext_export(
+    conn: &mut SqliteConnection,
+    cipher: Option<&Cipher>,
+) -> Result<()> {
+    if cipher.is_some() && get_config(conn, PLAINTEXT_EXPORT_KEY)?.as_deref() != Some("true") {
+        return Err(WorkSpaceError::PlaintextExportDenied);
+    }
+    Ok(())
+}
+
+pub(crate) fn is_encrypted_conn(conn: &mut SqliteConnection) -> Result<bool> {
+    Ok(get_config(conn, WRAPPED_KEY)?.is_some())
+}
+
+// from(None이면 평문)으로 저장된 데이터를 새 데이터 키로 다시 쓰고 passphrase로 감싼 키를 저장한다.
+// objects 파일은 트랜잭션 안에서 .rekey 파일로만 써 두고, 커밋된 뒤에 바꿔 넣는다
+fn reencrypt(
+    root: &Path,
+    conn: &mut SqliteConnection,
+    from: Option<&[u8; 32]>,
+    passphrase: &str,
+) -> Result<[u8; 32]> {
+    if passphrase.is_empty() {
+        return Err(WorkSpaceError::InvalidInput("empty passphrase".to_string()));
+    }
+
+    let from = from.map(Cipher::from_key);
+    let mut new_key = [0u8; 32];
+    new_key.copy_from_slice(&XChaCha20Poly1305::generate_key(&mut OsRng));
+    let to = Cipher::from_key(&new_key);
+
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let wrapped = derive_kek(passphrase, &salt)?.seal(DATA_KEY_AAD, &new_key);
+
+    let store = BlobStore::with_cipher(root, conn, from.clone())?;
+    let result = conn.transaction::<(), WorkSpaceError, _>(|tx| {
+        store.rekey_blobs(tx, &to)?;
+        clear_index(tx)?;
+
+        let nodes = nodes_dsl::nodes
+            .select((nodes_dsl::id, nodes_dsl::message))
+            .load::<(String, String)>(tx)?;
+        for (id, message) in nodes {
+            let message = open_message(from.as_ref(), id.as_bytes(), message)?;
+            diesel::update(nodes_dsl::nodes.filter(nodes_dsl::id.eq(&id)))
+                .set(nodes_dsl::message.eq(seal_message(Some(&to), id.as_bytes(), &message)))
+                .execute(tx)?;
+        }
+
+        let entries = reflog_dsl::reflog
+            .select((reflog_dsl::id, reflog_dsl::message))
+            .load::<(i32, String)>(tx)?;
+        for (id, message) in entries {
+            let message = open_message(from.as_ref(), REFLOG_AAD, message)?;
+            diesel::update(reflog_dsl::reflog.filter(reflog_dsl::id.eq(id)))
+                .set(reflog_dsl::message.eq(seal_message(Some(&to), REFLOG_AAD, &message)))
+                .execute(tx)?;
+        }
+
+        let notes = notes_dsl::node_notes
+            .select((notes_dsl::id, notes_dsl::node_id, notes_dsl::text))
+            .load::<(i32, String, String)>(tx)?;
+        for (id, node_id, text) in notes {
+            let aad = note_aad(&node_id);
+            let text = open_message(from.as_ref(), &aad, text)?;
+            diesel::update(notes_dsl::node_notes.filter(notes_dsl::id.eq(id)))
+                .set(notes_dsl::text.eq(seal_message(Some(&to), &aad, &text)))
+                .execute(tx)?;
+        }
+
+        let tokens = remotes_dsl::remotes
+            .filter(remotes_dsl::token.is_not_null())
+            .select((remotes_dsl::name, remotes_dsl::token.assume_not_null()))
+            .load::<(String, String)>(tx)?;
+        for (name, token) in tokens {
+            let aad = remote_token_aad(&name);
+            let token = open_message(from.as_ref(), &aad, token)?;
+            diesel::update(remotes_dsl::remotes.filter(remotes_dsl::name.eq(&name)))
+                .set(remotes_dsl::token.eq(seal_message(Some(&to), &aad, &token)))
+                .execute(tx)?;
+        }
+
+        set_config(tx, SALT_KEY, &hex::encode(salt))?;
+        set_config(tx, WRAPPED_KEY, &hex::encode(wrapped))?;
+        store.mark_rekey_pending(tx)
+    });
+
+    if let Err(e) = result {
+        store.discard_rekey()?;
+        return Err(e);
+    }
+    store.finish_rekey(conn)?;
+
+    Ok(new_key)
+}
+
+fn unwrap_data_key(conn: &mut SqliteConnection, passphrase: &str) -> Result<[u8; 32]> {
+    let (Some(salt), Some(wrapped)) = (get_config(conn, SALT_KEY)?, get_config(conn, WRAPPED_KEY)?)
+    else {
+        return Err(WorkSpaceError::InvalidInput(
+            "repository is not encrypted".to_string(),
+        ));
+    };
+    let corrupt = || WorkSpaceError::CorruptData("invalid encryption key config".to_string());
+    let salt = hex::decode(salt).map_err(|_| corrupt())?;
+    let wrapped = hex::decode(wrapped).map_err(|_| corrupt())?;
+
+    let key = derive_kek(passphrase, &salt)?
+        .open(DATA_KEY_AAD, &wrapped)
+        .ok_or(WorkSpaceError::WrongPassphrase)?;
+    key.try_into().map_err(|_| corrupt())
+}
+
+// Argon2id 기본 파라미터로 암호에서 키를 감쌀 키를 만든다
+fn derive_kek(passphrase: &str, salt: &[u8]) -> Result<Cipher> {
+    let mut kek = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut kek)
+        .map_err(|e| WorkSpaceError::InvalidInput(e.to_string()))?;
+
+    Ok(Cipher::from_key(&kek))
+}
+
+fn remember(root: &Path, key: [u8; 32]) -> Result<()> {
+    let root = root.canonicalize()?;
+    SESSION_KEYS.lock().unwrap().insert(root, key);
+
+    Ok(())
+}
@@ -1,8 +1,8 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::{Result, WorkSpaceError};
 // SQLite 전용 연결 타입
-use diesel::{Connection, sqlite::SqliteConnection};
+use diesel::{Connection, connection::SimpleConnection, sqlite::SqliteConnection};
 use diesel_migrations::{EmbeddedMigrations, embed_migrations};
 use std::fs;
 
@@ -14,12 +14,11 @@ pub(crate) const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migration
 const NOVEL_DIR: &str = ".novel";
 // 메타 디렉토리 안 SQLite 파일 이름
 const VCS_DB_FILE: &str = "vcs.db";
+// 다른 연결이 쓰기 중일 때 SQLITE_BUSY로 실패하기 전까지 기다리는 시간
+const BUSY_TIMEOUT_MS: u32 = 5_000;
 
-// DB 연결 헬퍼:
-// - 루트 경로 canonicalize
-// - .novel 디렉토리 생성 보장
-// - SQLite 연결 오픈
-pub(crate) fn open_connection(root: &Path) -> Result<SqliteConnection> {
+// .novel 메타 디렉토리 경로(없으면 만든다)
+pub(crate) fn meta_dir(root: &Path) -> Result<PathBuf> {
     // canonicalize: 상대경로/심볼릭 링크를 실제 절대경로로 정규화
     let canonical_root = root.canonicalize()?;
 
@@ -27,6 +26,17 @@ pub(crate) fn open_connection(root: &Path) -> Result<SqliteConnection> {
     let meta_dir = canonical_root.join(NOVEL_DIR);
     fs::create_dir_all(&meta_dir)?;
 
+    Ok(meta_dir)
+}
+
+// DB 연결 헬퍼:
+// - 루트 경로 canonicalize
+// - .novel 디렉토리 생성 보장
+// - SQLite 연결 오픈
+// - WAL 모드 + busy timeout 설정(CLI와 데스크톱 앱이 동시에 열 수 있다)
+pub(crate) fn open_connection(root: &Path) -> Result<SqliteConnection> {
+    let meta_dir = meta_dir(root)?;
+
     // 최종 DB 파일 경로(.novel/vcs.db)
     let db_path = meta_dir.join(VCS_DB_FILE);
 
@@ -34,17 +44,48 @@ pub(crate) fn open_connection(root: &Path) -> Result<SqliteConnection> {
     let db_url = db_path.to_string_lossy();
 
     // establish는 &str을 받으므로 as_ref()로 &str로 빌려서 넘긴다
-    let conn = SqliteConnection::establish(db_url.as_ref()).map_err(to_connection_error)?;
+    let mut conn = SqliteConnection::establish(db_url.as_ref()).map_err(to_connection_error)?;
+
+    // busy_timeout을 먼저 걸어야 journal_mode 전환도 잠금을 기다린다
+    conn.batch_execute(&format!(
+        "PRAGMA busy_timeout = {}; PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;",
+        BUSY_TIMEOUT_MS
+    ))
+    .map_err(to_db)?;
+
     Ok(conn)
 }
 
+// 이미 초기화된 저장소에만 연결한다(원격 저장소처럼 새로 만들면 안 되는 경우)
+pub(crate) fn open_existing_connection(root: &Path) -> Result<SqliteConnection> {
+    let db_path = root.join(NOVEL_DIR).join(VCS_DB_FILE);
+
+    if !db_path.is_file() {
+        return Err(WorkSpaceError::NotARepository(root.to_path_buf()));
+    }
+
+    open_connection(root)
+}
+
 // Diesel 연결 에러 -> 공통 WorkSpaceError 변환
 pub(crate) fn to_connection_error(e: diesel::ConnectionError) -> WorkSpaceError {
-    WorkSpaceError::Io(std::io::Error::other(e.to_string()))
+    WorkSpaceError::Database(e.to_string())
+}
+
+// 쿼리/트랜잭션 에러
+pub(crate) fn to_db(e: diesel::result::Error) -> WorkSpaceError {
+    WorkSpaceError::Database(e.to_string())
 }
 
-// Display 구현 타입이면 모두 받아서 공통 I/O 에러로 감싼다
+// run_pending_migrations 에러는 Box<dyn Error>라서 Display만 받는다
 // (impl Trait 문법: "이 trait를 만족하는 어떤 타입이든")
-pub(crate) fn to_io(e: impl std::fmt::Display) -> WorkSpaceError {
-    WorkSpaceError::Io(std::io::Error::other(e.to_string()))
+pub(crate) fn to_migration(e: impl std::fmt::Display) -> WorkSpaceError {
+    WorkSpaceError::Migration(e.to_string())
+}
+
+// conn.transaction()이 WorkSpaceError를 그대로 돌려줄 수 있도록 Diesel 에러 변환을 제공한다
+impl From<diesel::result::Error> for WorkSpaceError {
+    fn from(e: diesel::result::Error) -> Self {
+        to_db(e)
+    }
 }
@@ -1,97 +1,260 @@
 use std::collections::HashMap;
+use std::path::Path;
 
-use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection, dsl::select};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
 
+use crate::vcs::autosave::require_snapshot;
+use crate::vcs::repository::Repository;
 use crate::{
-    DiffKind, FileDiff, NodeDiff, Result, WorkSpaceError,
-    schema::{blobs, node_files},
-    vcs::db::{open_connection, to_io},
+    DiffKind, DiffSummary, FileChange, FileDiff, NodeDiff, Result, WorkSpaceError,
+    schema::node_files,
+    vcs::commit::collect_snapshot,
+    vcs::db::to_db,
+    vcs::objects::{BlobStats, BlobStore},
+    vcs::progress::Progress,
+    vcs::repo::current_head,
+    vcs::revision::resolve,
+    vcs::snapshot::normalize_rel_path,
+    vcs::storage::{self, SqliteObjects},
+    vcs::tree::node_tree_id,
 };
 
-pub fn diff_nodes(root: &std::path::Path, from: &str, to: &str) -> Result<NodeDiff> {
-    let mut conn = open_connection(root)?;
+impl Repository {
+    // from/to는 리비전(HEAD~1, 접두사, 태그 ...). 결과의 from/to에는 풀린 노드 id가 담긴다
+    pub fn diff_nodes(&self, from: &str, to: &str) -> Result<NodeDiff> {
+        let root = self.root();
+        let conn = &mut *self.conn()?;
 
-    if !node_exists(&mut conn, from)? {
-        return Err(WorkSpaceError::Io(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            format!("node not found: {}", from),
-        )));
+        let from = &resolve(conn, from)?;
+        let to = &resolve(conn, to)?;
+
+        let store = BlobStore::open(root, conn)?;
+        storage::diff_nodes(&mut SqliteObjects::new(conn, &store), from, to)
     }
-    if !node_exists(&mut conn, to)? {
-        return Err(WorkSpaceError::Io(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            format!("node not found: {}", to),
-        )));
+
+    // 작업 트리(디스크의 현재 파일)를 노드와 비교한다. revision이 None이면 HEAD와 비교하고,
+    // 커밋이 하나도 없으면 모든 파일이 Added다(from은 빈 문자열). 노드에 없는 파일은 Added로 나온다.
+    pub fn diff_workdir(&self, revision: Option<&str>) -> Result<NodeDiff> {
+        let root = self.root();
+        let conn = &mut *self.conn()?;
+
+        let from = match revision {
+            Some(revision) => Some(resolve(conn, revision)?),
+            None => current_head(conn)?,
+        };
+        let from_map = match &from {
+            Some(node_id) => load_blob_id_map(conn, node_id)?,
+            None => HashMap::new(),
+        };
+
+        let snapshot = collect_snapshot(root, &Progress::default())?;
+        let to_map = snapshot
+            .files
+            .iter()
+            .map(|f| (f.path.clone(), f.blob_id.clone()))
+            .collect::<HashMap<_, _>>();
+        let changes = changed_paths(&from_map, &to_map);
+
+        // 노드 쪽은 바뀐 파일의 blob만 읽는다
+        let blob_ids = changes
+            .iter()
+            .filter_map(|(path, _)| from_map.get(path).cloned())
+            .collect::<Vec<_>>();
+        let before_contents = BlobStore::open(root, conn)?
+            .load(conn, &blob_ids)?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let after_contents = snapshot
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.content.as_slice()))
+            .collect::<HashMap<_, _>>();
+
+        let mut files = Vec::with_capacity(changes.len());
+        for (path, kind) in changes {
+            let before = match from_map.get(&path) {
+                Some(blob_id) => Some(
+                    before_contents
+                        .get(blob_id)
+                        .ok_or_else(|| missing_blob(blob_id, &path))?
+                        .as_slice(),
+                ),
+                None => None,
+            };
+            let after = after_contents.get(path.as_str()).copied();
+            files.push(build_file_diff(path, kind, before, after));
+        }
+
+        Ok(NodeDiff {
+            from: from.unwrap_or_default(),
+            to: WORKDIR.to_string(),
+            files,
+        })
     }
 
-    let from_map = load_snapshot_map(&mut conn, from)?;
-    let to_map = load_snapshot_map(&mut conn, to)?;
+    // 바뀐 경로와 크기/단어 수만 돌려준다. blob id만 비교하므로 내용은 읽지 않는다
+    // (통계 컬럼 이전에 저장된 blob만 처음 한 번 읽어 통계를 채운다). 파일별 내용 diff는 diff_file로 받는다
+    pub fn diff_summary(&self, from: &str, to: &str) -> Result<DiffSummary> {
+        let root = self.root();
+        let conn = &mut *self.conn()?;
 
-    let mut files = Vec::new();
+        let from = resolve(conn, from)?;
+        let to = resolve(conn, to)?;
 
-    let mut paths: Vec<String> = from_map.keys().chain(to_map.keys()).cloned().collect();
-    paths.sort();
-    paths.dedup();
+        if node_tree_id(conn, &from)? == node_tree_id(conn, &to)? {
+            return Ok(DiffSummary {
+                from,
+                to,
+                files: Vec::new(),
+            });
+        }
 
-    for path in paths {
-        match (from_map.get(&path), to_map.get(&path)) {
-            (None, None) => unreachable!(),
-            (None, Some(to_blob)) => {
-                files.push(build_file_diff(path, DiffKind::Added, None, Some(to_blob)))
+        let from_map = load_blob_id_map(conn, &from)?;
+        let to_map = load_blob_id_map(conn, &to)?;
+        let changes = changed_paths(&from_map, &to_map);
+
+        let blob_ids = changes
+            .iter()
+            .flat_map(|(path, _)| [from_map.get(path), to_map.get(path)])
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>();
+        let stats = BlobStore::open(root, conn)?.stats(conn, &blob_ids)?;
+        let stats_of = |map: &HashMap<String, String>, path: &str| -> Result<Option<BlobStats>> {
+            match map.get(path) {
+                Some(blob_id) => stats
+                    .get(blob_id)
+                    .copied()
+                    .map(Some)
+                    .ok_or_else(|| missing_blob(blob_id, path)),
+                None => Ok(None),
             }
-            (Some(from_blob), None) => files.push(build_file_diff(
+        };
+
+        let mut files = Vec::with_capacity(changes.len());
+        for (path, kind) in changes {
+            let before = stats_of(&from_map, &path)?;
+            let after = stats_of(&to_map, &path)?;
+            let before_words = before.and_then(|s| s.words);
+            let after_words = after.and_then(|s| s.words);
+            let is_binary = before.is_some_and(|s| s.words.is_none())
+                || after.is_some_and(|s| s.words.is_none());
+
+            files.push(FileChange {
                 path,
-                DiffKind::Removed,
-                Some(from_blob),
-                None,
-            )),
-            (Some(from_blob), Some(to_blob)) if from_blob != to_blob => {
-                files.push(build_file_diff(
-                    path,
-                    DiffKind::Modified,
-                    Some(from_blob),
-                    Some(to_blob),
-                ));
-            }
-            _ => {}
+                kind,
+                before_size: before.map(|s| s.size),
+                after_size: after.map(|s| s.size),
+                before_words,
+                after_words,
+                word_delta: after_words.unwrap_or(0) - before_words.unwrap_or(0),
+                is_binary,
+            });
         }
+
+        Ok(DiffSummary { from, to, files })
     }
 
-    Ok(NodeDiff {
-        from: from.to_string(),
-        to: to.to_string(),
-        files,
-    })
+    // 파일 한 개의 내용 diff. 두 노드에서 내용이 같으면(또는 둘 다 없으면) None
+    pub fn diff_file(&self, from: &str, to: &str, path: &str) -> Result<Option<FileDiff>> {
+        let root = self.root();
+        let conn = &mut *self.conn()?;
+
+        let from = resolve(conn, from)?;
+        let to = resolve(conn, to)?;
+        let path = normalize_rel_path(Path::new(path));
+
+        let store = BlobStore::open(root, conn)?;
+        let before = store.node_file(conn, &from, &path)?;
+        let after = store.node_file(conn, &to, &path)?;
+
+        let kind = match (&before, &after) {
+            (None, Some(_)) => DiffKind::Added,
+            (Some(_), None) => DiffKind::Removed,
+            (Some(before), Some(after)) if before != after => DiffKind::Modified,
+            _ => return Ok(None),
+        };
+
+        Ok(Some(build_file_diff(
+            path,
+            kind,
+            before.as_deref(),
+            after.as_deref(),
+        )))
+    }
+}
+
+pub fn diff_nodes(root: &Path, from: &str, to: &str) -> Result<NodeDiff> {
+    Repository::open(root)?.diff_nodes(from, to)
+}
+
+pub fn diff_workdir(root: &Path, revision: Option<&str>) -> Result<NodeDiff> {
+    Repository::open(root)?.diff_workdir(revision)
+}
+
+pub fn diff_summary(root: &Path, from: &str, to: &str) -> Result<DiffSummary> {
+    Repository::open(root)?.diff_summary(from, to)
+}
+
+pub fn diff_file(root: &Path, from: &str, to: &str, path: &str) -> Result<Option<FileDiff>> {
+    Repository::open(root)?.diff_file(from, to, path)
+}
+
+// diff_workdir 결과의 to에 들어가는 이름
+pub const WORKDIR: &str = "WORKDIR";
+
+// 두 스냅샷(path -> 내용 또는 blob id)에서 바뀐 경로. 경로 순으로 정렬한다
+pub(crate) fn changed_paths<T: PartialEq>(
+    from: &HashMap<String, T>,
+    to: &HashMap<String, T>,
+) -> Vec<(String, DiffKind)> {
+    let mut paths = from.keys().chain(to.keys()).cloned().collect::<Vec<_>>();
+    paths.sort();
+    paths.dedup();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let kind = match (from.get(&path), to.get(&path)) {
+                (None, Some(_)) => DiffKind::Added,
+                (Some(_), None) => DiffKind::Removed,
+                (Some(before), Some(after)) if before != after => DiffKind::Modified,
+                _ => return None,
+            };
+            Some((path, kind))
+        })
+        .collect()
 }
 
 // return path -> blob_id map for node_id
-fn load_snapshot_map(
+pub(crate) fn load_blob_id_map(
     conn: &mut SqliteConnection,
     node_id: &str,
-) -> Result<HashMap<String, Vec<u8>>> {
+) -> Result<HashMap<String, String>> {
+    require_snapshot(conn, node_id)?;
+
     let rows = node_files::dsl::node_files
-        .inner_join(blobs::dsl::blobs)
         .filter(node_files::dsl::node_id.eq(node_id))
-        .select((node_files::dsl::path, blobs::dsl::content))
-        .load::<(String, Vec<u8>)>(conn)
-        .map_err(to_io)?;
+        .select((node_files::dsl::path, node_files::dsl::blob_id))
+        .load::<(String, String)>(conn)
+        .map_err(to_db)?;
 
     Ok(rows.into_iter().collect())
 }
 
+fn missing_blob(blob_id: &str, path: &str) -> WorkSpaceError {
+    WorkSpaceError::NotFound(format!("missing blob {} for {}", blob_id, path))
+}
+
 fn decode_utf(bytes: &[u8]) -> Option<String> {
     std::str::from_utf8(bytes).ok().map(ToString::to_string)
 }
 
 fn normalize_text_for_line_diff(text: &str) -> String {
-    let mut normalized = text.replace("\r\n", "\n");
-    if !normalized.ends_with('\n') {
-        normalized.push('\n');
-    }
-    normalized
+    text.replace("\r\n", "\n")
 }
 
-fn build_file_diff(
+pub(crate) fn build_file_diff(
     path: String,
     kind: DiffKind,
     before: Option<&[u8]>,
@@ -133,17 +296,6 @@ fn build_file_diff(
     }
 }
 
-fn node_exists(conn: &mut SqliteConnection, id: &str) -> Result<bool> {
-    use crate::schema::nodes::dsl as nodes_dsl;
-    use diesel::dsl::exists;
-
-    let exists = select(exists(nodes_dsl::nodes.filter(nodes_dsl::id.eq(id))))
-        .get_result(conn)
-        .map_err(to_io)?;
-
-    Ok(exists)
-}
-
-fn is_probably_binary(bytes: &[u8]) -> bool {
+pub(crate) fn is_probably_binary(bytes: &[u8]) -> bool {
     bytes.contains(&0) || std::str::from_utf8(bytes).is_err()
 }
@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use diesel::sql_types::{BigInt, Bool, Text};
+use diesel::{QueryableByName, RunQueryDsl, SqliteConnection, sql_query};
+
+use crate::vcs::db::to_db;
+use crate::vcs::repository::Repository;
+use crate::vcs::revision::resolve;
+use crate::{AheadBehind, NodeId, Result};
+
+#[derive(QueryableByName)]
+struct IdRow {
+    #[diesel(sql_type = Text)]
+    id: String,
+}
+
+#[derive(QueryableByName)]
+struct FlagRow {
+    #[diesel(sql_type = Bool)]
+    flag: bool,
+}
+
+#[derive(QueryableByName)]
+struct AheadBehindRow {
+    #[diesel(sql_type = BigInt)]
+    ahead: i64,
+    #[diesel(sql_type = BigInt)]
+    behind: i64,
+}
+
+// ancestors_<name>(id): 시작 노드 자신과 그 조상 전체. 뒤에 WITH 절을 이어 붙여 쓴다
+fn ancestors_cte(name: &str) -> String {
+    format!(
+        "ancestors_{name}(id) AS (
+            SELECT ?
+            UNION
+            SELECT node_parents.parent_id FROM node_parents
+            JOIN ancestors_{name} ON node_parents.node_id = ancestors_{name}.id
+        )"
+    )
+}
+
+impl Repository {
+    // 두 노드의 공통 조상 중 가장 가까운 것(다른 공통 조상의 조상이 아닌 것) 하나.
+    // criss-cross 머지처럼 여러 개면 가장 최근 노드를 고른다
+    pub fn merge_base(&self, a: &str, b: &str) -> Result<Option<NodeId>> {
+        Ok(self.merge_bases(a, b)?.into_iter().next())
+    }
+
+    // 가장 가까운 공통 조상 전체 (최신 순)
+    pub fn merge_bases(&self, a: &str, b: &str) -> Result<Vec<NodeId>> {
+        let conn = &mut *self.conn()?;
+        let a = resolve(conn, a)?;
+        let b = resolve(conn, b)?;
+
+        let rows = sql_query(format!(
+            "WITH RECURSIVE {}, {},
+            common(id) AS (
+                SELECT id FROM ancestors_a INTERSECT SELECT id FROM ancestors_b
+            ),
+            below(id) AS (
+                SELECT node_parents.parent_id FROM node_parents
+                WHERE node_parents.node_id IN (SELECT id FROM common)
+                UNION
+                SELECT node_parents.parent_id FROM node_parents
+                JOIN below ON node_parents.node_id = below.id
+            )
+            SELECT common.id AS id FROM common
+            JOIN nodes ON nodes.id = common.id
+            WHERE common.id NOT IN (SELECT id FROM below)
+            ORDER BY nodes.created_at_unix_ms DESC, common.id",
+            ancestors_cte("a"),
+            ancestors_cte("b")
+        ))
+        .bind::<Text, _>(&a)
+        .bind::<Text, _>(&b)
+        .load::<IdRow>(conn)
+        .map_err(to_db)?;
+
+        Ok(rows.into_iter().map(|r| r.id).collect())
+    }
+
+    // ancestor가 node의 조상인지(같은 노드도 포함)
+    pub fn is_ancestor(&self, ancestor: &str, node: &str) -> Result<bool> {
+        let conn = &mut *self.conn()?;
+        let ancestor = resolve(conn, ancestor)?;
+        let node = resolve(conn, node)?;
+
+        is_reachable(conn, &node, &ancestor)
+    }
+
+    // a에만 있는 노드 수(ahead)와 b에만 있는 노드 수(behind)
+    pub fn ahead_behind(&self, a: &str, b: &str) -> Result<AheadBehind> {
+        let conn = &mut *self.conn()?;
+        let a = resolve(conn, a)?;
+        let b = resolve(conn, b)?;
+
+        let row = sql_query(format!(
+            "WITH RECURSIVE {}, {}
+            SELECT
+                (SELECT COUNT(*) FROM (SELECT id FROM ancestors_a EXCEPT SELECT id FROM ancestors_b)) AS ahead,
+                (SELECT COUNT(*) FROM (SELECT id FROM ancestors_b EXCEPT SELECT id FROM ancestors_a)) AS behind",
+            ancestors_cte("a"),
+            ancestors_cte("b")
+        ))
+        .bind::<Text, _>(&a)
+        .bind::<Text, _>(&b)
+        .get_result::<AheadBehindRow>(conn)
+        .map_err(to_db)?;
+
+        Ok(AheadBehind {
+            ahead: row.ahead as usize,
+            behind: row.behind as usize,
+        })
+    }
+
+    // node를 조상으로 가지는 노드 전체(node 자신 제외, 오래된 순)
+    pub fn descendants(&self, node: &str) -> Result<Vec<NodeId>> {
+        let conn = &mut *self.conn()?;
+        let node = resolve(conn, node)?;
+
+        let rows = sql_query(
+            "WITH RECURSIVE below(id) AS (
+                SELECT node_parents.node_id FROM node_parents WHERE node_parents.parent_id = ?
+                UNION
+                SELECT node_parents.node_id FROM node_parents
+                JOIN below ON node_parents.parent_id = below.id
+            )
+            SELECT below.id AS id FROM below
+            JOIN nodes ON nodes.id = below.id
+            ORDER BY nodes.created_at_unix_ms, below.id",
+        )
+        .bind::<Text, _>(&node)
+        .load::<IdRow>(conn)
+        .map_err(to_db)?;
+
+        Ok(rows.into_iter().map(|r| r.id).collect())
+    }
+}
+
+pub fn merge_base(root: &Path, a: &str, b: &str) -> Result<Option<NodeId>> {
+    Repository::open(root)?.merge_base(a, b)
+}
+
+pub fn merge_bases(root: &Path, a: &str, b: &str) -> Result<Vec<NodeId>> {
+    Repository::open(root)?.merge_bases(a, b)
+}
+
+pub fn is_ancestor(root: &Path, ancestor: &str, node: &str) -> Result<bool> {
+    Repository::open(root)?.is_ancestor(ancestor, node)
+}
+
+pub fn ahead_behind(root: &Path, a: &str, b: &str) -> Result<AheadBehind> {
+    Repository::open(root)?.ahead_behind(a, b)
+}
+
+pub fn descendants(root: &Path, node: &str) -> Result<Vec<NodeId>> {
+    Repository::open(root)?.descendants(node)
+}
+
+// tips 자신과 그 조상 전체(node_parents를 재귀적으로 따라감)
+pub(crate) fn reachable_from(
+    conn: &mut SqliteConnection,
+    tips: &[String],
+) -> Result<HashSet<String>> {
+    if tips.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let rows = sql_query(
+        "WITH RECURSIVE reach(id) AS (
+            SELECT nodes.id FROM nodes WHERE nodes.id IN (SELECT value FROM json_each(?))
+            UNION
+            SELECT node_parents.parent_id FROM node_parents
+            JOIN reach ON node_parents.node_id = reach.id
+        )
+        SELECT id FROM reach",
+    )
+    .bind::<Text, _>(json_array(tips))
+    .load::<IdRow>(conn)
+    .map_err(to_db)?;
+
+    Ok(rows.into_iter().map(|r| r.id).collect())
+}
+
+// from에서 부모를 따라가 target에 닿을 수 있는지(from == target 포함).
+// target을 만나면 더 내려가지 않는다
+pub(crate) fn is_reachable(conn: &mut SqliteConnection, from: &str, target: &str) -> Result<bool> {
+    let row = sql_query(
+        "WITH RECURSIVE reach(id) AS (
+            SELECT ?
+            UNION
+            SELECT node_parents.parent_id FROM node_parents
+            JOIN reach ON node_parents.node_id = reach.id
+            WHERE reach.id <> ?
+        )
+        SELECT EXISTS(SELECT 1 FROM reach WHERE id = ?) AS flag",
+    )
+    .bind::<Text, _>(from)
+    .bind::<Text, _>(target)
+    .bind::<Text, _>(target)
+    .get_result::<FlagRow>(conn)
+    .map_err(to_db)?;
+
+    Ok(row.flag)
+}
+
+// 노드 id 목록을 json_each()에 넘길 JSON 배열 문자열로 만든다
+pub(crate) fn json_array(ids: &[String]) -> String {
+    let items = ids
+        .iter()
+        .map(|id| format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>();
+    format!("[{}]", items.join(","))
+}
@@ -0,0 +1,97 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::vcs::db::meta_dir;
+use crate::{Result, WorkSpaceError};
+
+// 메타 디렉토리 안 훅 디렉토리: hooks/<훅 이름> 실행 파일
+const HOOKS_DIR: &str = "hooks";
+
+// commit이 스냅샷을 저장하기 전에 실행한다. 0이 아닌 종료 코드면 커밋을 거부한다
+pub(crate) const PRE_SNAPSHOT: &str = "pre-snapshot";
+// 체크아웃(pull, undo 포함)이 작업 트리를 바꾼 뒤 실행한다. 종료 코드는 결과에 영향을 주지 않는다
+pub(crate) const POST_CHECKOUT: &str = "post-checkout";
+
+// 훅 실행 결과
+pub(crate) struct HookRun {
+    pub code: Option<i32>,
+    // stdout 뒤에 stderr를 이어 붙인 출력
+    pub output: String,
+}
+
+impl HookRun {
+    pub(crate) fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+// .novel/hooks/<name>이 있으면 작업 트리 루트에서 실행한다.
+// stdin으로 바뀐 파일 경로를 한 줄에 하나씩 넘기고, args는 명령행 인자로 넘긴다.
+// 훅이 없으면 None.
+pub(crate) fn run_hook(
+    root: &Path,
+    name: &str,
+    args: &[&str],
+    changed: &[String],
+) -> Result<Option<HookRun>> {
+    let path = meta_dir(root)?.join(HOOKS_DIR).join(name);
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let mut child = Command::new(&path)
+        .args(args)
+        .current_dir(root.canonicalize()?)
+        .env("NOVEL_HOOK", name)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // 훅이 stdin을 읽지 않고 출력만 많이 써도 막히지 않도록 따로 쓴다
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = changed
+        .iter()
+        .map(|p| format!("{}\n", p))
+        .collect::<String>();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child.wait_with_output()?;
+    match writer.join() {
+        Ok(Ok(())) => {}
+        // stdin을 다 읽지 않고 끝난 훅
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::BrokenPipe => {}
+        Ok(Err(e)) => return Err(e.into()),
+        Err(_) => {
+            return Err(WorkSpaceError::Io(std::io::Error::other(
+                "hook stdin writer panicked",
+            )));
+        }
+    }
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+
+    Ok(Some(HookRun {
+        code: output.status.code(),
+        output: text.trim_end().to_string(),
+    }))
+}
+
+// 훅이 실패하면 HookFailed로 바꾼다
+pub(crate) fn run_required_hook(
+    root: &Path,
+    name: &str,
+    args: &[&str],
+    changed: &[String],
+) -> Result<()> {
+    match run_hook(root, name, args, changed)? {
+        Some(run) if !run.success() => Err(WorkSpaceError::HookFailed {
+            hook: name.to_string(),
+            code: run.code,
+            output: run.output,
+        }),
+        _ => Ok(()),
+    }
+}
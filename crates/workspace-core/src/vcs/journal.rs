@@ -0,0 +1,360 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use diesel::prelude::*;
+use diesel_migrations::MigrationHarness;
+
+use crate::schema::nodes::dsl as nodes_dsl;
+use crate::vcs::db::{MIGRATIONS, open_connection, to_db, to_migration};
+use crate::vcs::reflog::{clear_head, move_head, short_id};
+use crate::vcs::repo::current_head;
+use crate::vcs::snapshot::is_safe_rel_path;
+use crate::{Result, WorkSpaceError};
+
+// 메타 디렉토리 안 체크아웃 작업 디렉토리: journal, stage/<i>, backup/<i>
+const CHECKOUT_DIR: &str = "checkout";
+const JOURNAL_FILE: &str = "journal";
+const STAGE_DIR: &str = "stage";
+const BACKUP_DIR: &str = "backup";
+const JOURNAL_HEADER: &str = "novel-checkout-journal v2";
+// HEAD를 옮긴 뒤 journal 끝에 붙이는 줄. 이 줄이 있어야만 복구가 체크아웃을 마무리한다
+const COMMITTED_LINE: &str = "committed";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum JournalOp {
+    Write,
+    Remove,
+}
+
+#[derive(Debug, Clone)]
+struct JournalEntry {
+    op: JournalOp,
+    path: String,
+}
+
+// 작업 트리를 한 스냅샷에서 다른 스냅샷으로 바꾸는 체크아웃 트랜잭션.
+//
+// 1. stage: 쓸 파일 내용을 전부 stage/<i>에 먼저 쓴다 (작업 트리는 그대로)
+// 2. apply: journal을 남긴 뒤, 바뀌는 경로마다 기존 파일을 backup/<i>로 옮기고 stage/<i>를 제자리로 옮긴다
+// 3. mark_committed: HEAD를 옮긴 뒤 journal에 committed 줄을 붙인다
+// 4. finish: 작업 디렉토리를 지운다
+//
+// apply 도중 실패하면 abort가 backup을 되돌려 놓는다. 프로세스가 죽어 journal이 남으면
+// 다음에 저장소 잠금을 잡을 때 recover_interrupted_checkout이 committed 줄을 보고
+// 마무리하거나, 작업 트리와 HEAD를 체크아웃 전으로 되돌린다.
+pub(crate) struct CheckoutTxn {
+    root: PathBuf,
+    dir: PathBuf,
+    target: String,
+    from: Option<String>,
+    entries: Vec<JournalEntry>,
+    applied: bool,
+}
+
+impl CheckoutTxn {
+    // root는 정규화된 작업 트리 루트, meta는 .novel 디렉토리, from은 체크아웃 전 HEAD
+    pub(crate) fn begin(
+        root: &Path,
+        meta: &Path,
+        target: &str,
+        from: Option<&str>,
+    ) -> Result<CheckoutTxn> {
+        let dir = meta.join(CHECKOUT_DIR);
+        fs::create_dir(&dir)?;
+        fs::create_dir(dir.join(STAGE_DIR))?;
+        fs::create_dir(dir.join(BACKUP_DIR))?;
+
+        Ok(CheckoutTxn {
+            root: root.to_path_buf(),
+            dir,
+            target: target.to_string(),
+            from: from.map(str::to_string),
+            entries: Vec::new(),
+            applied: false,
+        })
+    }
+
+    pub(crate) fn stage_write(&mut self, rel: &str, content: &[u8]) -> Result<()> {
+        check_rel_path(rel)?;
+        let index = self.entries.len();
+        fs::write(self.dir.join(STAGE_DIR).join(index.to_string()), content)?;
+        self.entries.push(JournalEntry {
+            op: JournalOp::Write,
+            path: rel.to_string(),
+        });
+        Ok(())
+    }
+
+    pub(crate) fn stage_remove(&mut self, rel: &str) -> Result<()> {
+        check_rel_path(rel)?;
+        self.entries.push(JournalEntry {
+            op: JournalOp::Remove,
+            path: rel.to_string(),
+        });
+        Ok(())
+    }
+
+    // 실패하면 abort로 작업 트리를 되돌린다
+    pub(crate) fn apply(&mut self) -> Result<()> {
+        write_journal(&self.dir, &self.target, self.from.as_deref(), &self.entries)?;
+        self.applied = true;
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            apply_entry(&self.root, &self.dir, index, entry)?;
+        }
+        Ok(())
+    }
+
+    // HEAD를 옮긴 직후 호출한다. 여기서 실패하면 journal이 남아 다음 복구가 HEAD까지 되돌린다
+    pub(crate) fn mark_committed(&self) -> Result<()> {
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(self.dir.join(JOURNAL_FILE))?;
+        file.write_all(format!("{}\n", COMMITTED_LINE).as_bytes())?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    // mark_committed 뒤에 호출한다: 백업을 버린다
+    pub(crate) fn finish(self) -> Result<()> {
+        fs::remove_file(self.dir.join(JOURNAL_FILE))?;
+        fs::remove_dir_all(&self.dir)?;
+        Ok(())
+    }
+
+    // 작업 트리를 되돌리고 작업 디렉토리를 지운다.
+    // 되돌리기가 실패하면 journal을 남겨 다음 복구에 맡긴다.
+    pub(crate) fn abort(self) {
+        if self.applied && rollback(&self.root, &self.dir, &self.entries).is_err() {
+            return;
+        }
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn apply_entry(root: &Path, dir: &Path, index: usize, entry: &JournalEntry) -> Result<()> {
+    let abs = root.join(&entry.path);
+
+    if is_file(&abs) {
+        fs::rename(&abs, dir.join(BACKUP_DIR).join(index.to_string()))?;
+    }
+
+    if entry.op == JournalOp::Write {
+        if let Some(parent) = abs.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(dir.join(STAGE_DIR).join(index.to_string()), &abs)?;
+    }
+
+    Ok(())
+}
+
+// 디스크 상태만 보고 되돌린다. rename은 원자적이므로 경로마다 상태는
+// (아무것도 안 함 | 백업만 함 | 백업 후 새 파일을 놓음 | 원본 없이 새 파일을 놓음) 중 하나다.
+// 놓인 새 파일은 지우지 않고 stage로 돌려보내서, 되돌리기가 중간에 멈춰도 다시 돌릴 수 있다.
+fn rollback(root: &Path, dir: &Path, entries: &[JournalEntry]) -> Result<()> {
+    let mut first_error = None;
+
+    for (index, entry) in entries.iter().enumerate().rev() {
+        let abs = root.join(&entry.path);
+        let staged = dir.join(STAGE_DIR).join(index.to_string());
+        let backup = dir.join(BACKUP_DIR).join(index.to_string());
+
+        let placed = entry.op == JournalOp::Write && !staged.exists();
+        let result = (|| -> Result<()> {
+            if placed && is_file(&abs) {
+                fs::rename(&abs, &staged)?;
+            }
+            if backup.exists() {
+                fs::rename(&backup, &abs)?;
+            }
+            Ok(())
+        })();
+
+        if let Err(e) = result {
+            first_error.get_or_insert(e);
+        }
+    }
+
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+// 저장소 잠금을 잡은 직후 호출한다. 중단된 체크아웃이 남아 있으면 journal에 committed 줄이 있을 때만
+// 마무리(백업 삭제)하고, 없으면 작업 트리를 되돌리고 HEAD가 옮겨졌으면 체크아웃 전으로 돌려놓는다.
+// HEAD가 대상과 같은지로는 판단하지 않는다: HEAD 자신을 다시 체크아웃하다 죽은 경우와 구분할 수 없다.
+// (committed 줄이 없던 예전 형식 journal만 그렇게 판단한다)
+pub(crate) fn recover_interrupted_checkout(root: &Path, meta: &Path) -> Result<()> {
+    let dir = meta.join(CHECKOUT_DIR);
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    let journal = dir.join(JOURNAL_FILE);
+    if journal.exists() {
+        let record = read_journal(&journal)?;
+
+        let mut conn = open_connection(root)?;
+        conn.run_pending_migrations(MIGRATIONS)
+            .map_err(to_migration)?;
+        let head = current_head(&mut conn)?;
+
+        if !record.committed {
+            rollback(&root.canonicalize()?, &dir, &record.entries)?;
+
+            if head != record.from {
+                conn.transaction::<_, WorkSpaceError, _>(|tx| {
+                    restore_head(tx, record.from.as_deref(), &record.target)
+                })?;
+            }
+        }
+        fs::remove_file(&journal)?;
+    }
+
+    // journal이 없으면 작업 트리를 건드리기 전에 멈춘 것이다
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// journal 형식: 머리줄, "target <id>", "from <id|->", 그 뒤로 "write|remove <index> <hex 경로>",
+// HEAD를 옮긴 뒤에는 마지막에 "committed"
+fn write_journal(
+    dir: &Path,
+    target: &str,
+    from: Option<&str>,
+    entries: &[JournalEntry],
+) -> Result<()> {
+    let mut out = format!(
+        "{}\ntarget {}\nfrom {}\n",
+        JOURNAL_HEADER,
+        target,
+        from.unwrap_or("-")
+    );
+    for (index, entry) in entries.iter().enumerate() {
+        let op = match entry.op {
+            JournalOp::Write => "write",
+            JournalOp::Remove => "remove",
+        };
+        out.push_str(&format!(
+            "{} {} {}\n",
+            op,
+            index,
+            hex::encode(entry.path.as_bytes())
+        ));
+    }
+
+    let mut file = fs::File::create(dir.join(JOURNAL_FILE))?;
+    file.write_all(out.as_bytes())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+struct JournalRecord {
+    target: String,
+    // 체크아웃 전 HEAD
+    from: Option<String>,
+    entries: Vec<JournalEntry>,
+    committed: bool,
+}
+
+fn read_journal(path: &Path) -> Result<JournalRecord> {
+    let text = fs::read_to_string(path)?;
+    let mut lines = text.lines();
+
+    if lines.next() != Some(JOURNAL_HEADER) {
+        return Err(invalid_journal("unknown header"));
+    }
+    let target = lines
+        .next()
+        .and_then(|l| l.strip_prefix("target "))
+        .ok_or_else(|| invalid_journal("missing target line"))?
+        .to_string();
+    let from = match lines.next().and_then(|l| l.strip_prefix("from ")) {
+        Some("-") => None,
+        Some(id) => Some(id.to_string()),
+        None => return Err(invalid_journal("missing from line")),
+    };
+
+    let mut entries = Vec::new();
+    let mut committed = false;
+    for line in lines.filter(|l| !l.is_empty()) {
+        if committed {
+            return Err(invalid_journal(line));
+        }
+        if line == COMMITTED_LINE {
+            committed = true;
+            continue;
+        }
+
+        let mut parts = line.split(' ');
+        let op = match parts.next() {
+            Some("write") => JournalOp::Write,
+            Some("remove") => JournalOp::Remove,
+            _ => return Err(invalid_journal(line)),
+        };
+        let index = parts.next().and_then(|i| i.parse::<usize>().ok());
+        let path = parts
+            .next()
+            .and_then(|p| hex::decode(p).ok())
+            .and_then(|p| String::from_utf8(p).ok());
+
+        match (index, path) {
+            (Some(index), Some(path)) if index == entries.len() && is_safe_rel_path(&path) => {
+                entries.push(JournalEntry { op, path })
+            }
+            _ => return Err(invalid_journal(line)),
+        }
+    }
+
+    Ok(JournalRecord {
+        target,
+        from,
+        entries,
+        committed,
+    })
+}
+
+// 체크아웃이 옮긴 HEAD를 체크아웃 전으로 돌려놓는다
+fn restore_head(conn: &mut SqliteConnection, from: Option<&str>, target: &str) -> Result<()> {
+    let message = format!(
+        "rolling back interrupted checkout of {}",
+        short_id(Some(target))
+    );
+    match from {
+        Some(from) => {
+            // 다른 백엔드로 한 storage::checkout이면 HEAD가 이 DB에 없으므로 작업 트리만 되돌린다
+            let known = nodes_dsl::nodes
+                .filter(nodes_dsl::id.eq(from))
+                .count()
+                .get_result::<i64>(conn)
+                .map_err(to_db)?;
+            if known > 0 {
+                move_head(conn, from, "checkout-recover", &message)?;
+            }
+        }
+        None => clear_head(conn, "checkout-recover", &message)?,
+    }
+    Ok(())
+}
+
+// 작업 트리 밖을 가리키는 경로는 스냅샷에서 올 수 없다: 손상된 데이터로 본다
+fn check_rel_path(rel: &str) -> Result<()> {
+    if !is_safe_rel_path(rel) {
+        return Err(WorkSpaceError::CorruptData(format!(
+            "unsafe path in snapshot: {}",
+            rel
+        )));
+    }
+    Ok(())
+}
+
+fn is_file(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|m| m.is_file())
+}
+
+fn invalid_journal(reason: &str) -> WorkSpaceError {
+    WorkSpaceError::CorruptData(format!("invalid checkout journal: {}", reason))
+}
@@ -0,0 +1,136 @@
+use std::collections::{BinaryHeap, HashMap};
+use std::path::Path;
+
+use crate::vcs::repository::Repository;
+use crate::{GraphLayout, GraphRow, NodeId, Result, VersionNode};
+
+impl Repository {
+    // 히스토리 그래프 레이아웃 API (git log --graph와 같은 방식).
+    //
+    // 노드는 위상 순서(자식이 부모보다 먼저, 같은 조건이면 최신 순)로 한 행씩 놓이고,
+    // 각 열(lane)은 "아래쪽에서 기다리는 노드 id"를 가진 슬롯이고, 노드는 자신을 기다리던 열 중 가장 왼쪽에 놓인다.
+    // 슬롯은 비어도 당겨지지 않으므로 한 번 정해진 열은 머지를 지나도 그대로 유지된다.
+    pub fn graph_layout(&self) -> Result<GraphLayout> {
+        let nodes = topological_order(self.log()?);
+
+        let mut lanes: Vec<Option<NodeId>> = Vec::new();
+        let mut rows = Vec::with_capacity(nodes.len());
+        let mut lane_count = 0;
+
+        for node in nodes {
+            let incoming = lanes
+                .iter()
+                .enumerate()
+                .filter(|(_, id)| id.as_deref() == Some(node.id.as_str()))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            let pass_through = lanes
+                .iter()
+                .enumerate()
+                .filter(|(_, id)| id.as_ref().is_some_and(|id| *id != node.id))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+
+            // 기다리던 열이 없으면 새 head: 첫 빈 열에 놓는다
+            let lane = match incoming.first() {
+                Some(&lane) => lane,
+                None => free_lane(&mut lanes, 0),
+            };
+            let width_before = lanes.len();
+
+            // 이 노드로 모인 열은 모두 닫고, 부모를 기다리는 열을 정한다
+            for &i in &incoming {
+                lanes[i] = None;
+            }
+
+            // 첫 부모는 노드의 열을 그대로 잇는다. 다른 열도 같은 부모를 기다리고 있으면
+            // 두 선은 그 부모의 행에서 합쳐진다. 나머지 부모는 이미 기다리는 열이 있으면 그리로,
+            // 없으면 오른쪽 빈 열로 간다.
+            let mut parent_lanes = Vec::with_capacity(node.parents.len());
+            for (index, parent) in node.parents.iter().enumerate() {
+                let parent_lane = if index == 0 {
+                    lane
+                } else {
+                    match lanes.iter().position(|id| id.as_ref() == Some(parent)) {
+                        Some(j) => j,
+                        None => free_lane(&mut lanes, lane + 1),
+                    }
+                };
+                lanes[parent_lane] = Some(parent.clone());
+                parent_lanes.push(parent_lane);
+            }
+
+            while lanes.last().is_some_and(Option::is_none) {
+                lanes.pop();
+            }
+
+            let row_lane_count = width_before.max(lanes.len()).max(lane + 1);
+            lane_count = lane_count.max(row_lane_count);
+
+            rows.push(GraphRow {
+                node,
+                lane,
+                incoming,
+                pass_through,
+                parent_lanes,
+                lane_count: row_lane_count,
+            });
+        }
+
+        Ok(GraphLayout { rows, lane_count })
+    }
+}
+
+pub fn graph_layout(root: &Path) -> Result<GraphLayout> {
+    Repository::open(root)?.graph_layout()
+}
+
+// from 이상에서 첫 빈 열(없으면 새 열)
+fn free_lane(lanes: &mut Vec<Option<NodeId>>, from: usize) -> usize {
+    if let Some(i) = (from..lanes.len()).find(|&i| lanes[i].is_none()) {
+        return i;
+    }
+    lanes.push(None);
+    lanes.len() - 1
+}
+
+// 자식이 모두 나온 노드 중 가장 최신 노드를 먼저 내보낸다(Kahn 알고리즘).
+// 생성 시각이 뒤섞여 있어도(가져온 노드, 시계 차이) 자식이 부모보다 먼저 온다.
+fn topological_order(nodes: Vec<VersionNode>) -> Vec<VersionNode> {
+    let mut child_count: HashMap<NodeId, usize> = nodes.iter().map(|n| (n.id.clone(), 0)).collect();
+    for node in &nodes {
+        for parent in &node.parents {
+            if let Some(count) = child_count.get_mut(parent) {
+                *count += 1;
+            }
+        }
+    }
+
+    let mut by_id: HashMap<NodeId, VersionNode> =
+        nodes.into_iter().map(|n| (n.id.clone(), n)).collect();
+
+    let mut ready = by_id
+        .values()
+        .filter(|n| child_count[&n.id] == 0)
+        .map(|n| (n.created_at_unix_ms, n.id.clone()))
+        .collect::<BinaryHeap<_>>();
+
+    let mut out = Vec::with_capacity(by_id.len());
+    while let Some((_, id)) = ready.pop() {
+        let node = by_id.remove(&id).expect("ready node is unvisited");
+
+        for parent in &node.parents {
+            let Some(count) = child_count.get_mut(parent) else {
+                continue;
+            };
+            *count -= 1;
+            if *count == 0 {
+                ready.push((by_id[parent].created_at_unix_ms, parent.clone()));
+            }
+        }
+
+        out.push(node);
+    }
+
+    out
+}
@@ -0,0 +1,89 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use crate::vcs::db::meta_dir;
+use crate::vcs::journal::recover_interrupted_checkout;
+use crate::{Result, WorkSpaceError};
+
+// 메타 디렉토리 안 잠금 파일 이름
+const LOCK_FILE: &str = "lock";
+
+thread_local! {
+    // 이 스레드가 잡고 있는 잠금: 메타 디렉토리 -> 중첩 횟수
+    static HELD: RefCell<HashMap<PathBuf, usize>> = RefCell::new(HashMap::new());
+}
+
+// 작업 트리나 HEAD를 바꾸는 작업(commit, checkout, 가져오기 등)이 잡는 저장소 잠금.
+// .novel/lock 파일에 OS의 배타 advisory 잠금을 건다. 프로세스가 죽으면 OS가 풀어 주므로
+// pid나 시각으로 버려진 잠금을 추측하지 않는다. 파일은 지우지 않고 계속 둔다.
+// 잠금을 잡으면 중단된 체크아웃 journal이 남아 있는지 먼저 확인한다.
+// 같은 스레드가 이미 잡고 있으면 다시 잡을 수 있다(storage::checkout 안의 SqliteStorage 쓰기 등).
+// 이때는 진행 중인 작업 안이므로 복구하지 않는다
+#[derive(Debug)]
+pub(crate) struct RepoLock {
+    meta: PathBuf,
+    // drop 되어 닫히면 잠금이 풀린다. 중첩된 잠금이면 None
+    _file: Option<fs::File>,
+    // 중첩 횟수를 스레드별로 세므로 다른 스레드로 넘기지 않는다
+    _not_send: PhantomData<*const ()>,
+}
+
+impl RepoLock {
+    pub(crate) fn acquire(root: &Path) -> Result<RepoLock> {
+        let meta = meta_dir(root)?;
+        let nested = HELD.with_borrow_mut(|held| match held.get_mut(&meta) {
+            Some(count) => {
+                *count += 1;
+                true
+            }
+            None => false,
+        });
+        if nested {
+            return Ok(RepoLock {
+                meta,
+                _file: None,
+                _not_send: PhantomData,
+            });
+        }
+
+        let path = meta.join(LOCK_FILE);
+
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(fs::TryLockError::WouldBlock) => return Err(WorkSpaceError::RepoLocked(path)),
+            Err(fs::TryLockError::Error(e)) => return Err(e.into()),
+        }
+        HELD.with_borrow_mut(|held| held.insert(meta.clone(), 1));
+        let lock = RepoLock {
+            meta: meta.clone(),
+            _file: Some(file),
+            _not_send: PhantomData,
+        };
+
+        // 이전 프로세스가 체크아웃 도중 죽었으면 작업 트리를 먼저 정리한다
+        recover_interrupted_checkout(root, &meta)?;
+        Ok(lock)
+    }
+}
+
+impl Drop for RepoLock {
+    fn drop(&mut self) {
+        HELD.with_borrow_mut(|held| {
+            if let Some(count) = held.get_mut(&self.meta) {
+                *count -= 1;
+                if *count == 0 {
+                    held.remove(&self.meta);
+                }
+            }
+        });
+    }
+}
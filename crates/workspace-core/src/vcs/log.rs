@@ -1,10 +1,12 @@
 use std::path::Path;
 
 use diesel::prelude::*;
-use diesel_migrations::MigrationHarness;
 
-use crate::schema::node_parents;
-use crate::vcs::db::{MIGRATIONS, open_connection, to_io};
+use crate::vcs::autosave::{thinned_parents, visible_parents};
+use crate::vcs::notes::notes_by_node;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::repository::Repository;
+use crate::vcs::storage::{self, SqliteObjects};
 use crate::{Result, VersionNode};
 
 #[derive(Debug, Queryable)]
@@ -12,42 +14,34 @@ pub struct NodeRow {
     pub id: String,
     pub message: String,
     pub created_at_unix_ms: i64,
+    pub kind: String,
 }
 
-// 로그 조회 API 스텁
-pub fn log(root: &Path) -> Result<Vec<VersionNode>> {
-    use crate::schema::nodes::dsl as nodes_dsl;
-
-    let mut conn = open_connection(root)?;
-    conn.run_pending_migrations(MIGRATIONS).map_err(to_io)?;
-
-    let node_rows = nodes_dsl::nodes
-        .select((
-            nodes_dsl::id,
-            nodes_dsl::message,
-            nodes_dsl::created_at_unix_ms,
-        ))
-        .order(nodes_dsl::created_at_unix_ms.desc())
-        .load::<NodeRow>(&mut conn)
-        .map_err(to_io)?;
-
-    let mut out: Vec<VersionNode> = Vec::with_capacity(node_rows.len());
-
-    for row in node_rows {
-        let parents = node_parents::dsl::node_parents
-            .filter(node_parents::dsl::node_id.eq(&row.id))
-            .select(node_parents::dsl::parent_id)
-            .order(node_parents::dsl::ord.asc())
-            .load::<String>(&mut conn)
-            .map_err(to_io)?;
-
-        out.push(VersionNode {
-            id: row.id,
-            message: row.message,
-            created_at_unix_ms: row.created_at_unix_ms,
-            parents,
-        });
+impl Repository {
+    // 로그 조회 API 스텁
+    pub fn log(&self) -> Result<Vec<VersionNode>> {
+        let root = self.root();
+        let conn = &mut *self.conn()?;
+
+        let store = BlobStore::open(root, conn)?;
+        let mut out = storage::log(&mut SqliteObjects::new(conn, &store))?;
+
+        // 솎아낸 자동 저장은 빼고, 그 자리에는 남은 조상을 부모로 보여 준다
+        let thinned = thinned_parents(conn)?;
+        out.retain(|node| !thinned.contains_key(&node.id));
+        for node in &mut out {
+            node.parents = visible_parents(&node.parents, &thinned);
+        }
+
+        let mut notes = notes_by_node(conn, store.cipher())?;
+        for node in &mut out {
+            node.notes = notes.remove(&node.id).unwrap_or_default();
+        }
+
+        Ok(out)
     }
+}
 
-    Ok(out)
+pub fn log(root: &Path) -> Result<Vec<VersionNode>> {
+    Repository::open(root)?.log()
 }
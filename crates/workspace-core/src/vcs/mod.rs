@@ -1,15 +1,66 @@
+mod autosave;
+mod bundle;
 mod checkout;
 mod commit;
+mod config;
+mod crypto;
 mod db;
 mod diff;
+mod graph;
+mod hooks;
+mod journal;
+mod layout;
+mod lock;
 mod log;
+mod notes;
+mod objects;
+mod pickaxe;
+mod progress;
+mod reflog;
+mod refs;
+mod remote;
 mod repo;
+mod repository;
+mod revision;
+mod search;
 mod snapshot;
+mod stage;
+pub mod storage;
+mod sync;
+mod timeline;
+mod tree;
 mod types;
 
+pub use autosave::{autosave, gc, gc_with_progress, thin_autosaves};
+pub use bundle::{
+    bundle_create, bundle_create_since, bundle_create_with_progress, bundle_unbundle,
+    bundle_unbundle_with_progress,
+};
 pub use checkout::*;
 pub use commit::*;
+pub use crypto::{
+    forget_repo_key, is_encrypted, plaintext_export_allowed, rotate_key, set_plaintext_export,
+    unlock_repo,
+};
 pub use diff::*;
+pub use graph::{ahead_behind, descendants, is_ancestor, merge_base, merge_bases};
+pub use layout::graph_layout;
 pub use log::*;
+pub use notes::{node_notes, note_add, note_edit, note_remove};
+pub use objects::{DEFAULT_LARGE_BLOB_THRESHOLD, large_blob_threshold, set_large_blob_threshold};
+pub use pickaxe::pickaxe;
+pub use progress::{CancelToken, Progress, ProgressEvent, ProgressPhase};
+pub use reflog::{reflog, undo_last_head_move};
+pub use refs::{tag_create, tag_delete, tags};
+pub use remote::*;
 pub use repo::*;
+pub use repository::Repository;
+pub use revision::resolve_revision;
+pub use search::search_history;
+pub use stage::{commit_staged, stage, staged, unstage};
+pub use sync::{
+    SYNC_ADVERTISE_PATH, SYNC_RECEIVE_PACK_PATH, SYNC_UPLOAD_PACK_PATH, SyncRequest, sync_serve,
+    sync_status_code,
+};
+pub use timeline::progress_series;
 pub use types::*;
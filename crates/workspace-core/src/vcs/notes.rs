@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::Integer;
+
+use crate::schema::node_notes::dsl as notes_dsl;
+use crate::vcs::commit::now_unix_ms;
+use crate::vcs::crypto::{Cipher, open_message, repo_cipher, seal_message};
+use crate::vcs::db::to_db;
+use crate::vcs::lock::RepoLock;
+use crate::vcs::repository::Repository;
+use crate::vcs::revision::resolve;
+use crate::{NodeId, NodeNote, Result, WorkSpaceError};
+
+impl Repository {
+    // revision이 가리키는 노드에 메모를 붙인다. 노드 id와 스냅샷은 바뀌지 않는다
+    pub fn note_add(&self, revision: &str, text: &str) -> Result<NodeNote> {
+        let root = self.root();
+        let text = note_text(text)?;
+
+        // 키 교체와 겹치지 않게 잠근다
+        let _lock = RepoLock::acquire(root)?;
+        let conn = &mut *self.conn()?;
+
+        let cipher = repo_cipher(root, conn)?;
+        let node_id = resolve(conn, revision)?;
+        let now = now_unix_ms();
+
+        diesel::insert_into(notes_dsl::node_notes)
+            .values((
+                notes_dsl::node_id.eq(&node_id),
+                notes_dsl::text.eq(seal_message(cipher.as_ref(), &note_aad(&node_id), text)),
+                notes_dsl::created_at_unix_ms.eq(now),
+                notes_dsl::updated_at_unix_ms.eq(now),
+            ))
+            .execute(conn)
+            .map_err(to_db)?;
+        // 같은 연결에서 방금 넣은 행의 id
+        let id = diesel::select(sql::<Integer>("last_insert_rowid()"))
+            .get_result::<i32>(conn)
+            .map_err(to_db)?;
+
+        Ok(NodeNote {
+            id,
+            node_id,
+            text: text.to_string(),
+            created_at_unix_ms: now,
+            updated_at_unix_ms: now,
+        })
+    }
+
+    // 메모 내용을 바꾼다. created_at은 그대로 두고 updated_at만 옮긴다
+    pub fn note_edit(&self, note_id: i32, text: &str) -> Result<NodeNote> {
+        let root = self.root();
+        let text = note_text(text)?;
+
+        let _lock = RepoLock::acquire(root)?;
+        let conn = &mut *self.conn()?;
+
+        let cipher = repo_cipher(root, conn)?;
+        let (node_id, created_at_unix_ms) = notes_dsl::node_notes
+            .filter(notes_dsl::id.eq(note_id))
+            .select((notes_dsl::node_id, notes_dsl::created_at_unix_ms))
+            .first::<(String, i64)>(conn)
+            .optional()
+            .map_err(to_db)?
+            .ok_or_else(|| note_not_found(note_id))?;
+        let now = now_unix_ms();
+
+        diesel::update(notes_dsl::node_notes.filter(notes_dsl::id.eq(note_id)))
+            .set((
+                notes_dsl::text.eq(seal_message(cipher.as_ref(), &note_aad(&node_id), text)),
+                notes_dsl::updated_at_unix_ms.eq(now),
+            ))
+            .execute(conn)
+            .map_err(to_db)?;
+
+        Ok(NodeNote {
+            id: note_id,
+            node_id,
+            text: text.to_string(),
+            created_at_unix_ms,
+            updated_at_unix_ms: now,
+        })
+    }
+
+    pub fn note_remove(&self, note_id: i32) -> Result<()> {
+        let _lock = RepoLock::acquire(self.root())?;
+        let conn = &mut *self.conn()?;
+
+        let removed = diesel::delete(notes_dsl::node_notes.filter(notes_dsl::id.eq(note_id)))
+            .execute(conn)
+            .map_err(to_db)?;
+
+        if removed == 0 {
+            return Err(note_not_found(note_id));
+        }
+
+        Ok(())
+    }
+
+    // 노드의 메모(붙인 순서대로)
+    pub fn node_notes(&self, revision: &str) -> Result<Vec<NodeNote>> {
+        let root = self.root();
+        let conn = &mut *self.conn()?;
+
+        let cipher = repo_cipher(root, conn)?;
+        let node_id = resolve(conn, revision)?;
+
+        let rows = notes_dsl::node_notes
+            .filter(notes_dsl::node_id.eq(&node_id))
+            .order(notes_dsl::id.asc())
+            .load::<NodeNote>(conn)
+            .map_err(to_db)?;
+
+        rows.into_iter()
+            .map(|note| open_note(cipher.as_ref(), note))
+            .collect()
+    }
+}
+
+pub fn note_add(root: &Path, revision: &str, text: &str) -> Result<NodeNote> {
+    Repository::open(root)?.note_add(revision, text)
+}
+
+pub fn note_edit(root: &Path, note_id: i32, text: &str) -> Result<NodeNote> {
+    Repository::open(root)?.note_edit(note_id, text)
+}
+
+pub fn note_remove(root: &Path, note_id: i32) -> Result<()> {
+    Repository::open(root)?.note_remove(note_id)
+}
+
+pub fn node_notes(root: &Path, revision: &str) -> Result<Vec<NodeNote>> {
+    Repository::open(root)?.node_notes(revision)
+}
+
+// log에 붙일 노드별 메모 전체
+pub(crate) fn notes_by_node(
+    conn: &mut SqliteConnection,
+    cipher: Option<&Cipher>,
+) -> Result<HashMap<NodeId, Vec<NodeNote>>> {
+    let rows = notes_dsl::node_notes
+        .order(notes_dsl::id.asc())
+        .load::<NodeNote>(conn)
+        .map_err(to_db)?;
+
+    let mut out: HashMap<NodeId, Vec<NodeNote>> = HashMap::new();
+    for note in rows {
+        let note = open_note(cipher, note)?;
+        out.entry(note.node_id.clone()).or_default().push(note);
+    }
+
+    Ok(out)
+}
+
+fn open_note(cipher: Option<&Cipher>, mut note: NodeNote) -> Result<NodeNote> {
+    note.text = open_message(cipher, &note_aad(&note.node_id), note.text)?;
+    Ok(note)
+}
+
+// 노드 메시지(aad = 노드 id)와 자리를 바꿔 쓸 수 없게 구분한다
+pub(crate) fn note_aad(node_id: &str) -> Vec<u8> {
+    format!("note {}", node_id).into_bytes()
+}
+
+fn note_text(text: &str) -> Result<&str> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(WorkSpaceError::InvalidInput("empty note".to_string()));
+    }
+
+    Ok(text)
+}
+
+fn note_not_found(note_id: i32) -> WorkSpaceError {
+    WorkSpaceError::NotFound(format!("note not found: {}", note_id))
+}
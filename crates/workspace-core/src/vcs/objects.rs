@@ -0,0 +1,467 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use diesel::prelude::*;
+
+use crate::schema::blobs::dsl as blobs_dsl;
+use crate::schema::node_files::dsl as node_files_dsl;
+use crate::vcs::autosave::require_snapshot;
+use crate::vcs::config::{get_config, set_config};
+use crate::vcs::crypto::{Cipher, repo_cipher};
+use crate::vcs::db::{meta_dir, to_db};
+use crate::vcs::diff::is_probably_binary;
+use crate::vcs::progress::{Progress, ProgressPhase};
+use crate::vcs::repository::Repository;
+use crate::vcs::search::index_blob;
+use crate::vcs::snapshot::blob_id_for_content;
+use crate::{Result, WorkSpaceError};
+
+// 메타 디렉토리 안 외부 blob 디렉토리: objects/<id 앞 2자리>/<나머지>
+const OBJECTS_DIR: &str = "objects";
+const THRESHOLD_KEY: &str = "large_blob_threshold";
+// 키 교체 트랜잭션이 커밋됐지만 .rekey 파일을 아직 바꿔 넣지 못했다는 표시
+const REKEY_PENDING_KEY: &str = "encryption.rekey_pending";
+const REKEY_EXT: &str = "rekey";
+// 이보다 큰 blob은 SQLite 밖 파일로 저장한다
+pub const DEFAULT_LARGE_BLOB_THRESHOLD: u64 = 1024 * 1024;
+
+impl Repository {
+    // 큰 blob을 외부 파일로 저장하는 기준(바이트)
+    pub fn large_blob_threshold(&self) -> Result<u64> {
+        let conn = &mut *self.conn()?;
+
+        read_threshold(conn)
+    }
+
+    // 기준을 바꿔도 이미 저장된 blob은 옮기지 않는다. 이후 저장되는 blob부터 적용된다
+    pub fn set_large_blob_threshold(&self, bytes: u64) -> Result<()> {
+        let conn = &mut *self.conn()?;
+
+        set_config(conn, THRESHOLD_KEY, &bytes.to_string())
+    }
+}
+
+pub fn large_blob_threshold(root: &Path) -> Result<u64> {
+    Repository::open(root)?.large_blob_threshold()
+}
+
+pub fn set_large_blob_threshold(root: &Path, bytes: u64) -> Result<()> {
+    Repository::open(root)?.set_large_blob_threshold(bytes)
+}
+
+fn read_threshold(conn: &mut SqliteConnection) -> Result<u64> {
+    match get_config(conn, THRESHOLD_KEY)? {
+        Some(value) => value.parse::<u64>().map_err(|_| {
+            WorkSpaceError::CorruptData(format!("invalid {}: {}", THRESHOLD_KEY, value))
+        }),
+        None => Ok(DEFAULT_LARGE_BLOB_THRESHOLD),
+    }
+}
+
+// blob 크기와 단어 수. 바이너리면 words는 None
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BlobStats {
+    pub size: i64,
+    pub words: Option<i64>,
+}
+
+impl BlobStats {
+    pub(crate) fn of(content: &[u8]) -> BlobStats {
+        let words = if is_probably_binary(content) {
+            None
+        } else {
+            // 바이너리가 아니면 UTF-8이다
+            let text = std::str::from_utf8(content).unwrap_or_default();
+            Some(text.split_whitespace().count() as i64)
+        };
+
+        BlobStats {
+            size: content.len() as i64,
+            words,
+        }
+    }
+}
+
+// blob 읽기/쓰기. 작은 blob은 blobs.content에, 큰 blob은 objects 파일에 두고
+// blobs에는 external = 1인 빈 행만 남긴다. 호출하는 쪽은 어느 쪽인지 몰라도 된다.
+// 암호화 저장소면 두 곳 모두 blob id를 aad로 봉인한 내용을 둔다
+pub(crate) struct BlobStore {
+    dir: PathBuf,
+    threshold: u64,
+    cipher: Option<Cipher>,
+}
+
+impl BlobStore {
+    pub(crate) fn open(root: &Path, conn: &mut SqliteConnection) -> Result<BlobStore> {
+        let cipher = repo_cipher(root, conn)?;
+        BlobStore::with_cipher(root, conn, cipher)
+    }
+
+    pub(crate) fn with_cipher(
+        root: &Path,
+        conn: &mut SqliteConnection,
+        cipher: Option<Cipher>,
+    ) -> Result<BlobStore> {
+        let store = BlobStore {
+            dir: meta_dir(root)?.join(OBJECTS_DIR),
+            threshold: read_threshold(conn)?,
+            cipher,
+        };
+        // 키 교체 도중 멈췄으면 커밋된 쪽으로 마무리한다
+        if get_config(conn, REKEY_PENDING_KEY)?.is_some() {
+            store.finish_rekey(conn)?;
+        }
+
+        Ok(store)
+    }
+
+    pub(crate) fn cipher(&self) -> Option<&Cipher> {
+        self.cipher.as_ref()
+    }
+
+    // 없을 때만 넣는다. 새로 넣었으면 1.
+    // 외부 파일은 행보다 먼저 쓴다: 트랜잭션이 롤백돼 남은 파일은 gc가 지운다
+    pub(crate) fn put(
+        &self,
+        conn: &mut SqliteConnection,
+        id: &str,
+        content: &[u8],
+    ) -> Result<usize> {
+        let present = blobs_dsl::blobs
+            .filter(blobs_dsl::id.eq(id))
+            .count()
+            .get_result::<i64>(conn)
+            .map_err(to_db)?;
+        if present > 0 {
+            return Ok(0);
+        }
+
+        let external = content.len() as u64 > self.threshold;
+        let sealed = self.seal(id, content);
+        if external {
+            self.write_object(id, &sealed)?;
+        }
+
+        let stored: &[u8] = if external { &[] } else { &sealed };
+        let stats = BlobStats::of(content);
+        let inserted = diesel::insert_into(blobs_dsl::blobs)
+            .values((
+                blobs_dsl::id.eq(id),
+                blobs_dsl::content.eq(stored),
+                blobs_dsl::external.eq(external),
+                blobs_dsl::size.eq(stats.size),
+                blobs_dsl::word_count.eq(stats.words),
+            ))
+            .execute(conn)
+            .map_err(to_db)?;
+
+        // 검색 색인은 평문이므로 암호화 저장소에서는 만들지 않는다
+        if self.cipher.is_none() && stats.words.is_some() {
+            index_blob(conn, id, &String::from_utf8_lossy(content))?;
+        }
+
+        Ok(inserted)
+    }
+
+    // ids 중 있는 blob의 (id, 내용)
+    pub(crate) fn load(
+        &self,
+        conn: &mut SqliteConnection,
+        ids: &[String],
+    ) -> Result<Vec<(String, Vec<u8>)>> {
+        let rows = blobs_dsl::blobs
+            .filter(blobs_dsl::id.eq_any(ids))
+            .select((blobs_dsl::id, blobs_dsl::external, blobs_dsl::content))
+            .load::<(String, bool, Vec<u8>)>(conn)
+            .map_err(to_db)?;
+
+        rows.into_iter()
+            .map(|(id, external, content)| {
+                let content = self.resolve(&id, external, content)?;
+                Ok((id, content))
+            })
+            .collect()
+    }
+
+    // 노드 스냅샷의 (경로, 내용)
+    pub(crate) fn node_contents(
+        &self,
+        conn: &mut SqliteConnection,
+        node_id: &str,
+    ) -> Result<Vec<(String, Vec<u8>)>> {
+        require_snapshot(conn, node_id)?;
+
+        let rows = node_files_dsl::node_files
+            .inner_join(blobs_dsl::blobs.on(node_files_dsl::blob_id.eq(blobs_dsl::id)))
+            .filter(node_files_dsl::node_id.eq(node_id))
+            .select((
+                node_files_dsl::path,
+                blobs_dsl::id,
+                blobs_dsl::external,
+                blobs_dsl::content,
+            ))
+            .load::<(String, String, bool, Vec<u8>)>(conn)
+            .map_err(to_db)?;
+
+        rows.into_iter()
+            .map(|(path, id, external, content)| Ok((path, self.resolve(&id, external, content)?)))
+            .collect()
+    }
+
+    // 노드 스냅샷에서 path 한 파일의 내용
+    pub(crate) fn node_file(
+        &self,
+        conn: &mut SqliteConnection,
+        node_id: &str,
+        path: &str,
+    ) -> Result<Option<Vec<u8>>> {
+        require_snapshot(conn, node_id)?;
+
+        let row = node_files_dsl::node_files
+            .inner_join(blobs_dsl::blobs.on(node_files_dsl::blob_id.eq(blobs_dsl::id)))
+            .filter(node_files_dsl::node_id.eq(node_id))
+            .filter(node_files_dsl::path.eq(path))
+            .select((blobs_dsl::id, blobs_dsl::external, blobs_dsl::content))
+            .first::<(String, bool, Vec<u8>)>(conn)
+            .optional()
+            .map_err(to_db)?;
+
+        row.map(|(id, external, content)| self.resolve(&id, external, content))
+            .transpose()
+    }
+
+    // blob 통계. 통계 컬럼 이전에 저장된 blob은 내용을 한 번 읽어 채워 둔다
+    pub(crate) fn stats(
+        &self,
+        conn: &mut SqliteConnection,
+        ids: &[String],
+    ) -> Result<HashMap<String, BlobStats>> {
+        let rows = blobs_dsl::blobs
+            .filter(blobs_dsl::id.eq_any(ids))
+            .select((blobs_dsl::id, blobs_dsl::size, blobs_dsl::word_count))
+            .load::<(String, Option<i64>, Option<i64>)>(conn)
+            .map_err(to_db)?;
+
+        let mut stats = HashMap::with_capacity(rows.len());
+        let mut missing = Vec::new();
+        for (id, size, words) in rows {
+            match size {
+                Some(size) => {
+                    stats.insert(id, BlobStats { size, words });
+                }
+                None => missing.push(id),
+            }
+        }
+
+        for (id, content) in self.load(conn, &missing)? {
+            let computed = BlobStats::of(&content);
+            diesel::update(blobs_dsl::blobs.filter(blobs_dsl::id.eq(&id)))
+                .set((
+                    blobs_dsl::size.eq(computed.size),
+                    blobs_dsl::word_count.eq(computed.words),
+                ))
+                .execute(conn)
+                .map_err(to_db)?;
+            stats.insert(id, computed);
+        }
+
+        Ok(stats)
+    }
+
+    // blobs에 external 행이 없는 objects 파일(지운 blob, 롤백된 커밋의 잔여물)을 지운다
+    pub(crate) fn sweep(&self, conn: &mut SqliteConnection, progress: &Progress) -> Result<usize> {
+        if !self.dir.exists() {
+            return Ok(0);
+        }
+
+        let live = blobs_dsl::blobs
+            .filter(blobs_dsl::external.eq(true))
+            .select(blobs_dsl::id)
+            .load::<String>(conn)
+            .map_err(to_db)?
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        // 전체 개수를 알리기 위해 먼저 목록을 만든다
+        let mut fan_outs = Vec::new();
+        for fan_out in fs::read_dir(&self.dir)? {
+            let fan_out = fan_out?;
+            if !fan_out.file_type()?.is_dir() {
+                continue;
+            }
+            let prefix = fan_out.file_name().to_string_lossy().into_owned();
+
+            let mut entries = Vec::new();
+            for entry in fs::read_dir(fan_out.path())? {
+                let entry = entry?;
+                let id = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+                entries.push((id, entry.path(), entry.metadata()?.len()));
+            }
+            fan_outs.push((fan_out.path(), entries));
+        }
+
+        let total = fan_outs.iter().map(|(_, entries)| entries.len()).sum();
+        let mut done = 0;
+        let mut bytes = 0;
+        let mut removed = 0;
+        for (dir, entries) in fan_outs {
+            for (id, path, len) in entries {
+                progress.checkpoint()?;
+                if !live.contains(&id) {
+                    fs::remove_file(path)?;
+                    removed += 1;
+                }
+                done += 1;
+                bytes += len;
+                progress.report(ProgressPhase::Sweep, done, total, bytes);
+            }
+            // 빈 fan-out 디렉토리는 남겨 두지 않는다
+            let _ = fs::remove_dir(dir);
+        }
+
+        Ok(removed)
+    }
+
+    // 모든 blob을 to로 다시 봉인한다. 행은 conn(트랜잭션) 안에서 바꾸고 objects 파일은 옆에
+    // .rekey 파일로 써 둔다. 트랜잭션이 커밋되면 finish_rekey, 롤백되면 discard_rekey를 부른다
+    pub(crate) fn rekey_blobs(&self, conn: &mut SqliteConnection, to: &Cipher) -> Result<()> {
+        let rows = blobs_dsl::blobs
+            .select((blobs_dsl::id, blobs_dsl::external, blobs_dsl::content))
+            .load::<(String, bool, Vec<u8>)>(conn)
+            .map_err(to_db)?;
+
+        for (id, external, content) in rows {
+            let sealed = to.seal(id.as_bytes(), &self.resolve(&id, external, content)?);
+            if external {
+                let path = self.object_path(&id).with_extension(REKEY_EXT);
+                let mut file = fs::File::create(&path)?;
+                file.write_all(&sealed)?;
+                file.sync_all()?;
+            } else {
+                diesel::update(blobs_dsl::blobs.filter(blobs_dsl::id.eq(&id)))
+                    .set(blobs_dsl::content.eq(sealed))
+                    .execute(conn)
+                    .map_err(to_db)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // 키 교체 트랜잭션 안에서 부른다: 커밋되면 .rekey 파일이 새 키의 내용이다
+    pub(crate) fn mark_rekey_pending(&self, conn: &mut SqliteConnection) -> Result<()> {
+        set_config(conn, REKEY_PENDING_KEY, "1")
+    }
+
+    // .rekey 파일을 원래 objects 파일 자리로 옮기고 표시를 지운다
+    pub(crate) fn finish_rekey(&self, conn: &mut SqliteConnection) -> Result<()> {
+        for path in self.rekey_files()? {
+            fs::rename(&path, path.with_extension(""))?;
+        }
+
+        diesel::delete(
+            crate::schema::config::table.filter(crate::schema::config::key.eq(REKEY_PENDING_KEY)),
+        )
+        .execute(conn)
+        .map_err(to_db)?;
+
+        Ok(())
+    }
+
+    pub(crate) fn discard_rekey(&self) -> Result<()> {
+        for path in self.rekey_files()? {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    fn rekey_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        if !self.dir.exists() {
+            return Ok(files);
+        }
+
+        for fan_out in fs::read_dir(&self.dir)? {
+            let fan_out = fan_out?;
+            if !fan_out.file_type()?.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(fan_out.path())? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == REKEY_EXT) {
+                    files.push(path);
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn seal(&self, id: &str, content: &[u8]) -> Vec<u8> {
+        match &self.cipher {
+            Some(cipher) => cipher.seal(id.as_bytes(), content),
+            None => content.to_vec(),
+        }
+    }
+
+    fn open_sealed(&self, id: &str, content: Vec<u8>) -> Result<Vec<u8>> {
+        match &self.cipher {
+            Some(cipher) => cipher
+                .open(id.as_bytes(), &content)
+                .ok_or_else(|| WorkSpaceError::CorruptData(format!("cannot decrypt blob: {}", id))),
+            None => Ok(content),
+        }
+    }
+
+    fn resolve(&self, id: &str, external: bool, content: Vec<u8>) -> Result<Vec<u8>> {
+        if !external {
+            return self.open_sealed(id, content);
+        }
+
+        let content = fs::read(self.object_path(id)).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                WorkSpaceError::NotFound(format!("missing object: {}", id))
+            } else {
+                e.into()
+            }
+        })?;
+        let content = self.open_sealed(id, content)?;
+
+        // 파일은 DB 밖에 있으므로 읽을 때마다 내용이 id와 맞는지 확인한다
+        if blob_id_for_content(&content) != id {
+            return Err(WorkSpaceError::CorruptData(format!(
+                "corrupt object: {}",
+                id
+            )));
+        }
+        Ok(content)
+    }
+
+    // 내용 주소 파일이므로 이미 있으면 그대로 쓴다. 임시 파일에 쓴 뒤 rename해서 반쯤 쓴 파일이 남지 않게 한다.
+    // 암호화 저장소에서는 남은 파일이 다른 키로 봉인됐을 수 있어 항상 새로 쓴다
+    fn write_object(&self, id: &str, content: &[u8]) -> Result<()> {
+        let path = self.object_path(id);
+        if path.exists() && self.cipher.is_none() {
+            return Ok(());
+        }
+
+        let parent = path.parent().expect("object path has a fan-out directory");
+        fs::create_dir_all(parent)?;
+
+        let tmp = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path)?;
+
+        Ok(())
+    }
+
+    fn object_path(&self, id: &str) -> PathBuf {
+        let (fan_out, rest) = id.split_at(2.min(id.len()));
+        self.dir.join(fan_out).join(rest)
+    }
+}
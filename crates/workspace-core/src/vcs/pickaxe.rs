@@ -0,0 +1,190 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+use diesel::prelude::*;
+
+use crate::schema::node_parents::dsl as node_parents_dsl;
+use crate::schema::nodes::dsl as nodes_dsl;
+use crate::vcs::autosave::{thinned_parents, visible_parents};
+use crate::vcs::db::to_db;
+use crate::vcs::diff::load_blob_id_map;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::repository::Repository;
+use crate::vcs::snapshot::normalize_rel_path;
+use crate::{NodeId, PickaxeHit, Result, WorkSpaceError};
+
+impl Repository {
+    // 모든 노드를 부모와 비교해 파일 안의 phrase 개수가 바뀐 곳을 찾는다(최신 노드부터).
+    // path_filter는 파일 경로나 디렉터리. 병합 노드는 모든 부모와 개수가 다를 때만(병합하며 바뀐 것) 나온다
+    pub fn pickaxe(&self, phrase: &str, path_filter: Option<&str>) -> Result<Vec<PickaxeHit>> {
+        let root = self.root();
+        if phrase.is_empty() {
+            return Err(WorkSpaceError::InvalidInput(
+                "empty pickaxe phrase".to_string(),
+            ));
+        }
+
+        let conn = &mut *self.conn()?;
+
+        let store = BlobStore::open(root, conn)?;
+        // "./drafts/" 같은 형태도 받는다
+        let filter = path_filter.map(|p| {
+            normalize_rel_path(
+                &Path::new(p)
+                    .components()
+                    .filter(|c| matches!(c, Component::Normal(_)))
+                    .collect::<PathBuf>(),
+            )
+        });
+
+        // 솎아낸 노드는 파일 목록이 없으므로 건너뛰고 그 위의 남은 조상과 비교한다
+        let nodes = nodes_dsl::nodes
+            .filter(nodes_dsl::thinned.eq(false))
+            .select(nodes_dsl::id)
+            .order((nodes_dsl::created_at_unix_ms.desc(), nodes_dsl::id.desc()))
+            .load::<String>(conn)
+            .map_err(to_db)?;
+
+        let mut parents: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        for (node_id, parent_id) in node_parents_dsl::node_parents
+            .select((node_parents_dsl::node_id, node_parents_dsl::parent_id))
+            .order((node_parents_dsl::node_id, node_parents_dsl::ord))
+            .load::<(String, String)>(conn)
+            .map_err(to_db)?
+        {
+            parents.entry(node_id).or_default().push(parent_id);
+        }
+        let thinned = thinned_parents(conn)?;
+
+        let mut snapshots = SnapshotCache::default();
+        let mut counts = PhraseCounts::new(&store, phrase);
+        let mut hits = Vec::new();
+
+        for node_id in nodes {
+            let node_parents =
+                visible_parents(&parents.remove(&node_id).unwrap_or_default(), &thinned);
+            for id in node_parents.iter().chain([&node_id]) {
+                snapshots.load(conn, id, filter.as_deref())?;
+            }
+
+            let after_map = &snapshots.0[&node_id];
+            let empty = HashMap::new();
+            // 루트 노드는 빈 스냅샷과 비교한다
+            let before_maps = if node_parents.is_empty() {
+                vec![&empty]
+            } else {
+                node_parents.iter().map(|p| &snapshots.0[p]).collect()
+            };
+
+            let paths = before_maps
+                .iter()
+                .flat_map(|m| m.keys())
+                .chain(after_map.keys())
+                .cloned()
+                .collect::<BTreeSet<_>>();
+
+            for path in paths {
+                let after_blob = after_map.get(&path);
+                // 어느 부모와 blob이 같으면 개수도 같다: 내용을 읽지 않는다
+                if before_maps.iter().any(|m| m.get(&path) == after_blob) {
+                    continue;
+                }
+
+                let after_count = counts.get(conn, after_blob)?;
+                let mut before_counts = Vec::with_capacity(before_maps.len());
+                for map in &before_maps {
+                    before_counts.push(counts.get(conn, map.get(&path))?);
+                }
+                if before_counts.contains(&after_count) {
+                    continue;
+                }
+
+                hits.push(PickaxeHit {
+                    node_id: node_id.clone(),
+                    path,
+                    before_count: before_counts[0],
+                    after_count,
+                });
+            }
+
+            // 자식은 보통 먼저 지나갔으므로 이 노드의 스냅샷은 더 쓰이지 않는다
+            snapshots.0.remove(&node_id);
+        }
+
+        Ok(hits)
+    }
+}
+
+pub fn pickaxe(root: &Path, phrase: &str, path_filter: Option<&str>) -> Result<Vec<PickaxeHit>> {
+    Repository::open(root)?.pickaxe(phrase, path_filter)
+}
+
+// 노드별 path -> blob id. 형제 노드들이 같은 부모 스냅샷을 여러 번 읽지 않게 둔다
+#[derive(Default)]
+struct SnapshotCache(HashMap<NodeId, HashMap<String, String>>);
+
+impl SnapshotCache {
+    fn load(
+        &mut self,
+        conn: &mut SqliteConnection,
+        node_id: &str,
+        filter: Option<&str>,
+    ) -> Result<()> {
+        if !self.0.contains_key(node_id) {
+            let mut map = load_blob_id_map(conn, node_id)?;
+            if let Some(filter) = filter {
+                map.retain(|path, _| under(path, filter));
+            }
+            self.0.insert(node_id.to_string(), map);
+        }
+
+        Ok(())
+    }
+}
+
+// blob id -> phrase 개수. 바이너리나 UTF-8이 아닌 blob은 0
+struct PhraseCounts<'a> {
+    store: &'a BlobStore,
+    phrase: &'a str,
+    counts: HashMap<String, usize>,
+}
+
+impl<'a> PhraseCounts<'a> {
+    fn new(store: &'a BlobStore, phrase: &'a str) -> Self {
+        PhraseCounts {
+            store,
+            phrase,
+            counts: HashMap::new(),
+        }
+    }
+
+    // 파일이 없으면(None) 0
+    fn get(&mut self, conn: &mut SqliteConnection, blob_id: Option<&String>) -> Result<usize> {
+        let Some(blob_id) = blob_id else {
+            return Ok(0);
+        };
+        if let Some(count) = self.counts.get(blob_id) {
+            return Ok(*count);
+        }
+
+        let (_, content) = self
+            .store
+            .load(conn, std::slice::from_ref(blob_id))?
+            .pop()
+            .ok_or_else(|| WorkSpaceError::NotFound(format!("missing blob: {}", blob_id)))?;
+        let count =
+            std::str::from_utf8(&content).map_or(0, |text| text.matches(self.phrase).count());
+        self.counts.insert(blob_id.clone(), count);
+
+        Ok(count)
+    }
+}
+
+// path가 filter 파일이거나 filter 디렉터리 아래에 있는지
+fn under(path: &str, filter: &str) -> bool {
+    filter.is_empty()
+        || path == filter
+        || path
+            .strip_prefix(filter)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
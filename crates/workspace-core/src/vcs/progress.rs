@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+
+use crate::{Result, WorkSpaceError};
+
+// 오래 걸리는 작업의 단계
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressPhase {
+    // 작업 트리 파일을 읽고 해시한다 (commit)
+    Scan,
+    // blob을 저장소에 넣는다 (commit, bundle_unbundle)
+    Store,
+    // 대상 스냅샷 파일을 작업 트리에 쓴다 (checkout)
+    Write,
+    // 번들에 담을 blob을 읽는다 (bundle_create)
+    Pack,
+    // 참조 없는 objects 파일을 지운다 (gc)
+    Sweep,
+}
+
+// done/total은 파일(또는 blob) 수, bytes는 지금까지 처리한 내용 크기
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProgressEvent {
+    pub phase: ProgressPhase,
+    pub done: u64,
+    pub total: u64,
+    pub bytes: u64,
+}
+
+// 다른 스레드에서 cancel()을 부르면 진행 중인 작업이 다음 확인 지점에서 Cancelled로 끝난다
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+type ProgressCallback = dyn Fn(&ProgressEvent) + Send + Sync;
+
+// 진행 콜백 + 취소 토큰. 기본값은 아무것도 알리지 않고 취소되지 않는다
+#[derive(Clone, Default)]
+pub struct Progress {
+    callback: Option<Arc<ProgressCallback>>,
+    cancel: CancelToken,
+}
+
+impl Progress {
+    pub fn new(
+        callback: impl Fn(&ProgressEvent) + Send + Sync + 'static,
+        cancel: CancelToken,
+    ) -> Progress {
+        Progress {
+            callback: Some(Arc::new(callback)),
+            cancel,
+        }
+    }
+
+    // 콜백 없이 취소만 받는다
+    pub fn cancellable(cancel: CancelToken) -> Progress {
+        Progress {
+            callback: None,
+            cancel,
+        }
+    }
+
+    pub(crate) fn report(&self, phase: ProgressPhase, done: usize, total: usize, bytes: u64) {
+        if let Some(callback) = &self.callback {
+            callback(&ProgressEvent {
+                phase,
+                done: done as u64,
+                total: total as u64,
+                bytes,
+            });
+        }
+    }
+
+    // 취소됐으면 Cancelled. 트랜잭션 안에서 부르면 그대로 롤백된다
+    pub(crate) fn checkpoint(&self) -> Result<()> {
+        if self.cancel.is_cancelled() {
+            return Err(WorkSpaceError::Cancelled);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use std::path::Path;
+
+use diesel::prelude::*;
+
+use crate::schema::reflog::dsl as reflog_dsl;
+use crate::vcs::commit::now_unix_ms;
+use crate::vcs::crypto::{REFLOG_AAD, open_message, repo_cipher};
+use crate::vcs::db::to_db;
+use crate::vcs::lock::RepoLock;
+use crate::vcs::progress::Progress;
+use crate::vcs::refs::{read_ref, write_ref};
+use crate::vcs::repo::current_head;
+use crate::vcs::repository::Repository;
+use crate::{NodeId, ReflogEntry, Result, WorkSpaceError};
+
+pub(crate) const HEAD_REF: &str = "HEAD";
+
+// HEAD를 옮기고 reflog에 남긴다. 이전 HEAD를 돌려준다
+pub(crate) fn move_head(
+    conn: &mut SqliteConnection,
+    new_head: &str,
+    reason: &str,
+    message: &str,
+) -> Result<Option<NodeId>> {
+    use crate::schema::head::dsl as head_dsl;
+
+    let old_head = current_head(conn)?;
+
+    diesel::update(head_dsl::head)
+        .set(head_dsl::node_id.eq(Some(new_head)))
+        .execute(conn)
+        .map_err(to_db)?;
+
+    record(
+        conn,
+        HEAD_REF,
+        old_head.as_deref(),
+        Some(new_head),
+        reason,
+        message,
+    )?;
+
+    Ok(old_head)
+}
+
+// HEAD를 비운다(아직 아무 노드도 체크아웃하지 않은 상태)
+pub(crate) fn clear_head(conn: &mut SqliteConnection, reason: &str, message: &str) -> Result<()> {
+    use crate::schema::head::dsl as head_dsl;
+
+    let old_head = current_head(conn)?;
+
+    diesel::update(head_dsl::head)
+        .set(head_dsl::node_id.eq(None::<String>))
+        .execute(conn)
+        .map_err(to_db)?;
+
+    record(conn, HEAD_REF, old_head.as_deref(), None, reason, message)
+}
+
+// refs 테이블의 이름 있는 ref(원격 추적 head 등)를 옮기고 reflog에 남긴다
+pub(crate) fn move_ref(
+    conn: &mut SqliteConnection,
+    name: &str,
+    new_node_id: &str,
+    reason: &str,
+    message: &str,
+) -> Result<()> {
+    let old = read_ref(conn, name)?;
+    if old.as_deref() == Some(new_node_id) {
+        return Ok(());
+    }
+
+    write_ref(conn, name, new_node_id)?;
+    record(
+        conn,
+        name,
+        old.as_deref(),
+        Some(new_node_id),
+        reason,
+        message,
+    )
+}
+
+fn record(
+    conn: &mut SqliteConnection,
+    ref_name: &str,
+    old_node_id: Option<&str>,
+    new_node_id: Option<&str>,
+    reason: &str,
+    message: &str,
+) -> Result<()> {
+    diesel::insert_into(reflog_dsl::reflog)
+        .values((
+            reflog_dsl::ref_name.eq(ref_name),
+            reflog_dsl::old_node_id.eq(old_node_id),
+            reflog_dsl::new_node_id.eq(new_node_id),
+            reflog_dsl::reason.eq(reason),
+            reflog_dsl::message.eq(message),
+            reflog_dsl::created_at_unix_ms.eq(now_unix_ms()),
+        ))
+        .execute(conn)
+        .map_err(to_db)?;
+
+    Ok(())
+}
+
+impl Repository {
+    // reflog 조회 API: 최신 이동이 먼저 온다
+    pub fn reflog(&self) -> Result<Vec<ReflogEntry>> {
+        let root = self.root();
+        let conn = &mut *self.conn()?;
+
+        let rows = reflog_dsl::reflog
+            .select((
+                reflog_dsl::id,
+                reflog_dsl::ref_name,
+                reflog_dsl::old_node_id,
+                reflog_dsl::new_node_id,
+                reflog_dsl::reason,
+                reflog_dsl::message,
+                reflog_dsl::created_at_unix_ms,
+            ))
+            .order(reflog_dsl::id.desc())
+            .load::<ReflogEntry>(conn)
+            .map_err(to_db)?;
+
+        let cipher = repo_cipher(root, conn)?;
+        rows.into_iter()
+            .map(|mut entry| {
+                entry.message = open_message(cipher.as_ref(), REFLOG_AAD, entry.message)?;
+                Ok(entry)
+            })
+            .collect()
+    }
+
+    // 마지막 HEAD 이동을 되돌린다: 이동 직전 노드로 checkout 하고 그 자체도 "undo"로 기록한다.
+    // 되돌린 뒤 다시 호출하면 되돌리기 전 위치로 돌아간다.
+    pub fn undo_last_head_move(&self) -> Result<NodeId> {
+        let _lock = RepoLock::acquire(self.root())?;
+        let conn = &mut *self.conn()?;
+
+        let last = reflog_dsl::reflog
+            .filter(reflog_dsl::ref_name.eq(HEAD_REF))
+            .select(reflog_dsl::old_node_id)
+            .order(reflog_dsl::id.desc())
+            .first::<Option<String>>(conn)
+            .optional()
+            .map_err(to_db)?;
+
+        let target = last.flatten().ok_or_else(|| {
+            WorkSpaceError::InvalidInput(
+                "nothing to undo: HEAD has no previous position".to_string(),
+            )
+        })?;
+
+        self.checkout_in(conn, &target, "undo", &Progress::default())?;
+
+        Ok(target)
+    }
+}
+
+pub fn reflog(root: &Path) -> Result<Vec<ReflogEntry>> {
+    Repository::open(root)?.reflog()
+}
+
+pub fn undo_last_head_move(root: &Path) -> Result<NodeId> {
+    Repository::open(root)?.undo_last_head_move()
+}
+
+// reflog 메시지에 쓰는 짧은 id
+pub(crate) fn short_id(id: Option<&str>) -> &str {
+    match id {
+        Some(id) => &id[..id.len().min(12)],
+        None => "(none)",
+    }
+}
@@ -0,0 +1,144 @@
+use std::path::Path;
+
+use diesel::prelude::*;
+
+use crate::schema::refs::dsl as refs_dsl;
+use crate::vcs::db::to_db;
+use crate::vcs::reflog::move_ref;
+use crate::vcs::repository::Repository;
+use crate::vcs::revision::resolve;
+use crate::{NodeId, Result, Tag, WorkSpaceError};
+
+const TAG_PREFIX: &str = "tags/";
+
+// 원격 추적 head는 "remotes/<원격 이름>/HEAD" 이름으로 refs 테이블에 저장한다
+pub(crate) fn remote_tracking_ref(remote: &str) -> String {
+    format!("remotes/{}/HEAD", remote)
+}
+
+pub(crate) fn tag_ref(tag: &str) -> String {
+    format!("{}{}", TAG_PREFIX, tag)
+}
+
+impl Repository {
+    // 태그 생성 API: revision이 가리키는 노드에 이름을 붙인다. 같은 이름이 있으면 실패한다
+    pub fn tag_create(&self, name: &str, revision: &str) -> Result<Tag> {
+        validate_ref_name("tag", name)?;
+
+        let conn = &mut *self.conn()?;
+
+        conn.transaction::<Tag, WorkSpaceError, _>(|tx| {
+            let node_id = resolve(tx, revision)?;
+
+            if read_ref(tx, &tag_ref(name))?.is_some() {
+                return Err(WorkSpaceError::Conflict(format!(
+                    "tag already exists: {}",
+                    name
+                )));
+            }
+
+            move_ref(tx, &tag_ref(name), &node_id, "tag", revision)?;
+
+            Ok(Tag {
+                name: name.to_string(),
+                node_id,
+            })
+        })
+    }
+
+    pub fn tag_delete(&self, name: &str) -> Result<()> {
+        let conn = &mut *self.conn()?;
+
+        let removed = diesel::delete(refs_dsl::refs.filter(refs_dsl::name.eq(tag_ref(name))))
+            .execute(conn)
+            .map_err(to_db)?;
+
+        if removed == 0 {
+            return Err(WorkSpaceError::NotFound(format!("tag not found: {}", name)));
+        }
+
+        Ok(())
+    }
+
+    // 태그 목록 API (이름순)
+    pub fn tags(&self) -> Result<Vec<Tag>> {
+        let conn = &mut *self.conn()?;
+
+        let rows = refs_dsl::refs
+            .select((refs_dsl::name, refs_dsl::node_id))
+            .order(refs_dsl::name.asc())
+            .load::<(String, String)>(conn)
+            .map_err(to_db)?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(name, node_id)| {
+                let name = name.strip_prefix(TAG_PREFIX)?.to_string();
+                Some(Tag { name, node_id })
+            })
+            .collect())
+    }
+}
+
+pub fn tag_create(root: &Path, name: &str, revision: &str) -> Result<Tag> {
+    Repository::open(root)?.tag_create(name, revision)
+}
+
+pub fn tag_delete(root: &Path, name: &str) -> Result<()> {
+    Repository::open(root)?.tag_delete(name)
+}
+
+pub fn tags(root: &Path) -> Result<Vec<Tag>> {
+    Repository::open(root)?.tags()
+}
+
+// 원격/태그 이름 규칙. 리비전 문법(~ ^ @{})이나 경로 구분자와 섞이지 않게 한다
+pub(crate) fn validate_ref_name(kind: &str, name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && name != "."
+        && name != ".."
+        && name != "HEAD";
+
+    if !valid {
+        return Err(WorkSpaceError::InvalidInput(format!(
+            "invalid {} name: {}",
+            kind, name
+        )));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn read_ref(conn: &mut SqliteConnection, name: &str) -> Result<Option<NodeId>> {
+    let node_id = refs_dsl::refs
+        .filter(refs_dsl::name.eq(name))
+        .select(refs_dsl::node_id)
+        .first::<String>(conn)
+        .optional()
+        .map_err(to_db)?;
+
+    Ok(node_id)
+}
+
+pub(crate) fn write_ref(conn: &mut SqliteConnection, name: &str, node_id: &str) -> Result<()> {
+    diesel::insert_into(refs_dsl::refs)
+        .values((refs_dsl::name.eq(name), refs_dsl::node_id.eq(node_id)))
+        .on_conflict(refs_dsl::name)
+        .do_update()
+        .set(refs_dsl::node_id.eq(node_id))
+        .execute(conn)
+        .map_err(to_db)?;
+
+    Ok(())
+}
+
+pub(crate) fn delete_ref(conn: &mut SqliteConnection, name: &str) -> Result<()> {
+    diesel::delete(refs_dsl::refs.filter(refs_dsl::name.eq(name)))
+        .execute(conn)
+        .map_err(to_db)?;
+
+    Ok(())
+}
@@ -0,0 +1,405 @@
+use std::path::{Path, PathBuf};
+
+use diesel::prelude::*;
+use diesel_migrations::MigrationHarness;
+
+use crate::schema::nodes::dsl as nodes_dsl;
+use crate::schema::remotes::dsl as remotes_dsl;
+use crate::vcs::bundle::{ObjectPack, apply_pack, collect_pack};
+use crate::vcs::commit::{changed_paths, collect_snapshot};
+use crate::vcs::crypto::{open_message, repo_cipher, seal_message};
+use crate::vcs::db::{MIGRATIONS, open_existing_connection, to_db, to_migration};
+use crate::vcs::graph::is_reachable;
+use crate::vcs::lock::RepoLock;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::progress::Progress;
+use crate::vcs::reflog::move_ref;
+use crate::vcs::refs::{delete_ref, read_ref, remote_tracking_ref, validate_ref_name};
+use crate::vcs::repo::current_head;
+use crate::vcs::repository::Repository;
+use crate::vcs::snapshot::collect_files_in_workspace;
+use crate::vcs::sync::{HttpRemote, advertise, receive_pack};
+use crate::{
+    BundleRef, FetchSummary, NodeId, PullSummary, PushSummary, ReceiveSummary, Remote, Result,
+    SyncAdvertisement, WorkSpaceError,
+};
+
+impl Repository {
+    // 원격 등록 API: url은 다른 .novel 저장소의 루트 경로(상대 경로면 root 기준)
+    // 또는 동기화 서버 주소(http(s)://host:port/<repo>)
+    pub fn remote_add(&self, name: &str, url: &str) -> Result<()> {
+        validate_ref_name("remote", name)?;
+
+        if url.trim().is_empty() {
+            return Err(WorkSpaceError::InvalidInput("empty remote url".to_string()));
+        }
+
+        let conn = &mut *self.conn()?;
+
+        if find_remote_url(conn, name)?.is_some() {
+            return Err(WorkSpaceError::Conflict(format!(
+                "remote already exists: {}",
+                name
+            )));
+        }
+
+        diesel::insert_into(remotes_dsl::remotes)
+            .values((remotes_dsl::name.eq(name), remotes_dsl::url.eq(url)))
+            .execute(conn)
+            .map_err(to_db)?;
+
+        Ok(())
+    }
+
+    // 원격과 그 원격 추적 head를 함께 지운다
+    pub fn remote_remove(&self, name: &str) -> Result<()> {
+        let conn = &mut *self.conn()?;
+
+        conn.transaction::<(), WorkSpaceError, _>(|tx| {
+            let removed = diesel::delete(remotes_dsl::remotes.filter(remotes_dsl::name.eq(name)))
+                .execute(tx)
+                .map_err(to_db)?;
+
+            if removed == 0 {
+                return Err(remote_not_found(name));
+            }
+
+            delete_ref(tx, &remote_tracking_ref(name))
+        })
+    }
+
+    pub fn remotes(&self) -> Result<Vec<Remote>> {
+        let conn = &mut *self.conn()?;
+
+        let rows = remotes_dsl::remotes
+            .select((remotes_dsl::name, remotes_dsl::url))
+            .order(remotes_dsl::name.asc())
+            .load::<(String, String)>(conn)
+            .map_err(to_db)?;
+
+        let mut out = Vec::with_capacity(rows.len());
+        for (name, url) in rows {
+            let head = read_ref(conn, &remote_tracking_ref(&name))?;
+            out.push(Remote { name, url, head });
+        }
+
+        Ok(out)
+    }
+
+    // HTTP 원격 인증 토큰 설정(None이면 제거). 암호화 저장소면 토큰도 데이터 키로 봉인해 둔다
+    pub fn remote_set_token(&self, name: &str, token: Option<&str>) -> Result<()> {
+        let root = self.root();
+        let conn = &mut *self.conn()?;
+
+        let sealed = match token {
+            Some(token) => Some(seal_message(
+                repo_cipher(root, conn)?.as_ref(),
+                &remote_token_aad(name),
+                token,
+            )),
+            None => None,
+        };
+        let updated = diesel::update(remotes_dsl::remotes.filter(remotes_dsl::name.eq(name)))
+            .set(remotes_dsl::token.eq(sealed))
+            .execute(conn)
+            .map_err(to_db)?;
+
+        if updated == 0 {
+            return Err(remote_not_found(name));
+        }
+
+        Ok(())
+    }
+
+    // 원격 HEAD까지의 히스토리 중 로컬에 없는 노드/blob만 가져오고 원격 추적 head를 갱신한다.
+    // 로컬 HEAD와 작업 트리는 건드리지 않는다.
+    pub fn fetch(&self, remote: &str) -> Result<FetchSummary> {
+        let _lock = RepoLock::acquire(self.root())?;
+        let conn = &mut *self.conn()?;
+
+        self.fetch_in(conn, remote)
+    }
+
+    // fetch 본체. 호출하는 쪽이 저장소 잠금과 연결을 잡고 있다
+    fn fetch_in(&self, conn: &mut SqliteConnection, remote: &str) -> Result<FetchSummary> {
+        let root = self.root();
+
+        let store = BlobStore::open(root, conn)?;
+        let mut transport = Transport::open(conn, root, remote)?;
+        let advertisement = transport.advertise()?;
+
+        let (imported_nodes, imported_blobs) = match &advertisement.head {
+            Some(tip) => {
+                let haves = known_nodes(conn, &advertisement.nodes)?;
+                let pack = transport.upload_pack(tip, &haves)?;
+
+                conn.transaction::<(usize, usize), WorkSpaceError, _>(|tx| {
+                    let imported = apply_pack(tx, &store, &pack, &Progress::default())?;
+                    move_ref(tx, &remote_tracking_ref(remote), tip, "fetch", remote)?;
+                    Ok(imported)
+                })?
+            }
+            None => (0, 0),
+        };
+
+        Ok(FetchSummary {
+            remote: remote.to_string(),
+            head: advertisement.head,
+            imported_nodes,
+            imported_blobs,
+        })
+    }
+
+    // 로컬 HEAD를 원격으로 보내고 원격 HEAD를 옮긴다.
+    // 원격 HEAD가 로컬 HEAD의 조상이 아니면(fast-forward가 아니면) force 없이는 거부한다.
+    // 원격은 보관용 복사본으로 취급하므로 원격 쪽 작업 트리는 갱신하지 않는다. 그래서 로컬 경로 원격의
+    // 작업 트리에 파일이 있으면(누가 그 저장소에서 작업 중이면) HEAD만 옮겨 어긋나게 하지 않고 Conflict로 거부한다.
+    pub fn push(&self, remote: &str, force: bool) -> Result<PushSummary> {
+        let root = self.root();
+        let conn = &mut *self.conn()?;
+
+        let local_head = current_head(conn)?.ok_or_else(|| {
+            WorkSpaceError::InvalidInput("nothing to push: repository has no commits".to_string())
+        })?;
+
+        let mut transport = Transport::open(conn, root, remote)?;
+        let advertisement = transport.advertise()?;
+
+        let haves = known_nodes(conn, &advertisement.nodes)?;
+        let store = BlobStore::open(root, conn)?;
+        let mut pack = collect_pack(
+            conn,
+            &store,
+            std::slice::from_ref(&local_head),
+            &haves,
+            &Progress::default(),
+        )?;
+        pack.refs = vec![BundleRef {
+            name: "HEAD".to_string(),
+            node_id: local_head.clone(),
+        }];
+
+        let received = transport.receive_pack(&pack, force)?;
+
+        move_ref(
+            conn,
+            &remote_tracking_ref(remote),
+            &local_head,
+            "push",
+            remote,
+        )?;
+
+        Ok(PushSummary {
+            remote: remote.to_string(),
+            old_head: received.old_head,
+            new_head: received.new_head,
+            pushed_nodes: received.imported_nodes,
+            pushed_blobs: received.imported_blobs,
+            forced: received.forced,
+        })
+    }
+
+    // fetch 후 로컬 HEAD가 원격 HEAD의 조상이면 원격 HEAD로 checkout 한다(fast-forward만 지원).
+    // checkout이 작업 트리를 덮어쓰므로 커밋하지 않은 변경이 있으면 fetch까지만 하고 거부한다
+    pub fn pull(&self, remote: &str) -> Result<PullSummary> {
+        let root = self.root();
+        // fetch부터 checkout까지 한 잠금 안에서 한다: 그 사이 HEAD나 작업 트리가 바뀌지 않는다
+        let _lock = RepoLock::acquire(root)?;
+        let conn = &mut *self.conn()?;
+
+        let fetched = self.fetch_in(conn, remote)?;
+        let old_head = current_head(conn)?;
+
+        let new_head = match (&old_head, &fetched.head) {
+            (_, None) => old_head.clone(),
+            (None, Some(remote_head)) => {
+                ensure_clean(root, conn, None)?;
+                self.checkout_in(conn, remote_head, "pull", &Progress::default())?;
+                Some(remote_head.clone())
+            }
+            (Some(local), Some(remote_head)) => {
+                if is_reachable(conn, local, remote_head)? {
+                    // 이미 최신이거나 로컬이 더 앞서 있다
+                    old_head.clone()
+                } else if is_reachable(conn, remote_head, local)? {
+                    ensure_clean(root, conn, Some(local))?;
+                    self.checkout_in(conn, remote_head, "pull", &Progress::default())?;
+                    Some(remote_head.clone())
+                } else {
+                    return Err(WorkSpaceError::NonFastForward(format!(
+                        "pull from '{}' rejected: local head {} and remote head {} have diverged",
+                        remote, local, remote_head
+                    )));
+                }
+            }
+        };
+
+        Ok(PullSummary {
+            remote: remote.to_string(),
+            old_head,
+            new_head,
+            imported_nodes: fetched.imported_nodes,
+            imported_blobs: fetched.imported_blobs,
+        })
+    }
+}
+
+pub fn remote_add(root: &Path, name: &str, url: &str) -> Result<()> {
+    Repository::open(root)?.remote_add(name, url)
+}
+
+pub fn remote_remove(root: &Path, name: &str) -> Result<()> {
+    Repository::open(root)?.remote_remove(name)
+}
+
+pub fn remotes(root: &Path) -> Result<Vec<Remote>> {
+    Repository::open(root)?.remotes()
+}
+
+pub fn remote_set_token(root: &Path, name: &str, token: Option<&str>) -> Result<()> {
+    Repository::open(root)?.remote_set_token(name, token)
+}
+
+pub fn fetch(root: &Path, remote: &str) -> Result<FetchSummary> {
+    Repository::open(root)?.fetch(remote)
+}
+
+pub fn push(root: &Path, remote: &str, force: bool) -> Result<PushSummary> {
+    Repository::open(root)?.push(remote, force)
+}
+
+pub fn pull(root: &Path, remote: &str) -> Result<PullSummary> {
+    Repository::open(root)?.pull(remote)
+}
+
+fn ensure_clean(root: &Path, conn: &mut SqliteConnection, head: Option<&str>) -> Result<()> {
+    let snapshot = collect_snapshot(root, &Progress::default())?;
+    let changed = changed_paths(conn, head, &snapshot)?;
+    if !changed.is_empty() {
+        return Err(WorkSpaceError::DirtyWorkingTree(changed));
+    }
+
+    Ok(())
+}
+
+// 원격 종류별 전송 방식. 로컬 경로는 DB에 직접 붙고, http(s)://는 동기화 서버와 통신한다.
+enum Transport {
+    Local {
+        root: PathBuf,
+        conn: SqliteConnection,
+    },
+    Http(HttpRemote),
+}
+
+impl Transport {
+    fn open(conn: &mut SqliteConnection, root: &Path, name: &str) -> Result<Self> {
+        let (url, token) = remotes_dsl::remotes
+            .filter(remotes_dsl::name.eq(name))
+            .select((remotes_dsl::url, remotes_dsl::token))
+            .first::<(String, Option<String>)>(conn)
+            .optional()
+            .map_err(to_db)?
+            .ok_or_else(|| remote_not_found(name))?;
+
+        if url.starts_with("http://") || url.starts_with("https://") {
+            let token = match token {
+                Some(token) => Some(open_message(
+                    repo_cipher(root, conn)?.as_ref(),
+                    &remote_token_aad(name),
+                    token,
+                )?),
+                None => None,
+            };
+            return Ok(Transport::Http(HttpRemote::new(&url, token)));
+        }
+
+        let path = PathBuf::from(url);
+        let remote_root = if path.is_absolute() {
+            path
+        } else {
+            root.join(path)
+        };
+
+        let mut remote_conn = open_existing_connection(&remote_root)?;
+        remote_conn
+            .run_pending_migrations(MIGRATIONS)
+            .map_err(to_migration)?;
+
+        Ok(Transport::Local {
+            root: remote_root,
+            conn: remote_conn,
+        })
+    }
+
+    fn advertise(&mut self) -> Result<SyncAdvertisement> {
+        match self {
+            Transport::Local { conn, .. } => advertise(conn),
+            Transport::Http(remote) => remote.advertise(),
+        }
+    }
+
+    fn upload_pack(&mut self, want: &str, haves: &[NodeId]) -> Result<ObjectPack> {
+        match self {
+            Transport::Local { root, conn } => {
+                let store = BlobStore::open(root, conn)?;
+                collect_pack(
+                    conn,
+                    &store,
+                    &[want.to_string()],
+                    haves,
+                    &Progress::default(),
+                )
+            }
+            Transport::Http(remote) => remote.upload_pack(want, haves),
+        }
+    }
+
+    fn receive_pack(&mut self, pack: &ObjectPack, force: bool) -> Result<ReceiveSummary> {
+        match self {
+            Transport::Local { root, conn } => {
+                // 원격 저장소의 HEAD를 옮기므로 원격 쪽 잠금을 잡는다
+                let _lock = RepoLock::acquire(root)?;
+                if !collect_files_in_workspace(root)?.is_empty() {
+                    return Err(WorkSpaceError::Conflict(format!(
+                        "remote {} has a checked-out working tree; pull from it there instead of pushing",
+                        root.display()
+                    )));
+                }
+                let store = BlobStore::open(root, conn)?;
+                receive_pack(conn, &store, pack, force)
+            }
+            Transport::Http(remote) => remote.receive_pack(pack, force),
+        }
+    }
+}
+
+// 상대가 광고한 노드 중 이쪽에도 있는 것. 있는 노드는 조상도 모두 있으므로 pack의 base로 쓴다.
+fn known_nodes(conn: &mut SqliteConnection, advertised: &[NodeId]) -> Result<Vec<NodeId>> {
+    let known = nodes_dsl::nodes
+        .filter(nodes_dsl::id.eq_any(advertised))
+        .select(nodes_dsl::id)
+        .load::<String>(conn)
+        .map_err(to_db)?;
+
+    Ok(known)
+}
+
+fn find_remote_url(conn: &mut SqliteConnection, name: &str) -> Result<Option<String>> {
+    let url = remotes_dsl::remotes
+        .filter(remotes_dsl::name.eq(name))
+        .select(remotes_dsl::url)
+        .first::<String>(conn)
+        .optional()
+        .map_err(to_db)?;
+
+    Ok(url)
+}
+
+// 노드 메시지/메모와 자리를 바꿔 쓸 수 없게 구분한다
+pub(crate) fn remote_token_aad(name: &str) -> Vec<u8> {
+    format!("remote token {}", name).into_bytes()
+}
+
+fn remote_not_found(name: &str) -> WorkSpaceError {
+    WorkSpaceError::NotFound(format!("remote not found: {}", name))
+}
@@ -0,0 +1,59 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use diesel::SqliteConnection;
+use diesel_migrations::MigrationHarness;
+
+use crate::Result;
+use crate::vcs::db::{MIGRATIONS, open_connection, open_existing_connection, to_migration};
+
+// 한 저장소에 대한 핸들. 열 때 한 번만 마이그레이션하고 연결을 들고 있는다.
+// 같은 루트의 자유 함수(commit(root, ..) 등)는 매번 Repository::open을 거친다
+pub struct Repository {
+    root: PathBuf,
+    conn: Mutex<SqliteConnection>,
+}
+
+impl Repository {
+    // .novel이 없으면 만든다(init_repo 전에도 열 수 있다)
+    pub fn open(root: &Path) -> Result<Repository> {
+        let conn = open_connection(root)?;
+        Repository::with_connection(root, conn)
+    }
+
+    // 이미 초기화된 저장소만 연다(sync_serve처럼 새로 만들면 안 되는 경우)
+    pub fn open_existing(root: &Path) -> Result<Repository> {
+        let conn = open_existing_connection(root)?;
+        Repository::with_connection(root, conn)
+    }
+
+    fn with_connection(root: &Path, mut conn: SqliteConnection) -> Result<Repository> {
+        conn.run_pending_migrations(MIGRATIONS)
+            .map_err(to_migration)?;
+
+        Ok(Repository {
+            root: root.canonicalize()?,
+            conn: Mutex::new(conn),
+        })
+    }
+
+    // canonical 루트 경로
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // 다른 스레드가 쓰는 중이면 끝날 때까지 기다린다. 메서드 안에서 다시 부르면 멈추므로
+    // 다른 작업을 부를 때는 빌린 연결을 넘긴다(checkout_in, fetch_in ...). 마이그레이션은 open에서 끝났다
+    pub(crate) fn conn(&self) -> Result<MutexGuard<'_, SqliteConnection>> {
+        match self.conn.lock() {
+            Ok(guard) => Ok(guard),
+            // 작업 중 panic이 났으면 트랜잭션 상태를 믿을 수 없으므로 연결을 바꾼다
+            Err(poisoned) => {
+                let mut guard = poisoned.into_inner();
+                *guard = open_connection(&self.root)?;
+                self.conn.clear_poison();
+                Ok(guard)
+            }
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use std::path::Path;
+
+use diesel::prelude::*;
+
+use crate::schema::node_parents::dsl as node_parents_dsl;
+use crate::schema::nodes::dsl as nodes_dsl;
+use crate::schema::reflog::dsl as reflog_dsl;
+use crate::vcs::commit::now_unix_ms;
+use crate::vcs::db::to_db;
+use crate::vcs::reflog::HEAD_REF;
+use crate::vcs::refs::{read_ref, remote_tracking_ref, tag_ref};
+use crate::vcs::repo::current_head;
+use crate::vcs::repository::Repository;
+use crate::{NodeId, Result, WorkSpaceError};
+
+// 노드 id 접두사로 인정하는 최소 길이
+const MIN_PREFIX_LEN: usize = 4;
+// 모호한 접두사 에러에 보여 줄 후보 수
+const MAX_CANDIDATES: i64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    // ~N: 첫 번째 부모를 N번 따라간다
+    Ancestor(usize),
+    // ^N: N번째 부모 (^0은 자기 자신)
+    Parent(usize),
+}
+
+impl Repository {
+    // 리비전 해석 API.
+    //
+    // <이름>[@{<날짜>}][~N|^N]...
+    //   이름: HEAD(@), 태그, refs 이름(remotes/<원격>/HEAD), 원격 이름, 노드 id 또는 고유한 접두사
+    //   @{날짜}: 그 시점에 ref가 가리키던 노드 (YYYY-MM-DD[ HH:MM[:SS]] UTC, "N days ago", yesterday)
+    //   @{N}: reflog의 N번째 기록이 가리키던 노드 (@{0}은 현재)
+    pub fn resolve_revision(&self, revision: &str) -> Result<NodeId> {
+        let conn = &mut *self.conn()?;
+
+        resolve(conn, revision)
+    }
+}
+
+pub fn resolve_revision(root: &Path, revision: &str) -> Result<NodeId> {
+    Repository::open(root)?.resolve_revision(revision)
+}
+
+pub(crate) fn resolve(conn: &mut SqliteConnection, revision: &str) -> Result<NodeId> {
+    let (name, date, steps) = parse_revision(revision)?;
+
+    let (ref_name, mut node_id) = resolve_name(conn, name, revision)?;
+
+    if let Some(date) = date {
+        let Some(ref_name) = ref_name else {
+            return Err(invalid_revision(revision, "@{...} needs a ref name"));
+        };
+        // 숫자만 있으면 reflog 순번이다 (reflog 출력의 HEAD@{n}과 같은 번호)
+        let at_node = if !date.is_empty() && date.chars().all(|c| c.is_ascii_digit()) {
+            let n = date
+                .parse::<i64>()
+                .map_err(|_| invalid_revision(revision, "reflog index is too large"))?;
+            if n == 0 {
+                Some(node_id)
+            } else {
+                ref_nth(conn, &ref_name, n)?
+            }
+        } else {
+            let at = parse_date(date, now_unix_ms())
+                .ok_or_else(|| invalid_revision(revision, "unknown date format"))?;
+            ref_at(conn, &ref_name, &node_id, at)?
+        };
+        node_id = at_node.ok_or_else(|| unknown_revision(revision))?;
+
+        // reflog는 지워진 노드(thin_autosaves가 지운 가지 등)를 가리키고 있을 수 있다
+        let present = nodes_dsl::nodes
+            .filter(nodes_dsl::id.eq(&node_id))
+            .count()
+            .get_result::<i64>(conn)
+            .map_err(to_db)?;
+        if present == 0 {
+            return Err(WorkSpaceError::NodeNotFound(node_id));
+        }
+    }
+
+    for step in steps {
+        node_id = match step {
+            Step::Ancestor(n) => {
+                let mut current = node_id;
+                for _ in 0..n {
+                    current =
+                        parent(conn, &current, 1)?.ok_or_else(|| unknown_revision(revision))?;
+                }
+                current
+            }
+            Step::Parent(0) => node_id,
+            Step::Parent(n) => {
+                parent(conn, &node_id, n)?.ok_or_else(|| unknown_revision(revision))?
+            }
+        };
+    }
+
+    Ok(node_id)
+}
+
+// (이름, @{} 안의 날짜, ~/^ 단계들)로 나눈다
+fn parse_revision(revision: &str) -> Result<(&str, Option<&str>, Vec<Step>)> {
+    // "@" 혼자는 HEAD의 별칭이므로 "@{"에서만 이름이 끝난다
+    let name_end = revision
+        .char_indices()
+        .find(|&(i, c)| matches!(c, '~' | '^') || revision[i..].starts_with("@{"))
+        .map_or(revision.len(), |(i, _)| i);
+    let (name, mut rest) = revision.split_at(name_end);
+
+    let mut date = None;
+    if let Some(after) = rest.strip_prefix("@{") {
+        let close = after
+            .find('}')
+            .ok_or_else(|| invalid_revision(revision, "missing '}'"))?;
+        date = Some(&after[..close]);
+        rest = &after[close + 1..];
+    }
+
+    let mut steps = Vec::new();
+    let mut chars = rest.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        let mut digits = String::new();
+        while let Some((_, d)) = chars.peek().filter(|(_, d)| d.is_ascii_digit()) {
+            digits.push(*d);
+            chars.next();
+        }
+        let n = if digits.is_empty() {
+            1
+        } else {
+            digits
+                .parse::<usize>()
+                .map_err(|_| invalid_revision(revision, "number too large"))?
+        };
+
+        steps.push(match c {
+            '~' => Step::Ancestor(n),
+            '^' => Step::Parent(n),
+            _ => return Err(invalid_revision(revision, "expected '~' or '^'")),
+        });
+    }
+
+    Ok((name, date, steps))
+}
+
+// 이름 -> (reflog를 찾을 ref 이름, 노드 id)
+fn resolve_name(
+    conn: &mut SqliteConnection,
+    name: &str,
+    revision: &str,
+) -> Result<(Option<String>, NodeId)> {
+    if name.is_empty() || name == "@" || name == HEAD_REF {
+        let head = current_head(conn)?.ok_or_else(|| unknown_revision(revision))?;
+        return Ok((Some(HEAD_REF.to_string()), head));
+    }
+
+    for ref_name in [name.to_string(), tag_ref(name), remote_tracking_ref(name)] {
+        if let Some(node_id) = read_ref(conn, &ref_name)? {
+            return Ok((Some(ref_name), node_id));
+        }
+    }
+
+    if name.len() >= MIN_PREFIX_LEN && name.chars().all(|c| c.is_ascii_hexdigit()) {
+        // 16진수만 있으므로 LIKE 와일드카드가 섞일 수 없다
+        let candidates = nodes_dsl::nodes
+            .filter(nodes_dsl::id.like(format!("{}%", name.to_ascii_lowercase())))
+            .select(nodes_dsl::id)
+            .order(nodes_dsl::id.asc())
+            .limit(MAX_CANDIDATES)
+            .load::<String>(conn)
+            .map_err(to_db)?;
+
+        match candidates.len() {
+            0 => {}
+            1 => return Ok((None, candidates.into_iter().next().unwrap())),
+            _ => {
+                return Err(WorkSpaceError::AmbiguousRevision {
+                    revision: revision.to_string(),
+                    candidates,
+                });
+            }
+        }
+    }
+
+    Err(unknown_revision(revision))
+}
+
+// reflog에서 n번째(0이 최신) 기록이 옮겨 간 노드
+fn ref_nth(conn: &mut SqliteConnection, ref_name: &str, n: i64) -> Result<Option<NodeId>> {
+    let logged = reflog_dsl::reflog
+        .filter(reflog_dsl::ref_name.eq(ref_name))
+        .select(reflog_dsl::new_node_id)
+        .order(reflog_dsl::id.desc())
+        .offset(n)
+        .first::<Option<String>>(conn)
+        .optional()
+        .map_err(to_db)?;
+
+    Ok(logged.flatten())
+}
+
+// at 시각에 ref가 가리키던 노드. reflog에 기록이 있으면 그것을 쓰고,
+// 기록이 없으면(reflog 이전에 만든 저장소 등) 현재 노드에서 첫 번째 부모를 따라 그 시각 이전 노드를 찾는다
+fn ref_at(
+    conn: &mut SqliteConnection,
+    ref_name: &str,
+    current: &str,
+    at: i64,
+) -> Result<Option<NodeId>> {
+    let logged = reflog_dsl::reflog
+        .filter(reflog_dsl::ref_name.eq(ref_name))
+        .filter(reflog_dsl::created_at_unix_ms.le(at))
+        .select(reflog_dsl::new_node_id)
+        .order(reflog_dsl::id.desc())
+        .first::<Option<String>>(conn)
+        .optional()
+        .map_err(to_db)?;
+
+    if let Some(node_id) = logged {
+        return Ok(node_id);
+    }
+
+    let mut node_id = current.to_string();
+    loop {
+        let created_at = nodes_dsl::nodes
+            .filter(nodes_dsl::id.eq(&node_id))
+            .select(nodes_dsl::created_at_unix_ms)
+            .first::<i64>(conn)
+            .map_err(to_db)?;
+        if created_at <= at {
+            return Ok(Some(node_id));
+        }
+
+        match parent(conn, &node_id, 1)? {
+            Some(parent) => node_id = parent,
+            None => return Ok(None),
+        }
+    }
+}
+
+// n번째(1부터) 부모
+fn parent(conn: &mut SqliteConnection, node_id: &str, n: usize) -> Result<Option<NodeId>> {
+    let parent = node_parents_dsl::node_parents
+        .filter(node_parents_dsl::node_id.eq(node_id))
+        .filter(node_parents_dsl::ord.eq((n - 1) as i32))
+        .select(node_parents_dsl::parent_id)
+        .first::<String>(conn)
+        .optional()
+        .map_err(to_db)?;
+
+    Ok(parent)
+}
+
+// 날짜 -> Unix ms. 절대 날짜는 UTC로 본다
+fn parse_date(text: &str, now_ms: i64) -> Option<i64> {
+    const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+    let text = text.trim();
+    match text {
+        "now" => return Some(now_ms),
+        "yesterday" => return Some(now_ms - DAY_MS),
+        _ => {}
+    }
+
+    // "3 days ago", "3.days.ago"
+    let words = text.split([' ', '.']).collect::<Vec<_>>();
+    if let [count, unit, "ago"] = words.as_slice() {
+        let count = count.parse::<i64>().ok()?;
+        let unit_ms = match unit.trim_end_matches('s') {
+            "second" => 1000,
+            "minute" => 60 * 1000,
+            "hour" => 60 * 60 * 1000,
+            "day" => DAY_MS,
+            "week" => 7 * DAY_MS,
+            _ => return None,
+        };
+        return Some(now_ms - count.checked_mul(unit_ms)?);
+    }
+
+    // "2026-03-01", "2026-03-01 14:30", "2026-03-01T14:30:15"
+    let (date, time) = match text.split_once([' ', 'T']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+
+    let mut ymd = date.split('-').map(|p| p.parse::<i64>().ok());
+    let (Some(Some(year)), Some(Some(month)), Some(Some(day)), None) =
+        (ymd.next(), ymd.next(), ymd.next(), ymd.next())
+    else {
+        return None;
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let mut seconds = 0;
+    if let Some(time) = time {
+        let parts = time
+            .split(':')
+            .map(|p| p.parse::<i64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        let (hour, minute, second) = match parts.as_slice() {
+            [h, m] => (*h, *m, 0),
+            [h, m, s] => (*h, *m, *s),
+            _ => return None,
+        };
+        if !(0..24).contains(&hour) || !(0..60).contains(&minute) || !(0..60).contains(&second) {
+            return None;
+        }
+        seconds = hour * 3600 + minute * 60 + second;
+    }
+
+    Some((days_from_civil(year, month, day) * 86_400 + seconds) * 1000)
+}
+
+// 그레고리력 날짜 -> 1970-01-01 기준 일수 (Howard Hinnant의 days_from_civil)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn unknown_revision(revision: &str) -> WorkSpaceError {
+    WorkSpaceError::NodeNotFound(revision.to_string())
+}
+
+fn invalid_revision(revision: &str, reason: &str) -> WorkSpaceError {
+    WorkSpaceError::InvalidRevision {
+        revision: revision.to_string(),
+        reason: reason.to_string(),
+    }
+}
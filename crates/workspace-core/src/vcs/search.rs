@@ -0,0 +1,235 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Text};
+
+use crate::schema::blobs::dsl as blobs_dsl;
+use crate::schema::node_files::dsl as node_files_dsl;
+use crate::schema::nodes::dsl as nodes_dsl;
+use crate::vcs::crypto::is_encrypted_conn;
+use crate::vcs::db::to_db;
+use crate::vcs::lock::RepoLock;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::repository::Repository;
+use crate::{NodeId, Result, SearchHit, WorkSpaceError};
+
+// 한 번에 돌려주는 최대 결과 수
+const SEARCH_LIMIT: usize = 100;
+// 스니펫에서 일치한 부분 앞뒤로 보여 줄 글자 수
+const SNIPPET_CONTEXT_CHARS: i32 = 40;
+
+// 일치한 blob과 처음 일치한 곳 주변(excerpt). 위치와 길이는 글자 단위, pos는 1부터
+#[derive(QueryableByName)]
+struct SearchRow {
+    #[diesel(sql_type = Text)]
+    blob_id: String,
+    #[diesel(sql_type = Text)]
+    excerpt: String,
+    #[diesel(sql_type = Integer)]
+    pos: i32,
+    #[diesel(sql_type = Integer)]
+    len: i32,
+}
+
+impl Repository {
+    // 모든 버전의 텍스트 파일에서 query를 부분 문자열로 찾는다(ASCII는 대소문자 무시).
+    // 결과는 같은 내용이 같은 경로로 들어 있는 노드끼리 묶고, 가장 최근 노드가 새로운 것부터 온다
+    pub fn search_history(&self, query: &str) -> Result<Vec<SearchHit>> {
+        let root = self.root();
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(WorkSpaceError::InvalidInput(
+                "empty search query".to_string(),
+            ));
+        }
+
+        let conn = &mut *self.conn()?;
+
+        if is_encrypted_conn(conn)? {
+            return Err(WorkSpaceError::InvalidInput(
+                "search is not available for encrypted repositories".to_string(),
+            ));
+        }
+
+        let store = BlobStore::open(root, conn)?;
+        index_external_blobs(root, conn, &store)?;
+
+        // trigram 색인은 3글자 이상인 LIKE 패턴에 쓰인다. 더 짧으면 색인 내용을 훑는다.
+        // 그 blob을 쓰는 가장 최근 노드 순으로 SEARCH_LIMIT개만 고르고, 내용 대신 스니펫 부분만 읽는다.
+        // 결과 한 개는 (blob, 경로)이고 그 시각은 blob의 최근 시각을 넘지 않으므로 위쪽 결과는 빠지지 않는다.
+        // lower/instr은 ASCII만 대소문자를 무시하므로 LIKE와 같은 곳을 찾는다
+        let rows = diesel::sql_query(
+            "SELECT blob_id, pos, len, \
+                 substr(content, max(1, pos - ?3), min(pos - 1, ?3) + length(?1) + ?3) AS excerpt \
+             FROM (SELECT s.blob_id, s.content, \
+                       instr(lower(s.content), lower(?1)) AS pos, length(s.content) AS len, \
+                       (SELECT max(n.created_at_unix_ms) FROM node_files f \
+                        JOIN nodes n ON n.id = f.node_id WHERE f.blob_id = s.blob_id) AS latest \
+                   FROM blob_search s WHERE s.content LIKE ?2 ESCAPE '\\') \
+             WHERE latest IS NOT NULL \
+             ORDER BY latest DESC, blob_id \
+             LIMIT ?4",
+        )
+        .bind::<Text, _>(query)
+        .bind::<Text, _>(like_pattern(query))
+        .bind::<Integer, _>(SNIPPET_CONTEXT_CHARS)
+        .bind::<BigInt, _>(SEARCH_LIMIT as i64)
+        .load::<SearchRow>(conn)
+        .map_err(to_db)?;
+
+        // blob별 경로별 (노드, 시각)
+        let mut by_blob: HashMap<String, BTreeMap<String, Vec<(NodeId, i64)>>> = HashMap::new();
+        for (blob_id, path, node_id, created_at) in node_files_dsl::node_files
+            .inner_join(nodes_dsl::nodes)
+            .filter(node_files_dsl::blob_id.eq_any(rows.iter().map(|r| &r.blob_id)))
+            .select((
+                node_files_dsl::blob_id,
+                node_files_dsl::path,
+                node_files_dsl::node_id,
+                nodes_dsl::created_at_unix_ms,
+            ))
+            .load::<(String, String, String, i64)>(conn)
+            .map_err(to_db)?
+        {
+            by_blob
+                .entry(blob_id)
+                .or_default()
+                .entry(path)
+                .or_default()
+                .push((node_id, created_at));
+        }
+
+        let mut hits = Vec::new();
+        for row in rows {
+            let snippet = snippet(&row, query);
+
+            for (path, mut nodes) in by_blob.remove(&row.blob_id).unwrap_or_default() {
+                nodes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.0.cmp(&a.0)));
+                hits.push((
+                    nodes[0].1,
+                    SearchHit {
+                        path,
+                        blob_id: row.blob_id.clone(),
+                        snippet: snippet.clone(),
+                        nodes: nodes.into_iter().map(|(id, _)| id).collect(),
+                    },
+                ));
+            }
+        }
+
+        hits.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.path.cmp(&b.1.path)));
+        hits.truncate(SEARCH_LIMIT);
+
+        Ok(hits.into_iter().map(|(_, hit)| hit).collect())
+    }
+}
+
+pub fn search_history(root: &Path, query: &str) -> Result<Vec<SearchHit>> {
+    Repository::open(root)?.search_history(query)
+}
+
+// 새로 저장하는 텍스트 blob을 색인에 넣는다. BlobStore::put이 부른다
+pub(crate) fn index_blob(conn: &mut SqliteConnection, id: &str, text: &str) -> Result<()> {
+    diesel::sql_query("INSERT INTO blob_search (blob_id, content) VALUES (?, ?)")
+        .bind::<Text, _>(id)
+        .bind::<Text, _>(text)
+        .execute(conn)
+        .map_err(to_db)?;
+
+    Ok(())
+}
+
+// 지운 blob의 색인을 지운다(gc)
+pub(crate) fn prune_index(conn: &mut SqliteConnection) -> Result<()> {
+    diesel::sql_query("DELETE FROM blob_search WHERE blob_id NOT IN (SELECT id FROM blobs)")
+        .execute(conn)
+        .map_err(to_db)?;
+
+    Ok(())
+}
+
+// 암호화를 켜면 평문 색인을 남기지 않는다
+pub(crate) fn clear_index(conn: &mut SqliteConnection) -> Result<()> {
+    diesel::sql_query("DELETE FROM blob_search")
+        .execute(conn)
+        .map_err(to_db)?;
+
+    Ok(())
+}
+
+// 마이그레이션이 채우지 못한 objects 파일 blob을 색인한다.
+// 통계가 없으면 먼저 채워서, 바이너리로 밝혀진 blob은 다음부터 다시 읽지 않는다.
+// 색인과 통계를 쓰므로 저장소 잠금을 잡고, 잡은 뒤 다시 골라 다른 쓰기와 겹쳐 두 번 색인하지 않는다
+fn index_external_blobs(root: &Path, conn: &mut SqliteConnection, store: &BlobStore) -> Result<()> {
+    if pending_external_blobs(conn)?.is_empty() {
+        return Ok(());
+    }
+
+    let _lock = RepoLock::acquire(root)?;
+    let pending = pending_external_blobs(conn)?;
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let texts = store
+        .stats(conn, &pending)?
+        .into_iter()
+        .filter(|(_, stats)| stats.words.is_some())
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+
+    for (id, content) in store.load(conn, &texts)? {
+        // 통계가 텍스트라고 했으므로 UTF-8이다
+        index_blob(conn, &id, &String::from_utf8_lossy(&content))?;
+    }
+
+    Ok(())
+}
+
+// 아직 색인하지 않은 objects 파일 blob 중 텍스트이거나 통계가 없는 것
+fn pending_external_blobs(conn: &mut SqliteConnection) -> Result<Vec<String>> {
+    blobs_dsl::blobs
+        .filter(blobs_dsl::external.eq(true))
+        .filter(
+            blobs_dsl::word_count
+                .is_not_null()
+                .or(blobs_dsl::size.is_null()),
+        )
+        .filter(diesel::dsl::sql::<diesel::sql_types::Bool>(
+            "id NOT IN (SELECT blob_id FROM blob_search)",
+        ))
+        .select(blobs_dsl::id)
+        .load::<String>(conn)
+        .map_err(to_db)
+}
+
+// LIKE 특수 문자를 글자 그대로 찾게 이스케이프한다
+fn like_pattern(query: &str) -> String {
+    let mut pattern = String::with_capacity(query.len() + 2);
+    pattern.push('%');
+    for c in query.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+// 처음 일치한 곳 앞뒤 SNIPPET_CONTEXT_CHARS 글자. 줄바꿈은 공백으로 바꾼다
+fn snippet(row: &SearchRow, query: &str) -> String {
+    let start = (row.pos - SNIPPET_CONTEXT_CHARS).max(1);
+    let end = row.pos + query.chars().count() as i32 + SNIPPET_CONTEXT_CHARS;
+
+    let mut out = String::new();
+    if start > 1 {
+        out.push('…');
+    }
+    out.push_str(&row.excerpt.replace(['\r', '\n'], " "));
+    if end <= row.len {
+        out.push('…');
+    }
+    out
+}
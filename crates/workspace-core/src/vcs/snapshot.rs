@@ -1,4 +1,4 @@
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 use crate::{Result, WorkSpaceError};
 
@@ -17,7 +17,12 @@ pub(crate) fn blob_id_for_content(content: &[u8]) -> String {
     hex::encode(hasher.finalize())
 }
 
-fn collect_files_recursive(root: &Path, dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+// dir 아래 보통 파일의 root 기준 상대 경로. 심볼릭 링크와 .novel 디렉토리는 건너뛴다
+pub(crate) fn collect_files_recursive(
+    root: &Path,
+    dir: &Path,
+    out: &mut Vec<PathBuf>,
+) -> Result<()> {
     for entry in std::fs::read_dir(dir)? {
         let entry = entry?;
 
@@ -51,6 +56,26 @@ pub(crate) fn collect_files_in_workspace(root: &Path) -> Result<Vec<PathBuf>> {
     Ok(out)
 }
 
+// 첫 조각이 .novel인 경로. 대소문자를 가리지 않는 파일 시스템이 있으므로 대소문자 없이 비교한다
+pub(crate) fn is_meta_path(path: &str) -> bool {
+    path.split('/')
+        .next()
+        .is_some_and(|first| first.eq_ignore_ascii_case(".novel"))
+}
+
 pub(crate) fn normalize_rel_path(p: &Path) -> String {
     p.to_string_lossy().replace("\\", "/")
 }
+
+// 저장소 안에 남는 상대 경로인지: '/'로 나눈 조각이 모두 보통 이름이어야 하고, .novel(훅, DB) 아래가 아니어야 한다.
+// 번들/동기화로 들어온 경로는 이걸 통과해야 작업 트리에 쓴다
+pub(crate) fn is_safe_rel_path(path: &str) -> bool {
+    !is_meta_path(path)
+        && !path.contains('\\')
+        && path
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != "..")
+        && Path::new(path)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+}
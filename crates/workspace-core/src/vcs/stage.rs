@@ -0,0 +1,368 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Component, Path};
+
+use diesel::prelude::*;
+
+use crate::schema::node_files::dsl as node_files_dsl;
+use crate::schema::staged_files::dsl as staged_dsl;
+use crate::vcs::commit::{Snapshot, changed_paths, write_node};
+use crate::vcs::db::to_db;
+use crate::vcs::diff::load_blob_id_map;
+use crate::vcs::hooks::{PRE_SNAPSHOT, run_required_hook};
+use crate::vcs::lock::RepoLock;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::progress::Progress;
+use crate::vcs::repo::current_head;
+use crate::vcs::repository::Repository;
+use crate::vcs::snapshot::{
+    SnapshotFile, blob_id_for_content, collect_files_recursive, is_meta_path, normalize_rel_path,
+};
+use crate::vcs::tree::tree_id_for_entries;
+use crate::{DiffKind, NodeId, NodeKind, Result, StagedFile, WorkSpaceError};
+
+impl Repository {
+    // 작업 트리의 현재 내용을 올려 둔다. 디스크에 없는 파일은 삭제로 올린다.
+    // 디렉토리는 그 아래 파일을 모두 올린다(HEAD에 있었지만 지금 없는 파일은 삭제로).
+    // commit처럼 심볼릭 링크는 따라가지 않는다: 경로 중간에 링크가 있으면 거부한다.
+    // 같은 경로를 다시 stage하면 덮어쓴다
+    pub fn stage(&self, paths: &[&str]) -> Result<()> {
+        let root = self.root();
+        let _lock = RepoLock::acquire(root)?;
+
+        let conn = &mut *self.conn()?;
+
+        let store = BlobStore::open(root, conn)?;
+        let head_paths = match current_head(conn)? {
+            Some(head) => load_blob_id_map(conn, &head)?.into_keys().collect(),
+            None => BTreeSet::new(),
+        };
+
+        // 모든 경로를 읽은 뒤에 한 번에 쓴다: 하나라도 잘못됐으면 아무것도 올리지 않는다.
+        // 파일이 None이면 삭제
+        let mut staged = Vec::with_capacity(paths.len());
+        for path in paths {
+            let path = index_path(path)?;
+
+            let mut found = BTreeSet::new();
+            match worktree_entry(root, &path)? {
+                Some(meta) if meta.is_file() => {
+                    found.insert(path.clone());
+                }
+                Some(meta) if meta.is_dir() => {
+                    let mut files = Vec::new();
+                    collect_files_recursive(root, &root.join(&path), &mut files)?;
+                    found.extend(files.iter().map(|rel| normalize_rel_path(rel)));
+                }
+                Some(_) => {
+                    return Err(WorkSpaceError::InvalidInput(format!(
+                        "not a regular file or directory: {}",
+                        path
+                    )));
+                }
+                None => {}
+            }
+
+            let prefix = format!("{}/", path);
+            let removed = head_paths
+                .iter()
+                .filter(|p| (**p == path || p.starts_with(&prefix)) && !found.contains(*p))
+                .cloned()
+                .collect::<Vec<_>>();
+            if found.is_empty() && removed.is_empty() {
+                return Err(WorkSpaceError::NotFound(format!(
+                    "pathspec did not match any file: {}",
+                    path
+                )));
+            }
+
+            for path in found {
+                let content = fs::read(root.join(&path))?;
+                let file = SnapshotFile {
+                    blob_id: blob_id_for_content(&content),
+                    path: path.clone(),
+                    content,
+                };
+                staged.push((path, Some(file)));
+            }
+            staged.extend(removed.into_iter().map(|path| (path, None)));
+        }
+
+        conn.transaction::<(), WorkSpaceError, _>(|tx| {
+            for (path, file) in &staged {
+                if let Some(file) = file {
+                    store.put(tx, &file.blob_id, &file.content)?;
+                }
+                let blob_id = file.as_ref().map(|f| f.blob_id.as_str());
+
+                diesel::insert_into(staged_dsl::staged_files)
+                    .values((staged_dsl::path.eq(path), staged_dsl::blob_id.eq(blob_id)))
+                    .on_conflict(staged_dsl::path)
+                    .do_update()
+                    .set(staged_dsl::blob_id.eq(blob_id))
+                    .execute(tx)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    // 올려 둔 변경을 내린다. 작업 트리는 건드리지 않는다
+    pub fn unstage(&self, paths: &[&str]) -> Result<()> {
+        let root = self.root();
+        let _lock = RepoLock::acquire(root)?;
+
+        let conn = &mut *self.conn()?;
+
+        let paths = paths
+            .iter()
+            .map(|path| index_path(path))
+            .collect::<Result<Vec<_>>>()?;
+
+        diesel::delete(staged_dsl::staged_files.filter(staged_dsl::path.eq_any(&paths)))
+            .execute(conn)
+            .map_err(to_db)?;
+
+        Ok(())
+    }
+
+    // 올려 둔 변경 목록(경로순). 종류는 지금 HEAD와 비교한 것이다
+    pub fn staged(&self) -> Result<Vec<StagedFile>> {
+        let conn = &mut *self.conn()?;
+
+        let head = current_head(conn)?;
+        let rows = load_staged(conn)?;
+
+        let mut out = Vec::with_capacity(rows.len());
+        for (path, blob_id) in rows {
+            let kind = match (head_blob(conn, head.as_deref(), &path)?, blob_id) {
+                (Some(before), Some(after)) if before == after => continue,
+                (Some(_), Some(_)) => DiffKind::Modified,
+                (None, Some(_)) => DiffKind::Added,
+                (Some(_), None) => DiffKind::Removed,
+                // HEAD에도 없는 파일의 삭제는 아무 변화도 아니다
+                (None, None) => continue,
+            };
+            out.push(StagedFile { path, kind });
+        }
+
+        Ok(out)
+    }
+
+    // HEAD 스냅샷에 올려 둔 변경만 덮어써 새 노드를 만들고 staging 영역을 비운다.
+    // 올리지 않은 작업 트리 변경은 그대로 남는다
+    pub fn commit_staged(&self, message: &str) -> Result<NodeId> {
+        let root = self.root();
+        let message_text = message.trim();
+
+        if message_text.is_empty() {
+            return Err(WorkSpaceError::EmptyMessage);
+        }
+
+        let _lock = RepoLock::acquire(root)?;
+
+        let mut guard = self.conn()?;
+        let conn = &mut *guard;
+
+        let store = BlobStore::open(root, conn)?;
+        let head = current_head(conn)?;
+
+        let rows = load_staged(conn)?;
+        if rows.is_empty() {
+            return Err(WorkSpaceError::InvalidInput(
+                "nothing staged: stage paths before commit_staged".to_string(),
+            ));
+        }
+
+        let mut files = match &head {
+            Some(head) => store
+                .node_contents(conn, head)?
+                .into_iter()
+                .collect::<BTreeMap<_, _>>(),
+            None => BTreeMap::new(),
+        };
+
+        let staged_ids = rows
+            .iter()
+            .filter_map(|(_, blob_id)| blob_id.clone())
+            .collect::<Vec<_>>();
+        let mut contents = store
+            .load(conn, &staged_ids)?
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+
+        for (path, blob_id) in rows {
+            match blob_id {
+                Some(blob_id) => {
+                    let content = contents.remove(&blob_id).ok_or_else(|| {
+                        WorkSpaceError::NotFound(format!("missing blob: {}", blob_id))
+                    })?;
+                    // 파일이 디렉토리로(또는 반대로) 바뀌었으면 HEAD 쪽 조상/자손 경로는 더 이상 없다
+                    drop_conflicting_paths(&mut files, &path);
+                    files.insert(path, content);
+                }
+                None => {
+                    files.remove(&path);
+                }
+            }
+        }
+
+        let files = files
+            .into_iter()
+            .map(|(path, content)| SnapshotFile {
+                blob_id: blob_id_for_content(&content),
+                path,
+                content,
+            })
+            .collect::<Vec<_>>();
+        let tree_id =
+            tree_id_for_entries(files.iter().map(|f| (f.path.as_str(), f.blob_id.as_str())))?;
+        let snapshot = Snapshot { files, tree_id };
+
+        let changed = changed_paths(conn, head.as_deref(), &snapshot)?;
+        if changed.is_empty()
+            && let Some(head) = head
+        {
+            return Err(WorkSpaceError::NothingToCommit(head));
+        }
+
+        // 훅은 이번 노드에 들어가는 변경만 받는다
+        // 훅이 도는 동안에는 연결을 놓아 다른 스레드의 읽기가 기다리지 않게 한다.
+        // 쓰기는 저장소 잠금이 막으므로 HEAD는 그대로다
+        drop(guard);
+        run_required_hook(root, PRE_SNAPSHOT, &[], &changed)?;
+
+        let conn = &mut *self.conn()?;
+        conn.transaction::<NodeId, WorkSpaceError, _>(|tx| {
+            let node_id = write_node(
+                tx,
+                &store,
+                message_text,
+                &snapshot,
+                NodeKind::Manual,
+                "commit",
+                &Progress::default(),
+            )?;
+            clear_staged(tx)?;
+
+            Ok(node_id)
+        })
+    }
+}
+
+pub fn stage(root: &Path, paths: &[&str]) -> Result<()> {
+    Repository::open(root)?.stage(paths)
+}
+
+pub fn unstage(root: &Path, paths: &[&str]) -> Result<()> {
+    Repository::open(root)?.unstage(paths)
+}
+
+pub fn staged(root: &Path) -> Result<Vec<StagedFile>> {
+    Repository::open(root)?.staged()
+}
+
+pub fn commit_staged(root: &Path, message: &str) -> Result<NodeId> {
+    Repository::open(root)?.commit_staged(message)
+}
+
+// commit이 작업 트리 전체를 저장했으면 올려 둔 변경도 모두 들어간 셈이다
+pub(crate) fn clear_staged(conn: &mut SqliteConnection) -> Result<()> {
+    diesel::delete(staged_dsl::staged_files)
+        .execute(conn)
+        .map_err(to_db)?;
+
+    Ok(())
+}
+
+fn load_staged(conn: &mut SqliteConnection) -> Result<Vec<(String, Option<String>)>> {
+    staged_dsl::staged_files
+        .select((staged_dsl::path, staged_dsl::blob_id))
+        .order(staged_dsl::path)
+        .load::<(String, Option<String>)>(conn)
+        .map_err(to_db)
+}
+
+fn head_blob(
+    conn: &mut SqliteConnection,
+    head: Option<&str>,
+    path: &str,
+) -> Result<Option<String>> {
+    let Some(head) = head else {
+        return Ok(None);
+    };
+
+    node_files_dsl::node_files
+        .filter(node_files_dsl::node_id.eq(head))
+        .filter(node_files_dsl::path.eq(path))
+        .select(node_files_dsl::blob_id)
+        .first::<String>(conn)
+        .optional()
+        .map_err(to_db)
+}
+
+// 루트 기준 상대 경로만 받는다. 지워진 파일도 올릴 수 있어야 하므로 canonicalize하지 않고 형태만 본다
+fn index_path(path: &str) -> Result<String> {
+    let rel = Path::new(path);
+    let escapes = rel
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if path.is_empty() || escapes {
+        return Err(WorkSpaceError::PathEscape(rel.to_path_buf()));
+    }
+
+    let normalized = normalize_rel_path(
+        &rel.components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .collect::<std::path::PathBuf>(),
+    );
+    if normalized.is_empty() || is_meta_path(&normalized) {
+        return Err(WorkSpaceError::InvalidInput(format!(
+            "cannot stage repository metadata: {}",
+            path
+        )));
+    }
+
+    Ok(normalized)
+}
+
+// 작업 트리에서 path의 메타데이터(없으면 None). 조각마다 symlink_metadata로 보고
+// 심볼릭 링크를 만나면 거부한다
+fn worktree_entry(root: &Path, path: &str) -> Result<Option<fs::Metadata>> {
+    let mut abs = root.to_path_buf();
+    let mut meta = None;
+
+    for part in path.split('/') {
+        if meta.as_ref().is_some_and(|m: &fs::Metadata| !m.is_dir()) {
+            return Ok(None);
+        }
+        abs.push(part);
+        let entry = match fs::symlink_metadata(&abs) {
+            Ok(entry) => entry,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if entry.file_type().is_symlink() {
+            return Err(WorkSpaceError::InvalidInput(format!(
+                "cannot stage through a symbolic link: {}",
+                path
+            )));
+        }
+        meta = Some(entry);
+    }
+
+    Ok(meta)
+}
+
+// path의 조상 디렉토리 자리에 있던 파일과 path 아래에 있던 파일을 지운다
+fn drop_conflicting_paths(files: &mut BTreeMap<String, Vec<u8>>, path: &str) {
+    let mut end = 0;
+    while let Some(pos) = path[end..].find('/') {
+        end += pos;
+        files.remove(&path[..end]);
+        end += 1;
+    }
+
+    let prefix = format!("{}/", path);
+    files.retain(|p, _| !p.starts_with(&prefix));
+}
@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use crate::vcs::storage::{ObjectStore, Tree, check_node_refs};
+use crate::{NodeId, NodeKind, Result, StoredNode, WorkSpaceError};
+
+const HEAD_FILE: &str = "HEAD";
+const NODES_DIR: &str = "nodes";
+const OBJECTS_DIR: &str = "objects";
+const REFS_DIR: &str = "refs";
+
+// 데이터베이스 없이 디렉터리에 두는 백엔드.
+//   HEAD              노드 id 한 줄
+//   nodes/<id>        텍스트: 머리 줄들(tree/parent/time/kind/file), 빈 줄, 메시지
+//   objects/ab/cdef.. blob 내용 (.novel/objects와 같은 fan-out)
+//   refs/<이름>       노드 id 한 줄
+// 작업 트리 안에 둘 때는 스냅샷에 섞이지 않도록 .novel 아래에 둔다
+#[derive(Debug)]
+pub struct FsStorage {
+    dir: PathBuf,
+}
+
+impl FsStorage {
+    pub fn open(dir: &Path) -> Result<FsStorage> {
+        for sub in [NODES_DIR, OBJECTS_DIR, REFS_DIR] {
+            fs::create_dir_all(dir.join(sub))?;
+        }
+
+        Ok(FsStorage {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    // 노드/blob id는 16진수라 파일 이름으로 바로 쓴다. 다른 문자가 섞인 id는 없는 것으로 본다
+    fn node_path(&self, node_id: &str) -> Option<PathBuf> {
+        is_hex_name(node_id).then(|| self.dir.join(NODES_DIR).join(node_id))
+    }
+
+    fn object_path(&self, blob_id: &str) -> Option<PathBuf> {
+        if !is_hex_name(blob_id) {
+            return None;
+        }
+        let (fan_out, rest) = blob_id.split_at(2.min(blob_id.len()));
+        Some(self.dir.join(OBJECTS_DIR).join(fan_out).join(rest))
+    }
+
+    fn ref_path(&self, name: &str) -> Result<PathBuf> {
+        let rel = Path::new(name);
+        // .tmp는 write_atomic의 임시 파일 이름이라 ref로 쓸 수 없다
+        let safe = !name.is_empty()
+            && !name.ends_with(".tmp")
+            && rel.components().all(|c| matches!(c, Component::Normal(_)));
+        if !safe {
+            return Err(WorkSpaceError::InvalidInput(format!(
+                "invalid ref name: {}",
+                name
+            )));
+        }
+
+        Ok(self.dir.join(REFS_DIR).join(rel))
+    }
+
+    fn read_node(&self, node_id: &str) -> Result<Option<(StoredNode, Tree)>> {
+        let Some(path) = self.node_path(node_id) else {
+            return Ok(None);
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => decode_node(node_id, &text).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn require_node(&self, node_id: &str) -> Result<()> {
+        match self.node_path(node_id) {
+            Some(path) if path.is_file() => Ok(()),
+            _ => Err(WorkSpaceError::NodeNotFound(node_id.to_string())),
+        }
+    }
+}
+
+impl ObjectStore for FsStorage {
+    fn put_node(&mut self, node: &StoredNode, tree: &Tree) -> Result<()> {
+        let path = self
+            .node_path(&node.id)
+            .ok_or_else(|| WorkSpaceError::InvalidInput(format!("invalid node id: {}", node.id)))?;
+        if path.exists() {
+            return Ok(());
+        }
+        check_node_refs(self, node, tree)?;
+
+        write_atomic(&path, encode_node(node, tree)?.as_bytes())
+    }
+
+    fn node(&mut self, node_id: &str) -> Result<Option<StoredNode>> {
+        Ok(self.read_node(node_id)?.map(|(node, _)| node))
+    }
+
+    fn nodes(&mut self) -> Result<Vec<StoredNode>> {
+        let mut nodes = Vec::new();
+        for entry in fs::read_dir(self.dir.join(NODES_DIR))? {
+            let name = entry?.file_name();
+            // 쓰다 만 .tmp 파일은 건너뛴다
+            let Some(node_id) = name.to_str().filter(|n| is_hex_name(n)) else {
+                continue;
+            };
+            if let Some((node, _)) = self.read_node(node_id)? {
+                nodes.push(node);
+            }
+        }
+
+        Ok(nodes)
+    }
+
+    fn tree(&mut self, node_id: &str) -> Result<Tree> {
+        self.read_node(node_id)?
+            .map(|(_, tree)| tree)
+            .ok_or_else(|| WorkSpaceError::NodeNotFound(node_id.to_string()))
+    }
+
+    fn has_blob(&mut self, blob_id: &str) -> Result<bool> {
+        Ok(self.object_path(blob_id).is_some_and(|p| p.is_file()))
+    }
+
+    fn put_blob(&mut self, blob_id: &str, content: &[u8]) -> Result<()> {
+        let path = self
+            .object_path(blob_id)
+            .ok_or_else(|| WorkSpaceError::InvalidInput(format!("invalid blob id: {}", blob_id)))?;
+        if path.exists() {
+            return Ok(());
+        }
+        fs::create_dir_all(path.parent().expect("object path has a fan-out directory"))?;
+
+        write_atomic(&path, content)
+    }
+
+    fn blob(&mut self, blob_id: &str) -> Result<Option<Vec<u8>>> {
+        let Some(path) = self.object_path(blob_id) else {
+            return Ok(None);
+        };
+        match fs::read(&path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn head(&mut self) -> Result<Option<NodeId>> {
+        read_id_file(&self.dir.join(HEAD_FILE))
+    }
+
+    fn set_head(&mut self, node_id: &str) -> Result<()> {
+        self.require_node(node_id)?;
+
+        write_atomic(
+            &self.dir.join(HEAD_FILE),
+            format!("{}\n", node_id).as_bytes(),
+        )
+    }
+
+    fn refs(&mut self) -> Result<BTreeMap<String, NodeId>> {
+        let refs_dir = self.dir.join(REFS_DIR);
+        let mut files = Vec::new();
+        collect_ref_files(&refs_dir, &mut files)?;
+
+        let mut refs = BTreeMap::new();
+        for path in files {
+            let name = path
+                .strip_prefix(&refs_dir)
+                .map_err(|_| WorkSpaceError::PathOutsideRoot(path.clone()))?
+                .to_string_lossy()
+                .replace('\\', "/");
+            if let Some(node_id) = read_id_file(&path)? {
+                refs.insert(name, node_id);
+            }
+        }
+
+        Ok(refs)
+    }
+
+    fn set_ref(&mut self, name: &str, node_id: &str) -> Result<()> {
+        let path = self.ref_path(name)?;
+        self.require_node(node_id)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        write_atomic(&path, format!("{}\n", node_id).as_bytes())
+    }
+
+    fn delete_ref(&mut self, name: &str) -> Result<bool> {
+        match fs::remove_file(self.ref_path(name)?) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+// write_atomic이 쓰다 만 임시 파일
+fn is_tmp_name(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.ends_with(".tmp"))
+}
+
+fn is_hex_name(id: &str) -> bool {
+    !id.is_empty() && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// 같은 디렉터리의 임시 파일(<이름>.tmp)에 쓴 뒤 이름을 바꾼다: 읽는 쪽은 반쯤 쓴 파일을 보지 않는다.
+// 확장자를 바꾸지 않고 덧붙이므로 v1.0과 v1.5가 같은 임시 파일을 쓰지 않는다
+fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)?;
+
+    Ok(())
+}
+
+fn read_id_file(path: &Path) -> Result<Option<NodeId>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text.trim().to_string()).filter(|id| !id.is_empty())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn collect_ref_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect_ref_files(&path, out)?;
+        } else if !is_tmp_name(&path) {
+            out.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn encode_node(node: &StoredNode, tree: &Tree) -> Result<String> {
+    let mut text = format!("tree {}\n", node.tree_id);
+    for parent in &node.parents {
+        text.push_str(&format!("parent {}\n", parent));
+    }
+    text.push_str(&format!("time {}\n", node.created_at_unix_ms));
+    text.push_str(&format!("kind {}\n", node.kind.as_str()));
+    for (path, blob_id) in tree {
+        // 한 줄에 한 파일이므로 줄바꿈이 든 경로는 담을 수 없다
+        if path.contains('\n') {
+            return Err(WorkSpaceError::InvalidInput(format!(
+                "path contains a newline: {:?}",
+                path
+            )));
+        }
+        text.push_str(&format!("file {} {}\n", blob_id, path));
+    }
+    text.push('\n');
+    text.push_str(&node.message);
+
+    Ok(text)
+}
+
+fn decode_node(node_id: &str, text: &str) -> Result<(StoredNode, Tree)> {
+    let corrupt = |reason: &str| {
+        WorkSpaceError::CorruptData(format!("invalid node file {}: {}", node_id, reason))
+    };
+
+    let (header, message) = text
+        .split_once("\n\n")
+        .ok_or_else(|| corrupt("missing message separator"))?;
+
+    let mut tree_id = None;
+    let mut parents = Vec::new();
+    let mut created_at = None;
+    let mut kind = NodeKind::Manual;
+    let mut tree = Tree::new();
+    for line in header.lines() {
+        let (key, value) = line.split_once(' ').ok_or_else(|| corrupt(line))?;
+        match key {
+            "tree" => tree_id = Some(value.to_string()),
+            "parent" => parents.push(value.to_string()),
+            "time" => created_at = Some(value.parse::<i64>().map_err(|_| corrupt(line))?),
+            "kind" => kind = NodeKind::from_db(value),
+            "file" => {
+                let (blob_id, path) = value.split_once(' ').ok_or_else(|| corrupt(line))?;
+                tree.insert(path.to_string(), blob_id.to_string());
+            }
+            _ => return Err(corrupt(line)),
+        }
+    }
+
+    let node = StoredNode {
+        id: node_id.to_string(),
+        parents,
+        message: message.to_string(),
+        created_at_unix_ms: created_at.ok_or_else(|| corrupt("missing time"))?,
+        tree_id: tree_id.ok_or_else(|| corrupt("missing tree"))?,
+        kind,
+    };
+
+    Ok((node, tree))
+}
@@ -0,0 +1,93 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::vcs::storage::{ObjectStore, Tree, check_node_refs};
+use crate::{NodeId, Result, StoredNode, WorkSpaceError};
+
+// 프로세스 메모리에만 두는 백엔드. 테스트용
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    nodes: HashMap<NodeId, (StoredNode, Tree)>,
+    blobs: HashMap<String, Vec<u8>>,
+    head: Option<NodeId>,
+    refs: BTreeMap<String, NodeId>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+}
+
+impl ObjectStore for MemoryStorage {
+    fn put_node(&mut self, node: &StoredNode, tree: &Tree) -> Result<()> {
+        if self.nodes.contains_key(&node.id) {
+            return Ok(());
+        }
+        check_node_refs(self, node, tree)?;
+        self.nodes
+            .insert(node.id.clone(), (node.clone(), tree.clone()));
+
+        Ok(())
+    }
+
+    fn node(&mut self, node_id: &str) -> Result<Option<StoredNode>> {
+        Ok(self.nodes.get(node_id).map(|(node, _)| node.clone()))
+    }
+
+    fn nodes(&mut self) -> Result<Vec<StoredNode>> {
+        Ok(self.nodes.values().map(|(node, _)| node.clone()).collect())
+    }
+
+    fn tree(&mut self, node_id: &str) -> Result<Tree> {
+        self.nodes
+            .get(node_id)
+            .map(|(_, tree)| tree.clone())
+            .ok_or_else(|| WorkSpaceError::NodeNotFound(node_id.to_string()))
+    }
+
+    fn has_blob(&mut self, blob_id: &str) -> Result<bool> {
+        Ok(self.blobs.contains_key(blob_id))
+    }
+
+    fn put_blob(&mut self, blob_id: &str, content: &[u8]) -> Result<()> {
+        self.blobs
+            .entry(blob_id.to_string())
+            .or_insert_with(|| content.to_vec());
+
+        Ok(())
+    }
+
+    fn blob(&mut self, blob_id: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.blobs.get(blob_id).cloned())
+    }
+
+    fn head(&mut self) -> Result<Option<NodeId>> {
+        Ok(self.head.clone())
+    }
+
+    fn set_head(&mut self, node_id: &str) -> Result<()> {
+        if !self.nodes.contains_key(node_id) {
+            return Err(WorkSpaceError::NodeNotFound(node_id.to_string()));
+        }
+        self.head = Some(node_id.to_string());
+
+        Ok(())
+    }
+
+    fn refs(&mut self) -> Result<BTreeMap<String, NodeId>> {
+        Ok(self.refs.clone())
+    }
+
+    fn set_ref(&mut self, name: &str, node_id: &str) -> Result<()> {
+        if !self.nodes.contains_key(node_id) {
+            return Err(WorkSpaceError::NodeNotFound(node_id.to_string()));
+        }
+        self.refs.insert(name.to_string(), node_id.to_string());
+
+        Ok(())
+    }
+
+    fn delete_ref(&mut self, name: &str) -> Result<bool> {
+        Ok(self.refs.remove(name).is_some())
+    }
+}
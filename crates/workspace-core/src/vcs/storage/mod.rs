@@ -0,0 +1,200 @@
+// 노드/부모/트리/blob/ref를 저장하는 백엔드 추상화.
+// 아래 commit/checkout/log/diff_nodes는 어떤 백엔드에서나 같은 결과를 낸다.
+// Repository의 log/diff_nodes도 SQLite 백엔드로 이 함수들을 부르고, checkout은 같은 journal 작업 트리 쓰기를 쓴다.
+// commit/checkout은 root의 저장소 잠금을 잡는다
+mod fs;
+mod memory;
+mod sqlite;
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::vcs::checkout::write_work_tree;
+use crate::vcs::commit::{collect_snapshot, new_node_id, now_unix_ms};
+use crate::vcs::diff::{build_file_diff, changed_paths};
+use crate::vcs::lock::RepoLock;
+use crate::vcs::progress::Progress;
+use crate::{NodeDiff, NodeId, NodeKind, Result, StoredNode, VersionNode, WorkSpaceError};
+
+pub use fs::FsStorage;
+pub use memory::MemoryStorage;
+pub(crate) use sqlite::SqliteObjects;
+pub use sqlite::SqliteStorage;
+
+// 경로 -> blob id
+pub type Tree = BTreeMap<String, String>;
+
+pub trait ObjectStore {
+    // 노드와 트리를 함께 넣는다. 부모 노드와 트리의 blob은 먼저 있어야 한다.
+    // 같은 id가 이미 있으면 아무것도 하지 않는다
+    fn put_node(&mut self, node: &StoredNode, tree: &Tree) -> Result<()>;
+    fn node(&mut self, node_id: &str) -> Result<Option<StoredNode>>;
+    // 순서는 정하지 않는다
+    fn nodes(&mut self) -> Result<Vec<StoredNode>>;
+    // 없는 노드면 NodeNotFound
+    fn tree(&mut self, node_id: &str) -> Result<Tree>;
+
+    fn has_blob(&mut self, blob_id: &str) -> Result<bool>;
+    // 이미 있는 blob은 다시 쓰지 않는다
+    fn put_blob(&mut self, blob_id: &str, content: &[u8]) -> Result<()>;
+    fn blob(&mut self, blob_id: &str) -> Result<Option<Vec<u8>>>;
+
+    fn head(&mut self) -> Result<Option<NodeId>>;
+    // 없는 노드면 NodeNotFound
+    fn set_head(&mut self, node_id: &str) -> Result<()>;
+    // 이름 -> 노드. 태그는 "tags/<이름>"
+    fn refs(&mut self) -> Result<BTreeMap<String, NodeId>>;
+    fn set_ref(&mut self, name: &str, node_id: &str) -> Result<()>;
+    // 지웠으면 true
+    fn delete_ref(&mut self, name: &str) -> Result<bool>;
+}
+
+// 작업 트리 전체를 HEAD의 자식 노드로 넣고 HEAD를 옮긴다.
+// HEAD와 트리가 같으면 NothingToCommit
+pub fn commit(store: &mut dyn ObjectStore, root: &Path, message: &str) -> Result<NodeId> {
+    let message_text = message.trim();
+    if message_text.is_empty() {
+        return Err(WorkSpaceError::EmptyMessage);
+    }
+
+    let _lock = RepoLock::acquire(root)?;
+    let snapshot = collect_snapshot(root, &Progress::default())?;
+    let head = store.head()?;
+    if let Some(head) = &head
+        && node_or_err(store, head)?.tree_id == snapshot.tree_id
+    {
+        return Err(WorkSpaceError::NothingToCommit(head.clone()));
+    }
+
+    let mut tree = Tree::new();
+    for file in &snapshot.files {
+        store.put_blob(&file.blob_id, &file.content)?;
+        tree.insert(file.path.clone(), file.blob_id.clone());
+    }
+
+    let created_at_ms = now_unix_ms();
+    let id = new_node_id(
+        message_text,
+        head.as_deref(),
+        &snapshot.tree_id,
+        created_at_ms,
+    );
+    store.put_node(
+        &StoredNode {
+            id: id.clone(),
+            parents: head.into_iter().collect(),
+            message: message_text.to_string(),
+            created_at_unix_ms: created_at_ms,
+            tree_id: snapshot.tree_id,
+            kind: NodeKind::Manual,
+        },
+        &tree,
+    )?;
+    store.set_head(&id)?;
+
+    Ok(id)
+}
+
+// 작업 트리를 노드 내용으로 바꾸고 HEAD를 옮긴다. node_id는 전체 id.
+// blob을 모두 읽은 뒤에 작업 트리를 고치므로 blob이 빠졌으면 아무것도 바뀌지 않는다.
+// Repository::checkout과 같이 journal을 남기므로 중간에 죽으면 다음 잠금에서 작업 트리를 되돌린다
+pub fn checkout(store: &mut dyn ObjectStore, root: &Path, node_id: &str) -> Result<()> {
+    let _lock = RepoLock::acquire(root)?;
+    let rows = load_contents(store, node_id)?
+        .into_iter()
+        .collect::<Vec<_>>();
+    let old_head = store.head()?;
+
+    write_work_tree(
+        root,
+        node_id,
+        old_head.as_deref(),
+        &rows,
+        &Progress::default(),
+        || store.set_head(node_id),
+    )?;
+
+    Ok(())
+}
+
+// 모든 노드를 최신 순으로. 메모는 SQLite 저장소에만 있으므로 비어 있다
+pub fn log(store: &mut dyn ObjectStore) -> Result<Vec<VersionNode>> {
+    let mut nodes = store.nodes()?;
+    nodes.sort_by(|a, b| (b.created_at_unix_ms, &b.id).cmp(&(a.created_at_unix_ms, &a.id)));
+
+    Ok(nodes
+        .into_iter()
+        .map(|node| VersionNode {
+            id: node.id,
+            parents: node.parents,
+            message: node.message,
+            created_at_unix_ms: node.created_at_unix_ms,
+            kind: node.kind,
+            notes: Vec::new(),
+        })
+        .collect())
+}
+
+// from/to는 전체 노드 id
+pub fn diff_nodes(store: &mut dyn ObjectStore, from: &str, to: &str) -> Result<NodeDiff> {
+    let mut files = Vec::new();
+    // 트리 id가 같으면 내용이 같다
+    if node_or_err(store, from)?.tree_id != node_or_err(store, to)?.tree_id {
+        let from_map = load_contents(store, from)?;
+        let to_map = load_contents(store, to)?;
+
+        files = changed_paths(&from_map, &to_map)
+            .into_iter()
+            .map(|(path, kind)| {
+                let before = from_map.get(&path).map(Vec::as_slice);
+                let after = to_map.get(&path).map(Vec::as_slice);
+                build_file_diff(path, kind, before, after)
+            })
+            .collect();
+    }
+
+    Ok(NodeDiff {
+        from: from.to_string(),
+        to: to.to_string(),
+        files,
+    })
+}
+
+fn node_or_err(store: &mut dyn ObjectStore, node_id: &str) -> Result<StoredNode> {
+    store
+        .node(node_id)?
+        .ok_or_else(|| WorkSpaceError::NodeNotFound(node_id.to_string()))
+}
+
+// 노드 스냅샷의 경로 -> 내용
+fn load_contents(store: &mut dyn ObjectStore, node_id: &str) -> Result<HashMap<String, Vec<u8>>> {
+    let tree = store.tree(node_id)?;
+    let mut contents = HashMap::with_capacity(tree.len());
+    for (path, blob_id) in tree {
+        let content = store.blob(&blob_id)?.ok_or_else(|| {
+            WorkSpaceError::NotFound(format!("missing blob {} for {}", blob_id, path))
+        })?;
+        contents.insert(path, content);
+    }
+
+    Ok(contents)
+}
+
+// put_node 전에 부모와 blob이 있는지 본다. 백엔드들이 같은 에러를 내도록 여기 둔다
+fn check_node_refs(store: &mut dyn ObjectStore, node: &StoredNode, tree: &Tree) -> Result<()> {
+    for parent in &node.parents {
+        if store.node(parent)?.is_none() {
+            return Err(WorkSpaceError::NodeNotFound(parent.clone()));
+        }
+    }
+    for (path, blob_id) in tree {
+        if !store.has_blob(blob_id)? {
+            return Err(WorkSpaceError::NotFound(format!(
+                "missing blob {} for {}",
+                blob_id, path
+            )));
+        }
+    }
+
+    Ok(())
+}
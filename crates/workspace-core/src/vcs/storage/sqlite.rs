@@ -0,0 +1,368 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use diesel::prelude::*;
+
+use crate::schema::blobs::dsl as blobs_dsl;
+use crate::schema::node_files::dsl as node_files_dsl;
+use crate::schema::node_parents::dsl as node_parents_dsl;
+use crate::schema::nodes::dsl as nodes_dsl;
+use crate::schema::refs::dsl as refs_dsl;
+use crate::vcs::crypto::{open_message, seal_message};
+use crate::vcs::db::to_db;
+use crate::vcs::diff::load_blob_id_map;
+use crate::vcs::lock::RepoLock;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::reflog::{move_head, short_id};
+use crate::vcs::repo::current_head;
+use crate::vcs::repository::Repository;
+use crate::vcs::storage::{ObjectStore, Tree, check_node_refs};
+use crate::vcs::timeline::{manuscript_counts, save_node_stats};
+use crate::vcs::tree::{node_tree_id, tree_id_for_entries};
+use crate::{NodeId, NodeKind, Result, StoredNode, WorkSpaceError};
+
+// .novel/vcs.db를 쓰는 기존 저장소. Repository와 같은 테이블을 쓰므로 양쪽에서 서로의 노드가 보인다.
+// 큰 blob은 objects 파일로, 암호화 저장소면 메시지와 blob을 봉인해서 둔다.
+// 쓰기는 Repository처럼 저장소 잠금을 잡는다
+pub struct SqliteStorage {
+    repo: Repository,
+    store: BlobStore,
+}
+
+impl SqliteStorage {
+    pub fn open(root: &Path) -> Result<SqliteStorage> {
+        SqliteStorage::from_repository(Repository::open(root)?)
+    }
+
+    // 잠긴 암호화 저장소면 KeyRequired
+    pub fn from_repository(repo: Repository) -> Result<SqliteStorage> {
+        let store = BlobStore::open(repo.root(), &mut *repo.conn()?)?;
+
+        Ok(SqliteStorage { repo, store })
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&mut SqliteObjects) -> Result<T>) -> Result<T> {
+        let conn = &mut *self.repo.conn()?;
+        f(&mut SqliteObjects::new(conn, &self.store))
+    }
+
+    fn write<T>(&self, f: impl FnOnce(&mut SqliteObjects) -> Result<T>) -> Result<T> {
+        let _lock = RepoLock::acquire(self.repo.root())?;
+        self.read(f)
+    }
+}
+
+impl ObjectStore for SqliteStorage {
+    fn put_node(&mut self, node: &StoredNode, tree: &Tree) -> Result<()> {
+        self.write(|objects| objects.put_node(node, tree))
+    }
+
+    fn node(&mut self, node_id: &str) -> Result<Option<StoredNode>> {
+        self.read(|objects| objects.node(node_id))
+    }
+
+    fn nodes(&mut self) -> Result<Vec<StoredNode>> {
+        self.read(|objects| objects.nodes())
+    }
+
+    fn tree(&mut self, node_id: &str) -> Result<Tree> {
+        self.read(|objects| objects.tree(node_id))
+    }
+
+    fn has_blob(&mut self, blob_id: &str) -> Result<bool> {
+        self.read(|objects| objects.has_blob(blob_id))
+    }
+
+    fn put_blob(&mut self, blob_id: &str, content: &[u8]) -> Result<()> {
+        self.write(|objects| objects.put_blob(blob_id, content))
+    }
+
+    fn blob(&mut self, blob_id: &str) -> Result<Option<Vec<u8>>> {
+        self.read(|objects| objects.blob(blob_id))
+    }
+
+    fn head(&mut self) -> Result<Option<NodeId>> {
+        self.read(|objects| objects.head())
+    }
+
+    fn set_head(&mut self, node_id: &str) -> Result<()> {
+        self.write(|objects| objects.set_head(node_id))
+    }
+
+    fn refs(&mut self) -> Result<BTreeMap<String, NodeId>> {
+        self.read(|objects| objects.refs())
+    }
+
+    fn set_ref(&mut self, name: &str, node_id: &str) -> Result<()> {
+        self.write(|objects| objects.set_ref(name, node_id))
+    }
+
+    fn delete_ref(&mut self, name: &str) -> Result<bool> {
+        self.write(|objects| objects.delete_ref(name))
+    }
+}
+
+// 이미 열어 둔 연결 위의 SQLite 백엔드. Repository 메서드가 storage 함수를 부를 때 쓴다.
+// 잠금은 잡지 않는다: 쓰기라면 호출하는 쪽이 잡는다
+pub(crate) struct SqliteObjects<'a> {
+    conn: &'a mut SqliteConnection,
+    store: &'a BlobStore,
+}
+
+impl<'a> SqliteObjects<'a> {
+    pub(crate) fn new(conn: &'a mut SqliteConnection, store: &'a BlobStore) -> SqliteObjects<'a> {
+        SqliteObjects { conn, store }
+    }
+}
+
+impl ObjectStore for SqliteObjects<'_> {
+    fn put_node(&mut self, node: &StoredNode, tree: &Tree) -> Result<()> {
+        if self.node(&node.id)?.is_some() {
+            return Ok(());
+        }
+        check_node_refs(self, node, tree)?;
+
+        let store = self.store;
+        let cipher = store.cipher();
+        self.conn.transaction::<(), WorkSpaceError, _>(|tx| {
+            diesel::insert_into(nodes_dsl::nodes)
+                .values((
+                    nodes_dsl::id.eq(&node.id),
+                    nodes_dsl::message.eq(seal_message(cipher, node.id.as_bytes(), &node.message)),
+                    nodes_dsl::created_at_unix_ms.eq(node.created_at_unix_ms),
+                    nodes_dsl::tree_id.eq(&node.tree_id),
+                    nodes_dsl::kind.eq(node.kind.as_str()),
+                ))
+                .execute(tx)?;
+
+            for (ord, parent) in node.parents.iter().enumerate() {
+                diesel::insert_into(node_parents_dsl::node_parents)
+                    .values((
+                        node_parents_dsl::node_id.eq(&node.id),
+                        node_parents_dsl::parent_id.eq(parent),
+                        node_parents_dsl::ord.eq(ord as i32),
+                    ))
+                    .execute(tx)?;
+            }
+
+            for (path, blob_id) in tree {
+                diesel::insert_into(node_files_dsl::node_files)
+                    .values((
+                        node_files_dsl::node_id.eq(&node.id),
+                        node_files_dsl::path.eq(path),
+                        node_files_dsl::blob_id.eq(blob_id),
+                    ))
+                    .execute(tx)?;
+            }
+
+            // Repository::commit처럼 진행 통계를 함께 남긴다
+            let contents = store.node_contents(tx, &node.id)?;
+            save_node_stats(
+                tx,
+                &node.id,
+                manuscript_counts(contents.iter().map(|(_, c)| c.as_slice())),
+            )?;
+
+            Ok(())
+        })
+    }
+
+    fn node(&mut self, node_id: &str) -> Result<Option<StoredNode>> {
+        let conn = &mut *self.conn;
+
+        let Some((message, created_at_unix_ms, tree_id, kind)) = nodes_dsl::nodes
+            .filter(nodes_dsl::id.eq(node_id))
+            .select((
+                nodes_dsl::message,
+                nodes_dsl::created_at_unix_ms,
+                nodes_dsl::tree_id,
+                nodes_dsl::kind,
+            ))
+            .first::<(String, i64, Option<String>, String)>(conn)
+            .optional()
+            .map_err(to_db)?
+        else {
+            return Ok(None);
+        };
+
+        let parents = node_parents_dsl::node_parents
+            .filter(node_parents_dsl::node_id.eq(node_id))
+            .select(node_parents_dsl::parent_id)
+            .order(node_parents_dsl::ord.asc())
+            .load::<String>(conn)
+            .map_err(to_db)?;
+
+        // tree_id 컬럼 이전의 노드는 파일 목록으로 계산한다
+        let tree_id = match tree_id {
+            Some(tree_id) => tree_id,
+            None => node_tree_id(conn, node_id)?,
+        };
+
+        Ok(Some(StoredNode {
+            id: node_id.to_string(),
+            parents,
+            message: open_message(self.store.cipher(), node_id.as_bytes(), message)?,
+            created_at_unix_ms,
+            tree_id,
+            kind: NodeKind::from_db(&kind),
+        }))
+    }
+
+    // 노드, 부모, (tree_id가 비어 있는 노드의) 파일 목록을 테이블마다 한 번에 읽는다
+    fn nodes(&mut self) -> Result<Vec<StoredNode>> {
+        let conn = &mut *self.conn;
+
+        let rows = nodes_dsl::nodes
+            .select((
+                nodes_dsl::id,
+                nodes_dsl::message,
+                nodes_dsl::created_at_unix_ms,
+                nodes_dsl::tree_id,
+                nodes_dsl::kind,
+            ))
+            .load::<(String, String, i64, Option<String>, String)>(conn)
+            .map_err(to_db)?;
+
+        let mut parents: HashMap<String, Vec<String>> = HashMap::new();
+        for (node_id, parent_id) in node_parents_dsl::node_parents
+            .select((node_parents_dsl::node_id, node_parents_dsl::parent_id))
+            .order((node_parents_dsl::node_id, node_parents_dsl::ord))
+            .load::<(String, String)>(conn)
+            .map_err(to_db)?
+        {
+            parents.entry(node_id).or_default().push(parent_id);
+        }
+
+        let untreed = rows
+            .iter()
+            .filter(|(_, _, _, tree_id, _)| tree_id.is_none())
+            .map(|(id, ..)| id.clone())
+            .collect::<Vec<_>>();
+        let mut files: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        if !untreed.is_empty() {
+            for (node_id, path, blob_id) in node_files_dsl::node_files
+                .filter(node_files_dsl::node_id.eq_any(&untreed))
+                .select((
+                    node_files_dsl::node_id,
+                    node_files_dsl::path,
+                    node_files_dsl::blob_id,
+                ))
+                .load::<(String, String, String)>(conn)
+                .map_err(to_db)?
+            {
+                files.entry(node_id).or_default().push((path, blob_id));
+            }
+        }
+
+        rows.into_iter()
+            .map(|(id, message, created_at_unix_ms, tree_id, kind)| {
+                let tree_id = match tree_id {
+                    Some(tree_id) => tree_id,
+                    None => {
+                        let files = files.remove(&id).unwrap_or_default();
+                        tree_id_for_entries(files.iter().map(|(p, b)| (p.as_str(), b.as_str())))?
+                    }
+                };
+
+                Ok(StoredNode {
+                    parents: parents.remove(&id).unwrap_or_default(),
+                    message: open_message(self.store.cipher(), id.as_bytes(), message)?,
+                    id,
+                    created_at_unix_ms,
+                    tree_id,
+                    kind: NodeKind::from_db(&kind),
+                })
+            })
+            .collect()
+    }
+
+    fn tree(&mut self, node_id: &str) -> Result<Tree> {
+        if self.node(node_id)?.is_none() {
+            return Err(WorkSpaceError::NodeNotFound(node_id.to_string()));
+        }
+
+        Ok(load_blob_id_map(&mut *self.conn, node_id)?
+            .into_iter()
+            .collect())
+    }
+
+    fn has_blob(&mut self, blob_id: &str) -> Result<bool> {
+        let present = blobs_dsl::blobs
+            .filter(blobs_dsl::id.eq(blob_id))
+            .count()
+            .get_result::<i64>(&mut *self.conn)
+            .map_err(to_db)?;
+
+        Ok(present > 0)
+    }
+
+    fn put_blob(&mut self, blob_id: &str, content: &[u8]) -> Result<()> {
+        self.store
+            .put(&mut *self.conn, blob_id, content)
+            .map(|_| ())
+    }
+
+    fn blob(&mut self, blob_id: &str) -> Result<Option<Vec<u8>>> {
+        let loaded = self.store.load(&mut *self.conn, &[blob_id.to_string()])?;
+
+        Ok(loaded.into_iter().next().map(|(_, content)| content))
+    }
+
+    fn head(&mut self) -> Result<Option<NodeId>> {
+        current_head(&mut *self.conn)
+    }
+
+    // reflog에는 "set_head"로 남는다
+    fn set_head(&mut self, node_id: &str) -> Result<()> {
+        if self.node(node_id)?.is_none() {
+            return Err(WorkSpaceError::NodeNotFound(node_id.to_string()));
+        }
+
+        let conn = &mut *self.conn;
+        conn.transaction::<_, WorkSpaceError, _>(|tx| {
+            let old_head = current_head(tx)?;
+            move_head(
+                tx,
+                node_id,
+                "set_head",
+                &format!(
+                    "moving from {} to {}",
+                    short_id(old_head.as_deref()),
+                    short_id(Some(node_id))
+                ),
+            )
+        })?;
+
+        Ok(())
+    }
+
+    fn refs(&mut self) -> Result<BTreeMap<String, NodeId>> {
+        let refs = refs_dsl::refs
+            .select((refs_dsl::name, refs_dsl::node_id))
+            .load::<(String, String)>(&mut *self.conn)
+            .map_err(to_db)?;
+
+        Ok(refs.into_iter().collect())
+    }
+
+    fn set_ref(&mut self, name: &str, node_id: &str) -> Result<()> {
+        if self.node(node_id)?.is_none() {
+            return Err(WorkSpaceError::NodeNotFound(node_id.to_string()));
+        }
+
+        diesel::replace_into(refs_dsl::refs)
+            .values((refs_dsl::name.eq(name), refs_dsl::node_id.eq(node_id)))
+            .execute(&mut *self.conn)
+            .map_err(to_db)?;
+
+        Ok(())
+    }
+
+    fn delete_ref(&mut self, name: &str) -> Result<bool> {
+        let removed = diesel::delete(refs_dsl::refs.filter(refs_dsl::name.eq(name)))
+            .execute(&mut *self.conn)
+            .map_err(to_db)?;
+
+        Ok(removed > 0)
+    }
+}
@@ -0,0 +1,313 @@
+use std::io::Read;
+use std::path::Path;
+
+use diesel::prelude::*;
+
+use crate::vcs::bundle::{ObjectPack, apply_pack, collect_pack, decode_pack, encode_pack};
+use crate::vcs::graph::{is_reachable, reachable_from};
+use crate::vcs::lock::RepoLock;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::progress::Progress;
+use crate::vcs::reflog::move_head;
+use crate::vcs::repo::current_head;
+use crate::vcs::repository::Repository;
+use crate::{NodeId, ReceiveSummary, Result, SyncAdvertisement, WorkSpaceError};
+
+// 동기화 서버가 받는 요청. HTTP 라우팅/인증은 서버 쪽 책임이고,
+// 여기서는 요청 본문을 해석해서 응답 본문을 만든다.
+//
+// GET  <repo>/advertise     -> "head <id>|-" 다음 줄부터 HEAD에서 도달 가능한 노드 id
+// POST <repo>/upload-pack   <- "want <id>" + "have <id>"... -> 번들 포맷 pack
+// POST <repo>/receive-pack  <- 번들 포맷 pack(refs[0] = HEAD) -> "old/new/nodes/blobs/forced" 줄
+#[derive(Debug, Clone, Copy)]
+pub enum SyncRequest<'a> {
+    Advertise,
+    UploadPack(&'a [u8]),
+    ReceivePack { pack: &'a [u8], force: bool },
+}
+
+pub const SYNC_ADVERTISE_PATH: &str = "advertise";
+pub const SYNC_UPLOAD_PACK_PATH: &str = "upload-pack";
+pub const SYNC_RECEIVE_PACK_PATH: &str = "receive-pack";
+
+impl Repository {
+    // 동기화 서버 요청 처리 API
+    pub fn sync_serve(&self, request: SyncRequest<'_>) -> Result<Vec<u8>> {
+        let root = self.root();
+        let conn = &mut *self.conn()?;
+
+        match request {
+            SyncRequest::Advertise => {
+                let advertisement = advertise(conn)?;
+                Ok(encode_advertisement(&advertisement).into_bytes())
+            }
+            SyncRequest::UploadPack(body) => {
+                let (want, haves) = decode_fetch_request(body)?;
+                let store = BlobStore::open(root, conn)?;
+                let pack = collect_pack(conn, &store, &[want], &haves, &Progress::default())?;
+                Ok(encode_pack(&pack))
+            }
+            SyncRequest::ReceivePack { pack, force } => {
+                let pack = decode_pack(pack)?;
+                let _lock = RepoLock::acquire(root)?;
+                let store = BlobStore::open(root, conn)?;
+                let summary = receive_pack(conn, &store, &pack, force)?;
+                Ok(encode_receive_summary(&summary).into_bytes())
+            }
+        }
+    }
+}
+
+pub fn sync_serve(root: &Path, request: SyncRequest<'_>) -> Result<Vec<u8>> {
+    Repository::open_existing(root)?.sync_serve(request)
+}
+
+pub(crate) fn advertise(conn: &mut SqliteConnection) -> Result<SyncAdvertisement> {
+    let head = current_head(conn)?;
+
+    let mut nodes = match &head {
+        Some(head) => reachable_from(conn, std::slice::from_ref(head))?
+            .into_iter()
+            .collect::<Vec<_>>(),
+        None => Vec::new(),
+    };
+    nodes.sort();
+
+    Ok(SyncAdvertisement { head, nodes })
+}
+
+// 받은 pack을 적용하고 HEAD를 pack.refs[0]으로 옮긴다. 전체가 하나의 트랜잭션이다.
+// 기존 HEAD가 새 HEAD의 조상이 아니면 force 없이는 거부(롤백)한다.
+pub(crate) fn receive_pack(
+    conn: &mut SqliteConnection,
+    store: &BlobStore,
+    pack: &ObjectPack,
+    force: bool,
+) -> Result<ReceiveSummary> {
+    let new_head = pack
+        .refs
+        .first()
+        .map(|r| r.node_id.clone())
+        .ok_or_else(|| invalid_request("pack has no head ref"))?;
+
+    conn.transaction::<ReceiveSummary, WorkSpaceError, _>(|tx| {
+        let old_head = current_head(tx)?;
+        let (imported_nodes, imported_blobs) = apply_pack(tx, store, pack, &Progress::default())?;
+
+        let fast_forward = match &old_head {
+            Some(old) => is_reachable(tx, &new_head, old)?,
+            None => true,
+        };
+
+        if !fast_forward && !force {
+            return Err(WorkSpaceError::NonFastForward(format!(
+                "head {} is not an ancestor of {}",
+                old_head.as_deref().unwrap_or("-"),
+                new_head
+            )));
+        }
+
+        let message = if fast_forward {
+            "fast-forward"
+        } else {
+            "forced update"
+        };
+        move_head(tx, &new_head, "push", message)?;
+
+        Ok(ReceiveSummary {
+            old_head,
+            new_head: new_head.clone(),
+            imported_nodes,
+            imported_blobs,
+            forced: !fast_forward,
+        })
+    })
+}
+
+// HTTP 원격(http://host:port/<repo>) 클라이언트
+pub(crate) struct HttpRemote {
+    base_url: String,
+    token: Option<String>,
+}
+
+impl HttpRemote {
+    pub(crate) fn new(url: &str, token: Option<String>) -> Self {
+        HttpRemote {
+            base_url: url.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+
+    pub(crate) fn advertise(&self) -> Result<SyncAdvertisement> {
+        let request = self.request("GET", SYNC_ADVERTISE_PATH);
+        let body = read_response(request.call())?;
+        decode_advertisement(&body)
+    }
+
+    pub(crate) fn upload_pack(&self, want: &str, haves: &[NodeId]) -> Result<ObjectPack> {
+        let request = self.request("POST", SYNC_UPLOAD_PACK_PATH);
+        let body = read_response(request.send_bytes(encode_fetch_request(want, haves).as_bytes()))?;
+        decode_pack(&body)
+    }
+
+    pub(crate) fn receive_pack(&self, pack: &ObjectPack, force: bool) -> Result<ReceiveSummary> {
+        let mut request = self.request("POST", SYNC_RECEIVE_PACK_PATH);
+        if force {
+            request = request.query("force", "1");
+        }
+        let body = read_response(request.send_bytes(&encode_pack(pack)))?;
+        decode_receive_summary(&body)
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        let request = ureq::request(method, &format!("{}/{}", self.base_url, path));
+        match &self.token {
+            Some(token) => request.set("Authorization", &format!("Bearer {}", token)),
+            None => request,
+        }
+    }
+}
+
+fn read_response(response: std::result::Result<ureq::Response, ureq::Error>) -> Result<Vec<u8>> {
+    match response {
+        Ok(response) => {
+            let mut body = Vec::new();
+            response.into_reader().read_to_end(&mut body)?;
+            Ok(body)
+        }
+        Err(ureq::Error::Status(status, response)) => {
+            let message = response
+                .into_string()
+                .unwrap_or_else(|_| format!("status {}", status));
+            Err(status_error(status, message))
+        }
+        Err(e) => Err(WorkSpaceError::Io(std::io::Error::other(e.to_string()))),
+    }
+}
+
+// 서버 응답 코드 -> 에러. 서버는 같은 규칙으로 에러를 상태 코드로 바꾼다(sync_status_code)
+fn status_error(status: u16, message: String) -> WorkSpaceError {
+    match status {
+        409 => WorkSpaceError::NonFastForward(message),
+        423 => WorkSpaceError::RepoLocked(std::path::PathBuf::from(message)),
+        400 | 413 => WorkSpaceError::InvalidInput(message),
+        404 => WorkSpaceError::NotFound(message),
+        401 | 403 => WorkSpaceError::AccessDenied(message),
+        _ => WorkSpaceError::Io(std::io::Error::other(message)),
+    }
+}
+
+// 서버가 에러를 HTTP 상태 코드로 바꿀 때 쓰는 규칙
+pub fn sync_status_code(error: &WorkSpaceError) -> u16 {
+    match error {
+        WorkSpaceError::NonFastForward(_) => 409,
+        WorkSpaceError::RepoLocked(_) => 423,
+        WorkSpaceError::AccessDenied(_) => 401,
+        // 서버 쪽 암호화 저장소가 잠겨 있거나 평문 내보내기를 막아 둔 경우
+        WorkSpaceError::PlaintextExportDenied | WorkSpaceError::KeyRequired(_) => 403,
+        WorkSpaceError::InvalidInput(_)
+        | WorkSpaceError::CorruptData(_)
+        | WorkSpaceError::InvalidRevision { .. } => 400,
+        WorkSpaceError::NotFound(_)
+        | WorkSpaceError::NodeNotFound(_)
+        | WorkSpaceError::SnapshotThinned(_)
+        | WorkSpaceError::NotARepository(_) => 404,
+        _ => 500,
+    }
+}
+
+fn encode_advertisement(advertisement: &SyncAdvertisement) -> String {
+    let mut out = format!("head {}\n", advertisement.head.as_deref().unwrap_or("-"));
+    for id in &advertisement.nodes {
+        out.push_str(id);
+        out.push('\n');
+    }
+    out
+}
+
+fn decode_advertisement(body: &[u8]) -> Result<SyncAdvertisement> {
+    let text = std::str::from_utf8(body).map_err(|_| invalid_request("invalid utf-8"))?;
+    let mut lines = text.lines();
+
+    let head = match lines.next().and_then(|l| l.strip_prefix("head ")) {
+        Some("-") => None,
+        Some(id) => Some(id.to_string()),
+        None => return Err(invalid_request("missing head line")),
+    };
+    let nodes = lines
+        .filter(|l| !l.is_empty())
+        .map(ToString::to_string)
+        .collect();
+
+    Ok(SyncAdvertisement { head, nodes })
+}
+
+fn encode_fetch_request(want: &str, haves: &[NodeId]) -> String {
+    let mut out = format!("want {}\n", want);
+    for id in haves {
+        out.push_str("have ");
+        out.push_str(id);
+        out.push('\n');
+    }
+    out
+}
+
+fn decode_fetch_request(body: &[u8]) -> Result<(NodeId, Vec<NodeId>)> {
+    let text = std::str::from_utf8(body).map_err(|_| invalid_request("invalid utf-8"))?;
+
+    let mut want = None;
+    let mut haves = Vec::new();
+    for line in text.lines().filter(|l| !l.is_empty()) {
+        if let Some(id) = line.strip_prefix("want ") {
+            want = Some(id.to_string());
+        } else if let Some(id) = line.strip_prefix("have ") {
+            haves.push(id.to_string());
+        } else {
+            return Err(invalid_request(&format!("unexpected line: {}", line)));
+        }
+    }
+
+    let want = want.ok_or_else(|| invalid_request("missing want line"))?;
+    Ok((want, haves))
+}
+
+fn encode_receive_summary(summary: &ReceiveSummary) -> String {
+    format!(
+        "old {}\nnew {}\nnodes {}\nblobs {}\nforced {}\n",
+        summary.old_head.as_deref().unwrap_or("-"),
+        summary.new_head,
+        summary.imported_nodes,
+        summary.imported_blobs,
+        summary.forced
+    )
+}
+
+fn decode_receive_summary(body: &[u8]) -> Result<ReceiveSummary> {
+    let text = std::str::from_utf8(body).map_err(|_| invalid_request("invalid utf-8"))?;
+
+    let field = |name: &str| {
+        text.lines()
+            .find_map(|l| l.strip_prefix(name)?.strip_prefix(' '))
+            .ok_or_else(|| invalid_request(&format!("missing {} line", name)))
+    };
+    let count = |name: &str| {
+        field(name)?
+            .parse::<usize>()
+            .map_err(|_| invalid_request(&format!("invalid {} line", name)))
+    };
+
+    Ok(ReceiveSummary {
+        old_head: match field("old")? {
+            "-" => None,
+            id => Some(id.to_string()),
+        },
+        new_head: field("new")?.to_string(),
+        imported_nodes: count("nodes")?,
+        imported_blobs: count("blobs")?,
+        forced: field("forced")? == "true",
+    })
+}
+
+fn invalid_request(reason: &str) -> WorkSpaceError {
+    WorkSpaceError::CorruptData(format!("invalid sync message: {}", reason))
+}
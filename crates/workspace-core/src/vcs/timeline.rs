@@ -0,0 +1,174 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use diesel::prelude::*;
+
+use crate::schema::node_parents::dsl as node_parents_dsl;
+use crate::schema::node_stats::dsl as stats_dsl;
+use crate::schema::nodes::dsl as nodes_dsl;
+use crate::vcs::autosave::format_utc_minutes;
+use crate::vcs::db::to_db;
+use crate::vcs::diff::is_probably_binary;
+use crate::vcs::objects::BlobStore;
+use crate::vcs::repo::current_head;
+use crate::vcs::repository::Repository;
+use crate::{NodeId, ProgressBucket, ProgressPoint, Result, WorkSpaceError};
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+// 1970-01-01은 목요일이다. 월요일 시작 주로 맞추려고 3일 당긴다
+const WEEK_OFFSET_MS: i64 = 3 * DAY_MS;
+
+impl Repository {
+    // HEAD에서 첫 부모만 따라간 노드들의 원고 크기를 UTC 일/주 단위로 묶는다(오래된 구간부터).
+    // 각 구간의 값은 그 구간 마지막 노드의 단어/글자 수
+    pub fn progress_series(&self, bucket: ProgressBucket) -> Result<Vec<ProgressPoint>> {
+        let root = self.root();
+        let conn = &mut *self.conn()?;
+
+        let Some(head) = current_head(conn)? else {
+            return Ok(Vec::new());
+        };
+
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        let mut next = Some(head);
+        while let Some(id) = next {
+            if !seen.insert(id.clone()) {
+                break;
+            }
+            next = node_parents_dsl::node_parents
+                .filter(node_parents_dsl::node_id.eq(&id))
+                .filter(node_parents_dsl::ord.eq(0))
+                .select(node_parents_dsl::parent_id)
+                .first::<String>(conn)
+                .optional()
+                .map_err(to_db)?;
+            chain.push(id);
+        }
+
+        let created_at = nodes_dsl::nodes
+            .filter(nodes_dsl::id.eq_any(&chain))
+            .select((nodes_dsl::id, nodes_dsl::created_at_unix_ms))
+            .load::<(String, i64)>(conn)
+            .map_err(to_db)?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        let mut stats = stats_dsl::node_stats
+            .filter(stats_dsl::node_id.eq_any(&chain))
+            .select((
+                stats_dsl::node_id,
+                stats_dsl::word_count,
+                stats_dsl::char_count,
+            ))
+            .load::<(String, i64, i64)>(conn)
+            .map_err(to_db)?
+            .into_iter()
+            .map(|(id, words, chars)| (id, (words, chars)))
+            .collect::<HashMap<_, _>>();
+
+        // 통계 테이블 이전의 노드는 응답을 위해서만 센다: 읽기 경로라 잠금 없이 쓰지 않는다.
+        // 잠긴 암호화 저장소라 내용을 읽을 수 없으면 그 노드들의 점은 건너뛴다
+        let missing = chain
+            .iter()
+            .filter(|id| !stats.contains_key(*id))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            match BlobStore::open(root, conn) {
+                Ok(store) => {
+                    for id in missing {
+                        // 통계 없이 솎아낸 채로 받은 노드는 셀 수 없으므로 점을 찍지 않는다
+                        let contents = match store.node_contents(conn, &id) {
+                            Err(WorkSpaceError::SnapshotThinned(_)) => continue,
+                            contents => contents?,
+                        };
+                        let counts = manuscript_counts(contents.iter().map(|(_, c)| c.as_slice()));
+                        stats.insert(id, counts);
+                    }
+                }
+                Err(WorkSpaceError::KeyRequired(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut points: Vec<ProgressPoint> = Vec::new();
+        // chain은 HEAD부터이므로 뒤집어 오래된 노드부터 본다
+        for id in chain.into_iter().rev() {
+            let at = created_at[&id];
+            let Some(&(words, chars)) = stats.get(&id) else {
+                continue;
+            };
+            let start = bucket_start(bucket, at);
+
+            match points.last_mut() {
+                Some(last) if last.bucket_start_unix_ms == start => {
+                    last.node_id = id;
+                    last.words = words;
+                    last.chars = chars;
+                    last.snapshots += 1;
+                }
+                _ => points.push(ProgressPoint {
+                    bucket_start_unix_ms: start,
+                    date: format_utc_minutes(start)[..10].to_string(),
+                    node_id: id,
+                    words,
+                    chars,
+                    snapshots: 1,
+                }),
+            }
+        }
+
+        Ok(points)
+    }
+}
+
+pub fn progress_series(root: &Path, bucket: ProgressBucket) -> Result<Vec<ProgressPoint>> {
+    Repository::open(root)?.progress_series(bucket)
+}
+
+// 스냅샷 전체의 (단어 수, 글자 수). 바이너리 파일은 세지 않는다
+pub(crate) fn manuscript_counts<'a>(contents: impl IntoIterator<Item = &'a [u8]>) -> (i64, i64) {
+    let mut words = 0;
+    let mut chars = 0;
+    for content in contents {
+        if is_probably_binary(content) {
+            continue;
+        }
+        let Ok(text) = std::str::from_utf8(content) else {
+            continue;
+        };
+        words += text.split_whitespace().count() as i64;
+        chars += text.chars().filter(|c| !c.is_whitespace()).count() as i64;
+    }
+
+    (words, chars)
+}
+
+pub(crate) fn save_node_stats(
+    conn: &mut SqliteConnection,
+    node_id: &NodeId,
+    (words, chars): (i64, i64),
+) -> Result<()> {
+    diesel::replace_into(stats_dsl::node_stats)
+        .values((
+            stats_dsl::node_id.eq(node_id),
+            stats_dsl::word_count.eq(words),
+            stats_dsl::char_count.eq(chars),
+        ))
+        .execute(conn)
+        .map_err(to_db)?;
+
+    Ok(())
+}
+
+// 구간 시작 시각(UTC). 주는 월요일 00:00부터
+fn bucket_start(bucket: ProgressBucket, at: i64) -> i64 {
+    match bucket {
+        ProgressBucket::Day => at.div_euclid(DAY_MS) * DAY_MS,
+        ProgressBucket::Week => {
+            let week_ms = 7 * DAY_MS;
+            (at + WEEK_OFFSET_MS).div_euclid(week_ms) * week_ms - WEEK_OFFSET_MS
+        }
+    }
+}
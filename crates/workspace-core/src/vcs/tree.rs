@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use diesel::prelude::*;
+
+use crate::vcs::db::to_db;
+use crate::{Result, WorkSpaceError};
+
+enum TreeEntry {
+    Blob(String),
+    Tree(BTreeMap<String, TreeEntry>),
+}
+
+// (경로, blob id) 목록의 Merkle 트리 해시.
+// 디렉토리마다 자식을 이름순으로 해시하므로 입력 순서와 무관하고,
+// 내용이 같은 스냅샷(또는 하위 디렉토리)은 언제나 같은 id를 갖는다.
+// 같은 경로가 두 번 나오거나 한 경로가 파일이면서 디렉토리이면(입력 순서와 상관없이) CorruptData
+pub(crate) fn tree_id_for_entries<'a>(
+    entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<String> {
+    let mut root = BTreeMap::new();
+
+    for (path, blob_id) in entries {
+        let mut dir = &mut root;
+        let mut parts = path.split('/').peekable();
+
+        while let Some(part) = parts.next() {
+            if parts.peek().is_none() {
+                if dir.contains_key(part) {
+                    return Err(conflicting_path(path));
+                }
+                dir.insert(part.to_string(), TreeEntry::Blob(blob_id.to_string()));
+                break;
+            }
+
+            let entry = dir
+                .entry(part.to_string())
+                .or_insert_with(|| TreeEntry::Tree(BTreeMap::new()));
+            dir = match entry {
+                TreeEntry::Tree(children) => children,
+                TreeEntry::Blob(_) => return Err(conflicting_path(path)),
+            };
+        }
+    }
+
+    Ok(hash_tree(&root))
+}
+
+fn conflicting_path(path: &str) -> WorkSpaceError {
+    WorkSpaceError::CorruptData(format!(
+        "tree has a path that is both a file and a directory (or appears twice): {}",
+        path
+    ))
+}
+
+fn hash_tree(children: &BTreeMap<String, TreeEntry>) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"tree\n");
+
+    for (name, entry) in children {
+        let (kind, id) = match entry {
+            TreeEntry::Blob(blob_id) => ("blob", blob_id.clone()),
+            TreeEntry::Tree(grandchildren) => ("tree", hash_tree(grandchildren)),
+        };
+        hasher.update(kind.as_bytes());
+        hasher.update(b" ");
+        hasher.update(name.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(id.as_bytes());
+        hasher.update(b"\n");
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+// 노드의 트리 id. tree_id 컬럼이 생기기 전에 만든 노드는 파일 목록으로 계산한다(읽기 경로라 저장하지 않는다)
+pub(crate) fn node_tree_id(conn: &mut SqliteConnection, node_id: &str) -> Result<String> {
+    use crate::schema::node_files::dsl as node_files_dsl;
+    use crate::schema::nodes::dsl as nodes_dsl;
+
+    let stored = nodes_dsl::nodes
+        .filter(nodes_dsl::id.eq(node_id))
+        .select(nodes_dsl::tree_id)
+        .first::<Option<String>>(conn)
+        .map_err(to_db)?;
+
+    if let Some(tree_id) = stored {
+        return Ok(tree_id);
+    }
+
+    let files = node_files_dsl::node_files
+        .filter(node_files_dsl::node_id.eq(node_id))
+        .select((node_files_dsl::path, node_files_dsl::blob_id))
+        .load::<(String, String)>(conn)
+        .map_err(to_db)?;
+
+    tree_id_for_entries(files.iter().map(|(p, b)| (p.as_str(), b.as_str())))
+}
+
+// node_tree_id와 같지만 계산한 값을 nodes에 채워 둔다. 저장소 잠금을 잡은 쓰기 경로에서만 부른다
+pub(crate) fn backfill_tree_id(conn: &mut SqliteConnection, node_id: &str) -> Result<String> {
+    use crate::schema::nodes::dsl as nodes_dsl;
+
+    let tree_id = node_tree_id(conn, node_id)?;
+    diesel::update(
+        nodes_dsl::nodes
+            .filter(nodes_dsl::id.eq(node_id))
+            .filter(nodes_dsl::tree_id.is_null()),
+    )
+    .set(nodes_dsl::tree_id.eq(&tree_id))
+    .execute(conn)
+    .map_err(to_db)?;
+
+    Ok(tree_id)
+}
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 // 노드 ID 타입 별칭(type alias). 현재는 String이지만 나중에 교체하기 쉽다.
 pub type NodeId = String;
@@ -14,6 +14,142 @@ pub struct VersionNode {
     pub message: String,
     // 생성 시각(ms, Unix epoch 기준)
     pub created_at_unix_ms: i64,
+    // 직접 만든 스냅샷인지 자동 저장인지
+    pub kind: NodeKind,
+    // 나중에 붙인 메모(붙인 순서대로)
+    pub notes: Vec<NodeNote>,
+}
+
+// 노드에 나중에 붙인 메모(git notes와 비슷). 고쳐도 노드 id는 바뀌지 않는다
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, diesel::Queryable)]
+pub struct NodeNote {
+    pub id: i32,
+    pub node_id: NodeId,
+    pub text: String,
+    pub created_at_unix_ms: i64,
+    pub updated_at_unix_ms: i64,
+}
+
+// ObjectStore 백엔드가 저장하는 노드 메타데이터. 파일 목록(트리)은 따로 둔다
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct StoredNode {
+    pub id: NodeId,
+    pub parents: Vec<NodeId>,
+    pub message: String,
+    pub created_at_unix_ms: i64,
+    pub tree_id: String,
+    pub kind: NodeKind,
+}
+
+// 노드 종류. nodes.kind 컬럼에 문자열로 저장된다
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    #[default]
+    Manual,
+    Autosave,
+}
+
+impl NodeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NodeKind::Manual => "manual",
+            NodeKind::Autosave => "autosave",
+        }
+    }
+
+    // 모르는 값은 지우면 안 되는 쪽(manual)으로 본다
+    pub(crate) fn from_db(value: &str) -> NodeKind {
+        match value {
+            "autosave" => NodeKind::Autosave,
+            _ => NodeKind::Manual,
+        }
+    }
+}
+
+// 자동 저장 정책
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct AutosavePolicy {
+    // 마지막 스냅샷(HEAD) 이후 이만큼 지나야 자동 저장한다
+    pub interval_ms: i64,
+    // 작업 트리가 HEAD와 같으면 건너뛴다
+    pub only_when_changed: bool,
+    // 메시지 템플릿: {time}은 UTC 시각(YYYY-MM-DD HH:MM), {files}는 바뀐 파일 수
+    pub message_template: String,
+}
+
+impl Default for AutosavePolicy {
+    fn default() -> Self {
+        AutosavePolicy {
+            interval_ms: 10 * 60 * 1000,
+            only_when_changed: true,
+            message_template: "autosave {time} ({files} files)".to_string(),
+        }
+    }
+}
+
+// thin_autosaves 결과
+#[derive(Debug, Clone, Serialize)]
+pub struct ThinSummary {
+    pub removed_nodes: Vec<NodeId>,
+    // 남는 노드의 조상이라 지우지 못하고 파일 목록만 지운 노드
+    pub thinned_nodes: Vec<NodeId>,
+    pub kept_autosaves: usize,
+}
+
+// gc 결과
+#[derive(Debug, Clone, Serialize)]
+pub struct GcSummary {
+    pub removed_blobs: usize,
+    // 지운 .novel/objects 파일 수
+    pub removed_objects: usize,
+}
+
+// init_repo_with_options 옵션
+#[derive(Clone, Default)]
+pub struct InitOptions {
+    // 있으면 blob 내용과 커밋 메시지를 이 암호에서 만든 키로 암호화한다. 이미 커밋이 있어도 된다
+    pub passphrase: Option<String>,
+}
+
+// commit_with_options 옵션
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommitOptions {
+    // 작업 트리가 HEAD와 같아도 노드를 만든다
+    pub allow_empty: bool,
+}
+
+// graph_layout 결과: 위에서 아래로 그릴 행들
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphLayout {
+    pub rows: Vec<GraphRow>,
+    // 전체 그래프에 필요한 열 수
+    pub lane_count: usize,
+}
+
+// 그래프 한 행. 열 번호는 0부터, 왼쪽부터 센다
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphRow {
+    pub node: VersionNode,
+    // 노드가 놓인 열
+    pub lane: usize,
+    // 위에서 이 노드로 모이는 선의 열(자식들이 기다리던 열)
+    pub incoming: Vec<usize>,
+    // 이 행을 세로로 지나가는 다른 노드의 선
+    pub pass_through: Vec<usize>,
+    // 노드에서 아래로 내려가는 부모 선의 열 (parents와 같은 순서)
+    pub parent_lanes: Vec<usize>,
+    // 이 행에서 쓰는 열 수
+    pub lane_count: usize,
+}
+
+// ahead_behind 결과
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct AheadBehind {
+    // 첫 번째 노드에만 있는 노드 수
+    pub ahead: usize,
+    // 두 번째 노드에만 있는 노드 수
+    pub behind: usize,
 }
 
 // 저장소 요약 상태. UI에서 빠르게 상태 표시할 때 사용
@@ -33,6 +169,55 @@ pub enum DiffKind {
     Modified,
 }
 
+// pickaxe 결과: node_id에서 path 안의 phrase 개수가 before_count -> after_count로 바뀌었다.
+// 병합 노드의 before_count는 첫 부모 기준
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct PickaxeHit {
+    pub node_id: NodeId,
+    pub path: String,
+    pub before_count: usize,
+    pub after_count: usize,
+}
+
+// progress_series의 묶음 단위(UTC). 주는 월요일에 시작한다
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressBucket {
+    Day,
+    Week,
+}
+
+// 한 구간의 원고 크기: 구간 마지막 노드(node_id) 시점의 단어 수와 글자 수(공백 제외).
+// snapshots는 구간 안에 든 노드 수
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ProgressPoint {
+    pub bucket_start_unix_ms: i64,
+    // 구간 시작일 "YYYY-MM-DD"(UTC)
+    pub date: String,
+    pub node_id: NodeId,
+    pub words: i64,
+    pub chars: i64,
+    pub snapshots: usize,
+}
+
+// search_history 결과: 같은 내용(blob)이 같은 경로로 들어 있는 노드들
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SearchHit {
+    pub path: String,
+    pub blob_id: String,
+    // 처음 일치한 곳 앞뒤 문맥(한 줄)
+    pub snippet: String,
+    // 최신 노드부터
+    pub nodes: Vec<NodeId>,
+}
+
+// staging 영역에 올려 둔 변경 하나. kind는 HEAD와 비교한 것
+#[derive(Debug, Clone, serde::Serialize, PartialEq, Eq)]
+pub struct StagedFile {
+    pub path: String,
+    pub kind: DiffKind,
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct FileDiff {
     pub path: String,
@@ -49,3 +234,124 @@ pub struct NodeDiff {
     pub to: String,
     pub files: Vec<FileDiff>,
 }
+
+// diff_summary의 파일 한 개. 내용 없이 크기와 단어 수만 담는다
+#[derive(Debug, Clone, serde::Serialize, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: String,
+    pub kind: DiffKind,
+    // 바이트 수. 없는 쪽은 None
+    pub before_size: Option<i64>,
+    pub after_size: Option<i64>,
+    // 공백으로 나눈 단어 수. 없는 쪽이나 바이너리는 None
+    pub before_words: Option<i64>,
+    pub after_words: Option<i64>,
+    // after_words - before_words (없으면 0으로 본다)
+    pub word_delta: i64,
+    pub is_binary: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DiffSummary {
+    pub from: String,
+    pub to: String,
+    pub files: Vec<FileChange>,
+}
+
+// 노드에 붙인 이름. refs 테이블에 "tags/<이름>"으로 저장된다
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Tag {
+    pub name: String,
+    pub node_id: NodeId,
+}
+
+// 번들에 기록되는 이름 -> 노드 참조
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct BundleRef {
+    pub name: String,
+    pub node_id: NodeId,
+}
+
+// bundle_create 결과 요약
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleSummary {
+    pub refs: Vec<BundleRef>,
+    // 번들에 담기지 않아 받는 쪽 저장소에 미리 있어야 하는 노드
+    pub prerequisites: Vec<NodeId>,
+    pub node_count: usize,
+    pub blob_count: usize,
+}
+
+// bundle_unbundle 결과 요약. 이미 있던 객체는 imported_* 에 세지 않는다
+#[derive(Debug, Clone, Serialize)]
+pub struct UnbundleSummary {
+    pub refs: Vec<BundleRef>,
+    pub imported_nodes: usize,
+    pub imported_blobs: usize,
+}
+
+// 등록된 원격 저장소. head는 마지막 fetch/push 때 본 원격 HEAD(원격 추적 head)
+#[derive(Debug, Clone, Serialize)]
+pub struct Remote {
+    pub name: String,
+    pub url: String,
+    pub head: Option<NodeId>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchSummary {
+    pub remote: String,
+    pub head: Option<NodeId>,
+    pub imported_nodes: usize,
+    pub imported_blobs: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PushSummary {
+    pub remote: String,
+    pub old_head: Option<NodeId>,
+    pub new_head: NodeId,
+    pub pushed_nodes: usize,
+    pub pushed_blobs: usize,
+    pub forced: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PullSummary {
+    pub remote: String,
+    pub old_head: Option<NodeId>,
+    pub new_head: Option<NodeId>,
+    pub imported_nodes: usize,
+    pub imported_blobs: usize,
+}
+
+// 동기화 상대가 알려주는 HEAD와 HEAD에서 도달 가능한 노드 목록
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncAdvertisement {
+    pub head: Option<NodeId>,
+    pub nodes: Vec<NodeId>,
+}
+
+// push를 받은 쪽의 처리 결과. forced는 fast-forward가 아니었는데 덮어쓴 경우 true
+#[derive(Debug, Clone, Serialize)]
+pub struct ReceiveSummary {
+    pub old_head: Option<NodeId>,
+    pub new_head: NodeId,
+    pub imported_nodes: usize,
+    pub imported_blobs: usize,
+    pub forced: bool,
+}
+
+// HEAD(또는 원격 추적 head 같은 ref)가 한 번 움직인 기록
+#[derive(Debug, Clone, Serialize, diesel::Queryable)]
+pub struct ReflogEntry {
+    pub id: i32,
+    // "HEAD" 또는 "remotes/<원격>/HEAD"
+    pub ref_name: String,
+    pub old_node_id: Option<NodeId>,
+    pub new_node_id: Option<NodeId>,
+    // commit, checkout, pull, undo, fetch, push ...
+    pub reason: String,
+    pub message: String,
+    pub created_at_unix_ms: i64,
+}
@@ -0,0 +1,343 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use diesel::prelude::*;
+use workspace_core::schema::{blobs, head, node_parents, nodes, reflog};
+use workspace_core::{
+    AutosavePolicy, NodeKind, Result, WorkSpaceError, autosave, bundle_create, bundle_unbundle,
+    checkout, commit, gc, init_repo, log, read_file, tag_create, thin_autosaves, write_file,
+};
+
+const HOUR_MS: i64 = 60 * 60 * 1000;
+const DAY_MS: i64 = 24 * HOUR_MS;
+const WEEK_MS: i64 = 7 * DAY_MS;
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+fn connect(root: &std::path::Path) -> SqliteConnection {
+    let db = root.join(".novel").join("vcs.db");
+    SqliteConnection::establish(db.to_str().unwrap()).unwrap()
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+// 시각을 마음대로 정해야 하므로 노드는 DB에 직접 넣는다.
+// (id, 종류, 생성 시각, 부모들)
+fn insert_nodes(root: &std::path::Path, graph: &[(&str, NodeKind, i64, &[&str])]) {
+    let mut conn = connect(root);
+
+    for (id, kind, created_at, parents) in graph {
+        diesel::insert_into(nodes::table)
+            .values((
+                nodes::id.eq(*id),
+                nodes::message.eq(*id),
+                nodes::created_at_unix_ms.eq(*created_at),
+                nodes::kind.eq(kind.as_str()),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+
+        for (ord, parent) in parents.iter().enumerate() {
+            diesel::insert_into(node_parents::table)
+                .values((
+                    node_parents::node_id.eq(*id),
+                    node_parents::parent_id.eq(*parent),
+                    node_parents::ord.eq(ord as i32),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+        }
+    }
+}
+
+fn policy(interval_ms: i64, only_when_changed: bool) -> AutosavePolicy {
+    AutosavePolicy {
+        interval_ms,
+        only_when_changed,
+        ..AutosavePolicy::default()
+    }
+}
+
+#[test]
+fn autosave_skips_unchanged_tree() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    write_file(&root, "a.txt", "one")?;
+
+    let first = autosave(&root, &policy(0, true))?.expect("first autosave");
+    assert_eq!(autosave(&root, &policy(0, true))?, None);
+
+    let nodes = log(&root)?;
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(nodes[0].id, first);
+    assert_eq!(nodes[0].kind, NodeKind::Autosave);
+    assert!(nodes[0].message.starts_with("autosave "));
+    assert!(nodes[0].message.ends_with("(1 files)"));
+
+    // 바뀐 게 없어도 정책이 허락하면 저장한다
+    let forced = autosave(&root, &policy(0, false))?.expect("forced autosave");
+    assert_eq!(log(&root)?[0].id, forced);
+
+    Ok(())
+}
+
+#[test]
+fn autosave_waits_for_interval() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    write_file(&root, "a.txt", "one")?;
+    commit(&root, "manual")?;
+
+    write_file(&root, "a.txt", "two")?;
+    write_file(&root, "b.txt", "new")?;
+    assert_eq!(autosave(&root, &AutosavePolicy::default())?, None);
+
+    let saved = autosave(
+        &root,
+        &AutosavePolicy {
+            message_template: "saved {files}".to_string(),
+            ..policy(0, true)
+        },
+    )?
+    .expect("autosave after interval");
+
+    let nodes = log(&root)?;
+    assert_eq!(nodes[0].id, saved);
+    assert_eq!(nodes[0].message, "saved 2");
+    assert_eq!(nodes[1].kind, NodeKind::Manual);
+
+    Ok(())
+}
+
+#[test]
+fn thinning_keeps_newest_per_bucket_and_protected_nodes() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+
+    let now = now_ms();
+    // 60일 전이 속한 주, 2시간 전이 속한 시간 구간의 시작
+    let week = (now - 60 * DAY_MS).div_euclid(WEEK_MS) * WEEK_MS;
+    let hour = (now - 2 * HOUR_MS).div_euclid(HOUR_MS) * HOUR_MS;
+
+    insert_nodes(
+        &root,
+        &[
+            ("0000", NodeKind::Manual, week - DAY_MS, &[]),
+            ("aaa1", NodeKind::Autosave, week + HOUR_MS, &["0000"]),
+            ("aaa2", NodeKind::Autosave, week + 2 * HOUR_MS, &["aaa1"]),
+            ("aaa3", NodeKind::Autosave, week + 3 * HOUR_MS, &["aaa2"]),
+            // aaa1에서 갈라져 버려진 가지
+            (
+                "xxx1",
+                NodeKind::Autosave,
+                week + HOUR_MS + 60_000,
+                &["aaa1"],
+            ),
+            (
+                "xxx2",
+                NodeKind::Autosave,
+                week + HOUR_MS + 120_000,
+                &["xxx1"],
+            ),
+            ("bbb1", NodeKind::Autosave, hour + 60_000, &["aaa3"]),
+            ("bbb2", NodeKind::Autosave, hour + 120_000, &["bbb1"]),
+            ("cccc", NodeKind::Manual, hour + 180_000, &["bbb2", "aaa2"]),
+        ],
+    );
+    tag_create(&root, "draft", "aaa1")?;
+    diesel::update(head::table)
+        .set(head::node_id.eq("bbb1"))
+        .execute(&mut connect(&root))
+        .unwrap();
+
+    // aaa2는 솎을 대상이지만 남는 aaa3, cccc의 부모라서 지우지 않고 파일 목록만 지운다
+    let summary = thin_autosaves(&root)?;
+    assert_eq!(
+        summary.removed_nodes,
+        vec!["xxx2".to_string(), "xxx1".to_string()]
+    );
+    assert_eq!(summary.thinned_nodes, vec!["aaa2".to_string()]);
+    assert_eq!(summary.kept_autosaves, 4);
+
+    let parents = |id: &str| {
+        log(&root)
+            .unwrap()
+            .into_iter()
+            .find(|n| n.id == id)
+            .map(|n| n.parents)
+    };
+    // log는 솎아낸 aaa2 대신 그 부모 aaa1을 부모로 보여 준다
+    assert_eq!(parents("xxx1"), None);
+    assert_eq!(parents("aaa2"), None);
+    assert_eq!(parents("aaa3"), Some(vec!["aaa1".to_string()]));
+    assert_eq!(
+        parents("cccc"),
+        Some(vec!["bbb2".to_string(), "aaa1".to_string()])
+    );
+
+    // 다시 솎아도 바뀌지 않는다
+    let again = thin_autosaves(&root)?;
+    assert!(again.removed_nodes.is_empty());
+    assert!(again.thinned_nodes.is_empty());
+    assert_eq!(again.kept_autosaves, 4);
+
+    Ok(())
+}
+
+#[test]
+fn thinning_never_rewrites_parents_of_kept_nodes() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+
+    let hour = (now_ms() - 3 * HOUR_MS).div_euclid(HOUR_MS) * HOUR_MS;
+    insert_nodes(
+        &root,
+        &[
+            ("s1", NodeKind::Autosave, hour + 60_000, &[]),
+            ("s2", NodeKind::Autosave, hour + 120_000, &["s1"]),
+            ("s3", NodeKind::Autosave, hour + 180_000, &["s2"]),
+            ("m", NodeKind::Manual, hour + 240_000, &["s3"]),
+            ("t1", NodeKind::Autosave, hour + 130_000, &["s1"]),
+            ("t2", NodeKind::Autosave, hour + 140_000, &["t1"]),
+        ],
+    );
+    // HEAD가 t2에 들렀다가 m으로 옮겨 간 기록
+    let mut conn = connect(&root);
+    for (old, new) in [("s3", "t2"), ("t2", "m")] {
+        diesel::insert_into(reflog::table)
+            .values((
+                reflog::ref_name.eq("HEAD"),
+                reflog::old_node_id.eq(old),
+                reflog::new_node_id.eq(new),
+                reflog::reason.eq("checkout"),
+                reflog::message.eq(""),
+                reflog::created_at_unix_ms.eq(hour + 200_000),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+    }
+
+    let summary = thin_autosaves(&root)?;
+    assert_eq!(
+        summary.removed_nodes,
+        vec!["t2".to_string(), "t1".to_string()]
+    );
+    assert_eq!(
+        summary.thinned_nodes,
+        vec!["s2".to_string(), "s1".to_string()]
+    );
+    assert_eq!(summary.kept_autosaves, 1);
+
+    let nodes = log(&root)?;
+    let ids = nodes.iter().map(|n| n.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["m", "s3"]);
+    assert!(nodes[1].parents.is_empty());
+
+    // DB의 부모 목록은 그대로다
+    let s3_parents = node_parents::table
+        .filter(node_parents::node_id.eq("s3"))
+        .select(node_parents::parent_id)
+        .load::<String>(&mut conn)
+        .unwrap();
+    assert_eq!(s3_parents, vec!["s2".to_string()]);
+
+    // 지운 노드로 가는 기록은 사라지고, 지운 노드에서 떠난 기록은 출발점이 비워진다
+    let moves = reflog::table
+        .select((reflog::old_node_id, reflog::new_node_id))
+        .load::<(Option<String>, Option<String>)>(&mut conn)
+        .unwrap();
+    assert_eq!(moves, vec![(None, Some("m".to_string()))]);
+
+    Ok(())
+}
+
+#[test]
+fn thinning_frees_files_of_autosaves_on_linear_history() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    // 자동 저장 셋이 같은 시간 구간에 들어가야 한다
+    let into_hour = now_ms().rem_euclid(HOUR_MS);
+    if into_hour > HOUR_MS - 10_000 {
+        std::thread::sleep(std::time::Duration::from_millis(
+            (HOUR_MS - into_hour + 100) as u64,
+        ));
+    }
+
+    let mut saved = Vec::new();
+    for text in ["one", "two", "three"] {
+        write_file(&root, "a.txt", text)?;
+        saved.push(autosave(&root, &policy(0, true))?.expect("autosave"));
+    }
+    write_file(&root, "a.txt", "four")?;
+    commit(&root, "manual")?;
+
+    // 모두 HEAD의 조상이라 지울 수 있는 노드는 없지만, 가장 최근 것 말고는 파일 목록을 잃는다
+    let summary = thin_autosaves(&root)?;
+    assert!(summary.removed_nodes.is_empty());
+    let mut thinned = summary.thinned_nodes.clone();
+    thinned.sort();
+    let mut expected = vec![saved[0].clone(), saved[1].clone()];
+    expected.sort();
+    assert_eq!(thinned, expected);
+    assert_eq!(summary.kept_autosaves, 1);
+
+    let nodes = log(&root)?;
+    let ids = nodes.iter().map(|n| n.id.clone()).collect::<Vec<_>>();
+    assert_eq!(ids[1..], [saved[2].clone()]);
+    assert!(nodes[1].parents.is_empty());
+
+    // "one", "two"는 이제 어떤 노드도 쓰지 않는다
+    assert_eq!(gc(&root)?.removed_blobs, 2);
+
+    // 솎아낸 노드는 체크아웃하지 않고, 작업 트리도 건드리지 않는다
+    assert!(matches!(
+        checkout(&root, &saved[0]),
+        Err(WorkSpaceError::SnapshotThinned(id)) if id == saved[0]
+    ));
+    assert_eq!(read_file(&root, "a.txt")?, "four");
+
+    // 번들로 옮겨도 노드 id가 그대로 검증된다
+    let bundle_dir = tempfile::tempdir().unwrap();
+    let bundle_path = bundle_dir.path().join("history.bundle");
+    bundle_create(&root, &bundle_path, &["HEAD"])?;
+    let (_other_td, other) = setup();
+    init_repo(&other)?;
+    assert_eq!(bundle_unbundle(&other, &bundle_path)?.imported_nodes, 4);
+    let other_ids = log(&other)?.into_iter().map(|n| n.id).collect::<Vec<_>>();
+    assert_eq!(other_ids, ids);
+
+    Ok(())
+}
+
+#[test]
+fn gc_removes_unreferenced_blobs() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    write_file(&root, "a.txt", "kept")?;
+    commit(&root, "manual")?;
+
+    diesel::insert_into(blobs::table)
+        .values((blobs::id.eq("orphan"), blobs::content.eq(b"gone".to_vec())))
+        .execute(&mut connect(&root))
+        .unwrap();
+
+    assert_eq!(gc(&root)?.removed_blobs, 1);
+    assert_eq!(gc(&root)?.removed_blobs, 0);
+
+    let remaining = blobs::table
+        .select(blobs::id)
+        .load::<String>(&mut connect(&root))
+        .unwrap();
+    assert_eq!(remaining.len(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,331 @@
+use diesel::prelude::*;
+use workspace_core::schema::{node_files, node_stats};
+use workspace_core::{
+    AutosavePolicy, NodeKind, Result, autosave, bundle_create, bundle_create_since,
+    bundle_unbundle, checkout, commit, init_repo, log,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+#[test]
+fn bundle_roundtrip_restores_history_in_fresh_repo() -> Result<()> {
+    let (_src_td, src) = setup();
+    let (_dst_td, dst) = setup();
+    let (_out_td, out) = setup();
+    let bundle_path = out.join("book.bundle");
+
+    init_repo(&src)?;
+    std::fs::write(src.join("chapter1.txt"), "It was a dark night.")?;
+    let first = commit(&src, "chapter 1")?;
+    std::fs::create_dir(src.join("notes"))?;
+    std::fs::write(src.join("notes/lighthouse.txt"), "keeper")?;
+    let second = commit(&src, "notes")?;
+
+    let summary = bundle_create(&src, &bundle_path, &["HEAD"])?;
+    assert_eq!(summary.node_count, 2);
+    assert_eq!(summary.blob_count, 2);
+    assert!(summary.prerequisites.is_empty());
+    assert_eq!(summary.refs[0].node_id, second);
+
+    init_repo(&dst)?;
+    let imported = bundle_unbundle(&dst, &bundle_path)?;
+    assert_eq!(imported.imported_nodes, 2);
+    assert_eq!(imported.imported_blobs, 2);
+
+    let nodes = log(&dst)?;
+    let second_node = nodes.iter().find(|n| n.id == second).unwrap();
+    assert_eq!(second_node.parents, vec![first]);
+
+    checkout(&dst, &imported.refs[0].node_id)?;
+    assert_eq!(
+        std::fs::read_to_string(dst.join("notes/lighthouse.txt"))?,
+        "keeper"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn unbundle_keeps_node_kind_and_records_progress() -> Result<()> {
+    let (_src_td, src) = setup();
+    let (_dst_td, dst) = setup();
+    let (_out_td, out) = setup();
+    let bundle_path = out.join("book.bundle");
+
+    init_repo(&src)?;
+    std::fs::write(src.join("ch1.md"), "hello world")?;
+    let saved = autosave(
+        &src,
+        &AutosavePolicy {
+            interval_ms: 0,
+            ..AutosavePolicy::default()
+        },
+    )?
+    .expect("autosave");
+    std::fs::write(src.join("ch2.md"), "and again")?;
+    let manual = commit(&src, "manual")?;
+    bundle_create(&src, &bundle_path, &["HEAD"])?;
+
+    init_repo(&dst)?;
+    bundle_unbundle(&dst, &bundle_path)?;
+    let mut kinds = log(&dst)?
+        .into_iter()
+        .map(|n| (n.id, n.kind))
+        .collect::<Vec<_>>();
+    kinds.sort_by_key(|(_, kind)| *kind == NodeKind::Manual);
+    assert_eq!(
+        kinds,
+        vec![
+            (saved.clone(), NodeKind::Autosave),
+            (manual.clone(), NodeKind::Manual)
+        ]
+    );
+
+    // 받을 때 센 통계가 남는다
+    let db = dst.join(".novel").join("vcs.db");
+    let mut conn = SqliteConnection::establish(db.to_str().unwrap()).unwrap();
+    let stats = node_stats::table
+        .select((node_stats::node_id, node_stats::word_count))
+        .order(node_stats::word_count)
+        .load::<(String, i64)>(&mut conn)
+        .unwrap();
+    assert_eq!(stats, vec![(saved, 2), (manual, 4)]);
+
+    Ok(())
+}
+
+#[test]
+fn unbundle_is_idempotent() -> Result<()> {
+    let (_src_td, src) = setup();
+    let (_dst_td, dst) = setup();
+    let (_out_td, out) = setup();
+    let bundle_path = out.join("book.bundle");
+
+    init_repo(&src)?;
+    std::fs::write(src.join("a.txt"), "a")?;
+    commit(&src, "first")?;
+
+    bundle_create(&src, &bundle_path, &["HEAD"])?;
+
+    bundle_unbundle(&dst, &bundle_path)?;
+    let again = bundle_unbundle(&dst, &bundle_path)?;
+
+    assert_eq!(again.imported_nodes, 0);
+    assert_eq!(again.imported_blobs, 0);
+    assert_eq!(log(&dst)?.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn incremental_bundle_skips_base_history() -> Result<()> {
+    let (_src_td, src) = setup();
+    let (_dst_td, dst) = setup();
+    let (_out_td, out) = setup();
+
+    init_repo(&src)?;
+    std::fs::write(src.join("a.txt"), "a")?;
+    let first = commit(&src, "first")?;
+
+    let full = out.join("full.bundle");
+    bundle_create(&src, &full, &[first.as_str()])?;
+
+    std::fs::write(src.join("b.txt"), "b")?;
+    let second = commit(&src, "second")?;
+
+    let incremental = out.join("incremental.bundle");
+    let summary = bundle_create_since(&src, &incremental, &["HEAD"], &first)?;
+    assert_eq!(summary.node_count, 1);
+    assert_eq!(summary.blob_count, 1);
+    assert_eq!(summary.prerequisites, vec![first.clone()]);
+
+    // base가 없는 저장소에는 증분 번들을 적용할 수 없다
+    let err = bundle_unbundle(&dst, &incremental).unwrap_err();
+    assert!(matches!(err, workspace_core::WorkSpaceError::NotFound(_)));
+    assert!(log(&dst)?.is_empty());
+
+    bundle_unbundle(&dst, &full)?;
+    bundle_unbundle(&dst, &incremental)?;
+
+    checkout(&dst, &second)?;
+    assert_eq!(std::fs::read_to_string(dst.join("a.txt"))?, "a");
+    assert_eq!(std::fs::read_to_string(dst.join("b.txt"))?, "b");
+
+    Ok(())
+}
+
+#[test]
+fn corrupted_bundle_is_rejected() -> Result<()> {
+    let (_src_td, src) = setup();
+    let (_dst_td, dst) = setup();
+    let (_out_td, out) = setup();
+    let bundle_path = out.join("book.bundle");
+
+    init_repo(&src)?;
+    std::fs::write(src.join("a.txt"), "a")?;
+    commit(&src, "first")?;
+    bundle_create(&src, &bundle_path, &["HEAD"])?;
+
+    let mut bytes = std::fs::read(&bundle_path)?;
+    let middle = bytes.len() / 2;
+    bytes[middle] ^= 0xff;
+    std::fs::write(&bundle_path, bytes)?;
+
+    let err = bundle_unbundle(&dst, &bundle_path).unwrap_err();
+    assert!(matches!(
+        err,
+        workspace_core::WorkSpaceError::CorruptData(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn bundle_with_file_and_directory_at_same_path_is_rejected() -> Result<()> {
+    let (_src_td, src) = setup();
+    let (_dst_td, dst) = setup();
+    let (_out_td, out) = setup();
+    let bundle_path = out.join("book.bundle");
+
+    init_repo(&src)?;
+    std::fs::write(src.join("notes"), "draft")?;
+    let node = commit(&src, "first")?;
+
+    // "notes"가 파일이면서 디렉토리인 트리를 만든다
+    let db = src.join(".novel").join("vcs.db");
+    let mut conn = SqliteConnection::establish(db.to_str().unwrap()).unwrap();
+    let blob_id = node_files::table
+        .filter(node_files::node_id.eq(&node))
+        .select(node_files::blob_id)
+        .first::<String>(&mut conn)
+        .unwrap();
+    diesel::insert_into(node_files::table)
+        .values((
+            node_files::node_id.eq(&node),
+            node_files::path.eq("notes/a.md"),
+            node_files::blob_id.eq(&blob_id),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+    bundle_create(&src, &bundle_path, &["HEAD"])?;
+
+    init_repo(&dst)?;
+    let err = bundle_unbundle(&dst, &bundle_path).unwrap_err();
+    assert!(matches!(
+        err,
+        workspace_core::WorkSpaceError::CorruptData(_)
+    ));
+    assert!(log(&dst)?.is_empty());
+
+    Ok(())
+}
+
+// 보낸 쪽 DB에서 노드의 파일 경로를 바꿔 만든 번들을 받는 쪽에 넣어 본다
+fn unbundle_with_renamed_path(new_path: &str) -> Result<workspace_core::WorkSpaceError> {
+    let (_src_td, src) = setup();
+    let (_dst_td, dst) = setup();
+    let (_out_td, out) = setup();
+    let bundle_path = out.join("book.bundle");
+
+    init_repo(&src)?;
+    std::fs::write(src.join("a.txt"), "a")?;
+    let node = commit(&src, "first")?;
+
+    let db = src.join(".novel").join("vcs.db");
+    let mut conn = SqliteConnection::establish(db.to_str().unwrap()).unwrap();
+    diesel::update(node_files::table.filter(node_files::node_id.eq(&node)))
+        .set(node_files::path.eq(new_path))
+        .execute(&mut conn)
+        .unwrap();
+    bundle_create(&src, &bundle_path, &["HEAD"])?;
+
+    init_repo(&dst)?;
+    let err = bundle_unbundle(&dst, &bundle_path).unwrap_err();
+    assert!(log(&dst)?.is_empty());
+
+    Ok(err)
+}
+
+#[test]
+fn bundle_with_path_outside_workspace_is_rejected() -> Result<()> {
+    for path in [
+        "../escape.txt",
+        "/tmp/escape.txt",
+        "notes/../../escape.txt",
+        "./a.txt",
+    ] {
+        let err = unbundle_with_renamed_path(path)?;
+        assert!(
+            matches!(&err, workspace_core::WorkSpaceError::CorruptData(msg) if msg.contains("unsafe path")),
+            "{}: {:?}",
+            path,
+            err
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn bundle_with_repository_metadata_path_is_rejected() -> Result<()> {
+    // 훅이나 DB를 덮어쓰게 두면 받는 쪽에서 코드가 실행된다
+    for path in [".novel/hooks/pre-snapshot", ".NOVEL/vcs.db", ".Novel"] {
+        let err = unbundle_with_renamed_path(path)?;
+        assert!(
+            matches!(&err, workspace_core::WorkSpaceError::CorruptData(msg) if msg.contains("unsafe path")),
+            "{}: {:?}",
+            path,
+            err
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn bundle_node_that_does_not_match_its_id_is_rejected() -> Result<()> {
+    // 경로는 안전하지만 트리가 바뀌었으니 id와 맞지 않는다
+    let err = unbundle_with_renamed_path("b.txt")?;
+    assert!(matches!(
+        err,
+        workspace_core::WorkSpaceError::CorruptData(msg) if msg.contains("does not match id")
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn encrypted_repository_needs_opt_in_for_plaintext_bundle() -> Result<()> {
+    let (_src_td, src) = setup();
+    let (_out_td, out) = setup();
+    let bundle_path = out.join("book.bundle");
+
+    workspace_core::init_repo_with_options(
+        &src,
+        &workspace_core::InitOptions {
+            passphrase: Some("secret".to_string()),
+        },
+    )?;
+    std::fs::write(src.join("chapter1.txt"), "It was a dark night.")?;
+    commit(&src, "chapter 1")?;
+
+    assert!(!workspace_core::plaintext_export_allowed(&src)?);
+    let err = bundle_create(&src, &bundle_path, &["HEAD"]).unwrap_err();
+    assert!(matches!(
+        err,
+        workspace_core::WorkSpaceError::PlaintextExportDenied
+    ));
+    assert!(!bundle_path.exists());
+
+    workspace_core::set_plaintext_export(&src, true)?;
+    assert_eq!(bundle_create(&src, &bundle_path, &["HEAD"])?.node_count, 1);
+    let bytes = std::fs::read(&bundle_path)?;
+    assert!(bytes.windows(10).any(|w| w == b"dark night"));
+
+    Ok(())
+}
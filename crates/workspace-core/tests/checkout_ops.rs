@@ -0,0 +1,189 @@
+use std::fs;
+
+use workspace_core::{Result, checkout, commit, init_repo, reflog, repo_state};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+// 중단된 체크아웃이 남긴 journal을 흉내 낸다: 경로마다 (write|remove, 경로).
+// committed면 HEAD를 옮긴 뒤 죽은 것이다
+fn write_journal(
+    root: &std::path::Path,
+    target: &str,
+    from: &str,
+    entries: &[(&str, &str)],
+    committed: bool,
+) -> Result<()> {
+    let dir = root.join(".novel").join("checkout");
+    fs::create_dir_all(dir.join("stage"))?;
+    fs::create_dir_all(dir.join("backup"))?;
+
+    let mut journal = format!(
+        "novel-checkout-journal v2\ntarget {}\nfrom {}\n",
+        target, from
+    );
+    for (index, (op, path)) in entries.iter().enumerate() {
+        journal.push_str(&format!("{} {} {}\n", op, index, hex::encode(path)));
+    }
+    if committed {
+        journal.push_str("committed\n");
+    }
+    fs::write(dir.join("journal"), journal)?;
+    Ok(())
+}
+
+#[test]
+fn failed_checkout_restores_workspace() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+
+    fs::write(root.join("a.txt"), "a1")?;
+    fs::write(root.join("z.txt"), "z1")?;
+    let first = commit(&root, "first")?;
+
+    fs::write(root.join("a.txt"), "a2")?;
+    fs::remove_file(root.join("z.txt"))?;
+    let second = commit(&root, "second")?;
+
+    // z.txt 자리에 디렉토리가 있으면 a.txt를 바꿔 넣은 뒤 z.txt에서 실패한다
+    fs::create_dir(root.join("z.txt"))?;
+
+    let err = checkout(&root, &first);
+    assert!(err.is_err());
+
+    assert_eq!(fs::read_to_string(root.join("a.txt"))?, "a2");
+    assert!(root.join("z.txt").is_dir());
+    assert_eq!(repo_state(&root)?.head, Some(second));
+    assert!(!root.join(".novel").join("checkout").exists());
+    assert_eq!(reflog(&root)?[0].reason, "commit");
+
+    // 원인을 치우면 다시 체크아웃할 수 있다
+    fs::remove_dir(root.join("z.txt"))?;
+    checkout(&root, &first)?;
+    assert_eq!(fs::read_to_string(root.join("a.txt"))?, "a1");
+    assert_eq!(fs::read_to_string(root.join("z.txt"))?, "z1");
+
+    Ok(())
+}
+
+#[test]
+fn interrupted_checkout_is_rolled_back_on_next_lock() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+
+    fs::write(root.join("a.txt"), "original")?;
+    fs::write(root.join("b.txt"), "kept")?;
+    let head = commit(&root, "first")?;
+
+    // a.txt는 백업 후 새 내용으로 바뀌었고, new.txt가 새로 놓였고,
+    // b.txt는 백업으로 옮겨진(삭제된) 상태에서 HEAD를 옮기기 전에 죽었다
+    write_journal(
+        &root,
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        &head,
+        &[
+            ("write", "a.txt"),
+            ("write", "new.txt"),
+            ("remove", "b.txt"),
+        ],
+        false,
+    )?;
+    let backup = root.join(".novel").join("checkout").join("backup");
+    fs::rename(root.join("a.txt"), backup.join("0"))?;
+    fs::write(root.join("a.txt"), "half written")?;
+    fs::write(root.join("new.txt"), "new")?;
+    fs::rename(root.join("b.txt"), backup.join("2"))?;
+
+    // 잠금을 잡는 작업(여기서는 commit)이 먼저 작업 트리를 되돌린다
+    fs::write(root.join("c.txt"), "c")?;
+    let next = commit(&root, "second")?;
+
+    assert!(!root.join(".novel").join("checkout").exists());
+    assert_eq!(fs::read_to_string(root.join("a.txt"))?, "original");
+    assert_eq!(fs::read_to_string(root.join("b.txt"))?, "kept");
+    assert!(!root.join("new.txt").exists());
+    assert_ne!(next, head);
+
+    Ok(())
+}
+
+#[test]
+fn interrupted_checkout_after_head_moved_is_completed() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+
+    fs::write(root.join("a.txt"), "old")?;
+    let first = commit(&root, "first")?;
+    fs::write(root.join("a.txt"), "new")?;
+    let second = commit(&root, "second")?;
+
+    // second로 체크아웃하고 HEAD까지 옮긴 뒤 백업을 지우기 전에 죽었다
+    write_journal(&root, &second, &first, &[("write", "a.txt")], true)?;
+    let backup = root.join(".novel").join("checkout").join("backup");
+    fs::write(backup.join("0"), "old")?;
+
+    checkout(&root, &first)?;
+    assert_eq!(fs::read_to_string(root.join("a.txt"))?, "old");
+    assert!(!root.join(".novel").join("checkout").exists());
+
+    // 복구가 백업을 되살리지 않고 버렸는지 second로 돌아가 확인한다
+    checkout(&root, &second)?;
+    assert_eq!(fs::read_to_string(root.join("a.txt"))?, "new");
+
+    Ok(())
+}
+
+#[test]
+fn interrupted_checkout_of_current_head_is_rolled_back() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+
+    fs::write(root.join("a.txt"), "original")?;
+    let head = commit(&root, "first")?;
+
+    // HEAD 자신을 다시 체크아웃하다 a.txt를 반쯤 쓰고 죽었다. HEAD가 대상과 같아도 마무리된 게 아니다
+    write_journal(&root, &head, &head, &[("write", "a.txt")], false)?;
+    let backup = root.join(".novel").join("checkout").join("backup");
+    fs::rename(root.join("a.txt"), backup.join("0"))?;
+    fs::write(root.join("a.txt"), "half written")?;
+
+    fs::write(root.join("b.txt"), "b")?;
+    commit(&root, "second")?;
+
+    assert!(!root.join(".novel").join("checkout").exists());
+    assert_eq!(fs::read_to_string(root.join("a.txt"))?, "original");
+
+    Ok(())
+}
+
+#[test]
+fn interrupted_checkout_before_marker_restores_head() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+
+    fs::write(root.join("a.txt"), "old")?;
+    let first = commit(&root, "first")?;
+    fs::write(root.join("a.txt"), "new")?;
+    let second = commit(&root, "second")?;
+
+    // first에서 second로 HEAD는 옮겼지만 committed 줄을 쓰기 전에 죽었다
+    write_journal(&root, &second, &first, &[("write", "a.txt")], false)?;
+    let backup = root.join(".novel").join("checkout").join("backup");
+    fs::write(backup.join("0"), "old")?;
+
+    // 잠금을 잡는 작업이 작업 트리와 HEAD를 체크아웃 전으로 되돌린다
+    fs::write(root.join("b.txt"), "b")?;
+    let next = commit(&root, "third")?;
+
+    assert_eq!(fs::read_to_string(root.join("a.txt"))?, "old");
+    let entries = reflog(&root)?;
+    assert_eq!(entries[1].reason, "checkout-recover");
+    assert_eq!(entries[1].new_node_id.as_deref(), Some(first.as_str()));
+    assert_eq!(entries[0].new_node_id.as_deref(), Some(next.as_str()));
+
+    Ok(())
+}
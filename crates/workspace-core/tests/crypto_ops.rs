@@ -0,0 +1,181 @@
+use diesel::prelude::*;
+use workspace_core::schema::{blobs, nodes};
+use workspace_core::{
+    InitOptions, Result, WorkSpaceError, checkout, commit, forget_repo_key, init_repo,
+    init_repo_with_options, is_encrypted, log, reflog, rotate_key, set_large_blob_threshold,
+    unlock_repo,
+};
+
+const SECRET: &str = "the butler did it";
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+fn connect(root: &std::path::Path) -> SqliteConnection {
+    let db = root.join(".novel").join("vcs.db");
+    SqliteConnection::establish(db.to_str().unwrap()).unwrap()
+}
+
+fn encrypted(passphrase: &str) -> InitOptions {
+    InitOptions {
+        passphrase: Some(passphrase.to_string()),
+    }
+}
+
+// DB와 objects 파일 어디에도 평문이 남지 않았는지
+fn assert_no_plaintext(root: &std::path::Path, needle: &str) {
+    let mut conn = connect(root);
+    let contents = blobs::table
+        .select(blobs::content)
+        .load::<Vec<u8>>(&mut conn)
+        .unwrap();
+    let messages = nodes::table
+        .select(nodes::message)
+        .load::<String>(&mut conn)
+        .unwrap();
+    assert!(!contents.is_empty());
+    for content in contents {
+        assert!(!String::from_utf8_lossy(&content).contains(needle));
+    }
+    for message in messages {
+        assert!(!message.contains(needle));
+    }
+
+    let objects = root.join(".novel").join("objects");
+    if objects.exists() {
+        for fan_out in std::fs::read_dir(objects).unwrap() {
+            for entry in std::fs::read_dir(fan_out.unwrap().path()).unwrap() {
+                let bytes = std::fs::read(entry.unwrap().path()).unwrap();
+                assert!(!String::from_utf8_lossy(&bytes).contains(needle));
+            }
+        }
+    }
+}
+
+#[test]
+fn encrypted_repo_stores_no_plaintext_and_round_trips() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo_with_options(&root, &encrypted("correct horse"))?;
+    assert!(is_encrypted(&root)?);
+    set_large_blob_threshold(&root, 16)?;
+
+    std::fs::write(root.join("ch1.md"), SECRET)?;
+    std::fs::write(root.join("big.md"), SECRET.repeat(4))?;
+    let first = commit(&root, SECRET)?;
+    std::fs::write(root.join("ch1.md"), "revised")?;
+    commit(&root, "second")?;
+
+    assert_no_plaintext(&root, SECRET);
+
+    assert_eq!(log(&root)?[1].message, SECRET);
+    assert_eq!(reflog(&root)?[1].message, SECRET);
+    checkout(&root, &first)?;
+    assert_eq!(std::fs::read_to_string(root.join("ch1.md"))?, SECRET);
+    assert_eq!(
+        std::fs::read_to_string(root.join("big.md"))?,
+        SECRET.repeat(4)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn locked_repo_requires_the_passphrase() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo_with_options(&root, &encrypted("correct horse"))?;
+    std::fs::write(root.join("ch1.md"), SECRET)?;
+    commit(&root, "first")?;
+
+    forget_repo_key(&root)?;
+    let err = log(&root).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::KeyRequired(_)));
+    assert_eq!(err.code(), "key_required");
+    assert!(matches!(
+        commit(&root, "second").unwrap_err(),
+        WorkSpaceError::KeyRequired(_)
+    ));
+
+    let err = unlock_repo(&root, "battery staple").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::WrongPassphrase));
+
+    unlock_repo(&root, "correct horse")?;
+    assert_eq!(log(&root)?[0].message, "first");
+
+    Ok(())
+}
+
+#[test]
+fn rotate_key_reencrypts_under_the_new_passphrase() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo_with_options(&root, &encrypted("old secret"))?;
+    set_large_blob_threshold(&root, 16)?;
+    std::fs::write(root.join("ch1.md"), SECRET)?;
+    std::fs::write(root.join("big.md"), SECRET.repeat(4))?;
+    let first = commit(&root, "first")?;
+
+    assert!(matches!(
+        rotate_key(&root, "wrong", "new secret").unwrap_err(),
+        WorkSpaceError::WrongPassphrase
+    ));
+    rotate_key(&root, "old secret", "new secret")?;
+    assert_no_plaintext(&root, SECRET);
+    assert!(
+        !root
+            .join(".novel")
+            .join("objects")
+            .read_dir()?
+            .any(|fan_out| {
+                std::fs::read_dir(fan_out.unwrap().path())
+                    .unwrap()
+                    .any(|e| e.unwrap().path().extension().is_some())
+            })
+    );
+
+    forget_repo_key(&root)?;
+    assert!(matches!(
+        unlock_repo(&root, "old secret").unwrap_err(),
+        WorkSpaceError::WrongPassphrase
+    ));
+    unlock_repo(&root, "new secret")?;
+
+    std::fs::remove_file(root.join("ch1.md"))?;
+    std::fs::remove_file(root.join("big.md"))?;
+    checkout(&root, &first)?;
+    assert_eq!(std::fs::read_to_string(root.join("ch1.md"))?, SECRET);
+    assert_eq!(
+        std::fs::read_to_string(root.join("big.md"))?,
+        SECRET.repeat(4)
+    );
+    assert_eq!(log(&root)?[0].message, "first");
+
+    Ok(())
+}
+
+#[test]
+fn enabling_encryption_on_existing_repo_encrypts_history() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    assert!(!is_encrypted(&root)?);
+    std::fs::write(root.join("ch1.md"), SECRET)?;
+    commit(&root, SECRET)?;
+
+    init_repo_with_options(&root, &encrypted("correct horse"))?;
+    assert_no_plaintext(&root, SECRET);
+    assert_eq!(log(&root)?[0].message, SECRET);
+    assert_eq!(reflog(&root)?[0].message, SECRET);
+
+    let err = init_repo_with_options(&root, &encrypted("again")).unwrap_err();
+    assert_eq!(err.code(), "conflict");
+    assert_eq!(
+        init_repo_with_options(&root, &encrypted(""))
+            .unwrap_err()
+            .code(),
+        "conflict"
+    );
+
+    Ok(())
+}
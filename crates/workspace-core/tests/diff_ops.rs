@@ -0,0 +1,226 @@
+use diesel::prelude::*;
+use workspace_core::schema::blobs;
+use workspace_core::{
+    DiffKind, FileChange, Result, WORKDIR, commit, diff_file, diff_summary, diff_workdir,
+    init_repo, write_file,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+fn connect(root: &std::path::Path) -> SqliteConnection {
+    let db = root.join(".novel").join("vcs.db");
+    SqliteConnection::establish(db.to_str().unwrap()).unwrap()
+}
+
+// (첫 노드, 둘째 노드): a.md 수정, map.bin 수정(바이너리), old.md 삭제, new.md 추가, same.md 그대로
+fn two_snapshots(root: &std::path::Path) -> Result<(String, String)> {
+    init_repo(root)?;
+    write_file(root, "a.md", "one two three")?;
+    std::fs::write(root.join("map.bin"), [0u8, 1, 2])?;
+    write_file(root, "old.md", "to be removed")?;
+    write_file(root, "same.md", "unchanged text")?;
+    let first = commit(root, "first")?;
+
+    write_file(root, "a.md", "one two three four five")?;
+    std::fs::write(root.join("map.bin"), [0u8, 1, 2, 3])?;
+    std::fs::remove_file(root.join("old.md"))?;
+    write_file(root, "new.md", "새 장면 시작")?;
+    let second = commit(root, "second")?;
+
+    Ok((first, second))
+}
+
+fn expected_changes() -> Vec<FileChange> {
+    vec![
+        FileChange {
+            path: "a.md".to_string(),
+            kind: DiffKind::Modified,
+            before_size: Some(13),
+            after_size: Some(23),
+            before_words: Some(3),
+            after_words: Some(5),
+            word_delta: 2,
+            is_binary: false,
+        },
+        FileChange {
+            path: "map.bin".to_string(),
+            kind: DiffKind::Modified,
+            before_size: Some(3),
+            after_size: Some(4),
+            before_words: None,
+            after_words: None,
+            word_delta: 0,
+            is_binary: true,
+        },
+        FileChange {
+            path: "new.md".to_string(),
+            kind: DiffKind::Added,
+            before_size: None,
+            after_size: Some("새 장면 시작".len() as i64),
+            before_words: None,
+            after_words: Some(3),
+            word_delta: 3,
+            is_binary: false,
+        },
+        FileChange {
+            path: "old.md".to_string(),
+            kind: DiffKind::Removed,
+            before_size: Some(13),
+            after_size: None,
+            before_words: Some(3),
+            after_words: None,
+            word_delta: -3,
+            is_binary: false,
+        },
+    ]
+}
+
+// (경로, 종류) 목록
+fn kinds(files: &[workspace_core::FileDiff]) -> Vec<(String, DiffKind)> {
+    files
+        .iter()
+        .map(|f| (f.path.clone(), f.kind.clone()))
+        .collect()
+}
+
+#[test]
+fn diff_summary_lists_changes_with_sizes_and_word_deltas() -> Result<()> {
+    let (_td, root) = setup();
+    let (first, second) = two_snapshots(&root)?;
+
+    let summary = diff_summary(&root, "HEAD~1", "HEAD")?;
+    assert_eq!(summary.from, first);
+    assert_eq!(summary.to, second);
+    assert_eq!(summary.files, expected_changes());
+
+    assert!(diff_summary(&root, "HEAD", &second)?.files.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn diff_summary_backfills_stats_for_old_blobs() -> Result<()> {
+    let (_td, root) = setup();
+    two_snapshots(&root)?;
+
+    // 통계 컬럼이 생기기 전에 저장된 blob처럼 만든다
+    diesel::update(blobs::table)
+        .set((
+            blobs::size.eq(None::<i64>),
+            blobs::word_count.eq(None::<i64>),
+        ))
+        .execute(&mut connect(&root))
+        .unwrap();
+
+    assert_eq!(
+        diff_summary(&root, "HEAD~1", "HEAD")?.files,
+        expected_changes()
+    );
+
+    let unfilled = blobs::table
+        .filter(blobs::size.is_null())
+        .count()
+        .get_result::<i64>(&mut connect(&root))
+        .unwrap();
+    // 바뀌지 않은 same.md만 남는다
+    assert_eq!(unfilled, 1);
+
+    Ok(())
+}
+
+#[test]
+fn diff_file_loads_one_path_on_demand() -> Result<()> {
+    let (_td, root) = setup();
+    two_snapshots(&root)?;
+
+    let file = diff_file(&root, "HEAD~1", "HEAD", "a.md")?.expect("a.md changed");
+    assert_eq!(file.kind, DiffKind::Modified);
+    assert_eq!(file.before_text.as_deref(), Some("one two three"));
+    assert_eq!(file.after_text.as_deref(), Some("one two three four five"));
+    assert!(file.unified.is_some());
+
+    let added = diff_file(&root, "HEAD~1", "HEAD", "new.md")?.expect("new.md added");
+    assert_eq!(added.kind, DiffKind::Added);
+
+    let binary = diff_file(&root, "HEAD~1", "HEAD", "map.bin")?.expect("map.bin changed");
+    assert!(binary.is_binary);
+
+    assert!(diff_file(&root, "HEAD~1", "HEAD", "same.md")?.is_none());
+    assert!(diff_file(&root, "HEAD~1", "HEAD", "missing.md")?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn diff_workdir_compares_disk_with_head() -> Result<()> {
+    let (_td, root) = setup();
+    let (_, second) = two_snapshots(&root)?;
+
+    assert!(diff_workdir(&root, None)?.files.is_empty());
+
+    write_file(&root, "a.md", "one two")?;
+    write_file(&root, "untracked.md", "draft")?;
+    std::fs::remove_file(root.join("same.md"))?;
+
+    let diff = diff_workdir(&root, None)?;
+    assert_eq!(diff.from, second);
+    assert_eq!(diff.to, WORKDIR);
+    assert_eq!(
+        kinds(&diff.files),
+        vec![
+            ("a.md".to_string(), DiffKind::Modified),
+            ("same.md".to_string(), DiffKind::Removed),
+            ("untracked.md".to_string(), DiffKind::Added),
+        ]
+    );
+    assert_eq!(
+        diff.files[0].before_text.as_deref(),
+        Some("one two three four five")
+    );
+    assert_eq!(diff.files[0].after_text.as_deref(), Some("one two"));
+    assert_eq!(diff.files[2].after_text.as_deref(), Some("draft"));
+
+    Ok(())
+}
+
+#[test]
+fn diff_workdir_accepts_any_revision() -> Result<()> {
+    let (_td, root) = setup();
+    let (first, _) = two_snapshots(&root)?;
+
+    let diff = diff_workdir(&root, Some("HEAD~1"))?;
+    assert_eq!(diff.from, first);
+    assert_eq!(
+        kinds(&diff.files),
+        vec![
+            ("a.md".to_string(), DiffKind::Modified),
+            ("map.bin".to_string(), DiffKind::Modified),
+            ("new.md".to_string(), DiffKind::Added),
+            ("old.md".to_string(), DiffKind::Removed),
+        ]
+    );
+    assert!(diff.files[1].is_binary);
+
+    Ok(())
+}
+
+#[test]
+fn diff_workdir_without_commits_lists_everything_as_added() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    write_file(&root, "ch1.md", "first line")?;
+
+    let diff = diff_workdir(&root, None)?;
+    assert_eq!(diff.from, "");
+    assert_eq!(
+        kinds(&diff.files),
+        vec![("ch1.md".to_string(), DiffKind::Added)]
+    );
+
+    Ok(())
+}
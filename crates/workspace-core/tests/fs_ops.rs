@@ -1,5 +1,8 @@
 use std::io::ErrorKind;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 use workspace_core::{
     WorkSpaceError, checkout, create_file, list_files, read_file, resolve_path, write_file,
 };
@@ -38,7 +41,7 @@ fn list_files_returns_relative_sorted_paths() {
     assert!(
         entries
             .iter()
-            .any(|e| e.path == PathBuf::from("docs") && e.is_dir)
+            .any(|e| e.path == Path::new("docs") && e.is_dir)
     );
 }
 
@@ -90,5 +93,5 @@ fn checkout_unknown_node_returns_error() {
     let (_td, root) = setup();
 
     let err = checkout(&root, "unknown").unwrap_err();
-    assert!(matches!(err, WorkSpaceError::Io(ref e) if e.kind() == ErrorKind::NotFound));
+    assert!(matches!(err, WorkSpaceError::NodeNotFound(ref rev) if rev == "unknown"));
 }
@@ -0,0 +1,258 @@
+use diesel::prelude::*;
+use workspace_core::schema::{node_parents, nodes};
+use workspace_core::{
+    AheadBehind, GraphLayout, Result, WorkSpaceError, ahead_behind, descendants, graph_layout,
+    init_repo, is_ancestor, merge_base, merge_bases,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+// 머지 노드를 만드는 명령이 아직 없으므로 그래프는 DB에 직접 넣는다.
+// (id, 생성 시각, 부모들)
+fn insert_graph(root: &std::path::Path, graph: &[(&str, i64, &[&str])]) {
+    let db = root.join(".novel").join("vcs.db");
+    let mut conn = SqliteConnection::establish(db.to_str().unwrap()).unwrap();
+
+    for (id, created_at, parents) in graph {
+        diesel::insert_into(nodes::table)
+            .values((
+                nodes::id.eq(*id),
+                nodes::message.eq(*id),
+                nodes::created_at_unix_ms.eq(*created_at),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+
+        for (ord, parent) in parents.iter().enumerate() {
+            diesel::insert_into(node_parents::table)
+                .values((
+                    node_parents::node_id.eq(*id),
+                    node_parents::parent_id.eq(*parent),
+                    node_parents::ord.eq(ord as i32),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+        }
+    }
+}
+
+// (id, 열, 모이는 열, 지나가는 열, 부모 열)
+type RowSummary = (String, usize, Vec<usize>, Vec<usize>, Vec<usize>);
+
+fn summary(layout: &GraphLayout) -> Vec<RowSummary> {
+    layout
+        .rows
+        .iter()
+        .map(|r| {
+            (
+                r.node.id.clone(),
+                r.lane,
+                r.incoming.clone(),
+                r.pass_through.clone(),
+                r.parent_lanes.clone(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn linear_history_stays_in_one_lane() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    insert_graph(&root, &[("a", 1, &[]), ("b", 2, &["a"]), ("c", 3, &["b"])]);
+
+    let layout = graph_layout(&root)?;
+    assert_eq!(layout.lane_count, 1);
+    assert_eq!(
+        summary(&layout),
+        vec![
+            ("c".into(), 0, vec![], vec![], vec![0]),
+            ("b".into(), 0, vec![0], vec![], vec![0]),
+            ("a".into(), 0, vec![0], vec![], vec![]),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn diamond_merge_opens_and_closes_a_lane() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    insert_graph(
+        &root,
+        &[
+            ("a", 1, &[]),
+            ("b", 2, &["a"]),
+            ("c", 3, &["a"]),
+            ("m", 4, &["b", "c"]),
+        ],
+    );
+
+    let layout = graph_layout(&root)?;
+    assert_eq!(layout.lane_count, 2);
+    assert_eq!(
+        summary(&layout),
+        vec![
+            ("m".into(), 0, vec![], vec![], vec![0, 1]),
+            ("c".into(), 1, vec![1], vec![0], vec![1]),
+            ("b".into(), 0, vec![0], vec![1], vec![0]),
+            ("a".into(), 0, vec![0, 1], vec![], vec![]),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn children_come_before_parents_even_with_skewed_clocks() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    // 자식 b가 부모 a보다 이른 시각으로 기록됐다
+    insert_graph(
+        &root,
+        &[
+            ("base", 5, &[]),
+            ("a", 50, &[]),
+            ("b", 10, &["a"]),
+            ("other", 30, &["base"]),
+        ],
+    );
+
+    let layout = graph_layout(&root)?;
+    let order = layout
+        .rows
+        .iter()
+        .map(|r| r.node.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(order, vec!["other", "b", "a", "base"]);
+
+    // 서로 관계없는 두 줄기는 다른 열에 놓인다
+    assert_eq!(
+        summary(&layout)[1..],
+        [
+            ("b".into(), 1, vec![], vec![0], vec![1]),
+            ("a".into(), 1, vec![1], vec![0], vec![]),
+            ("base".into(), 0, vec![0], vec![], vec![]),
+        ]
+    );
+
+    Ok(())
+}
+
+// 그래프 질의는 리비전을 받으므로 id는 4자 이상의 16진수로 둔다
+fn diamond(root: &std::path::Path) {
+    // aaaa <- bbbb, aaaa <- cccc, (bbbb, cccc) <- dddd
+    insert_graph(
+        root,
+        &[
+            ("aaaa", 1, &[]),
+            ("bbbb", 2, &["aaaa"]),
+            ("cccc", 3, &["aaaa"]),
+            ("dddd", 4, &["bbbb", "cccc"]),
+        ],
+    );
+}
+
+#[test]
+fn diamond_queries() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    diamond(&root);
+
+    assert_eq!(merge_base(&root, "bbbb", "cccc")?, Some("aaaa".to_string()));
+    assert_eq!(merge_base(&root, "dddd", "bbbb")?, Some("bbbb".to_string()));
+    assert_eq!(merge_base(&root, "aaaa", "aaaa")?, Some("aaaa".to_string()));
+
+    assert!(is_ancestor(&root, "aaaa", "dddd")?);
+    assert!(is_ancestor(&root, "cccc", "dddd")?);
+    assert!(is_ancestor(&root, "dddd", "dddd")?);
+    assert!(!is_ancestor(&root, "bbbb", "cccc")?);
+    assert!(!is_ancestor(&root, "dddd", "aaaa")?);
+
+    assert_eq!(
+        ahead_behind(&root, "dddd", "bbbb")?,
+        AheadBehind {
+            ahead: 2,
+            behind: 0
+        }
+    );
+    assert_eq!(
+        ahead_behind(&root, "bbbb", "cccc")?,
+        AheadBehind {
+            ahead: 1,
+            behind: 1
+        }
+    );
+
+    assert_eq!(descendants(&root, "aaaa")?, vec!["bbbb", "cccc", "dddd"]);
+    assert_eq!(descendants(&root, "cccc")?, vec!["dddd"]);
+    assert!(descendants(&root, "dddd")?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn criss_cross_has_two_merge_bases() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    // bbbb와 cccc를 서로 반대 방향으로 머지한 두 줄기
+    insert_graph(
+        &root,
+        &[
+            ("aaaa", 1, &[]),
+            ("bbbb", 2, &["aaaa"]),
+            ("cccc", 3, &["aaaa"]),
+            ("dddd", 4, &["bbbb", "cccc"]),
+            ("eeee", 5, &["cccc", "bbbb"]),
+            ("d0d0", 6, &["dddd"]),
+            ("e0e0", 7, &["eeee"]),
+        ],
+    );
+
+    assert_eq!(merge_bases(&root, "d0d0", "e0e0")?, vec!["cccc", "bbbb"]);
+    assert_eq!(merge_base(&root, "d0d0", "e0e0")?, Some("cccc".to_string()));
+    assert_eq!(merge_bases(&root, "dddd", "eeee")?, vec!["cccc", "bbbb"]);
+
+    assert_eq!(
+        ahead_behind(&root, "d0d0", "e0e0")?,
+        AheadBehind {
+            ahead: 2,
+            behind: 2
+        }
+    );
+    assert!(!is_ancestor(&root, "dddd", "e0e0")?);
+    assert!(is_ancestor(&root, "bbbb", "e0e0")?);
+    assert_eq!(
+        descendants(&root, "bbbb")?,
+        vec!["dddd", "eeee", "d0d0", "e0e0"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn unrelated_histories_have_no_merge_base() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    insert_graph(&root, &[("aaaa", 1, &[]), ("bbbb", 2, &[])]);
+
+    assert_eq!(merge_base(&root, "aaaa", "bbbb")?, None);
+    assert_eq!(
+        ahead_behind(&root, "aaaa", "bbbb")?,
+        AheadBehind {
+            ahead: 1,
+            behind: 1
+        }
+    );
+
+    let err = merge_base(&root, "aaaa", "ffff").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NodeNotFound(ref rev) if rev == "ffff"));
+
+    Ok(())
+}
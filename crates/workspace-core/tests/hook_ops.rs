@@ -0,0 +1,119 @@
+// 훅은 셸 스크립트로 시험하므로 유닉스에서만 돌린다
+#![cfg(unix)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+use workspace_core::{
+    Result, WorkSpaceError, checkout, commit, init_repo, log, read_file, write_file,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+fn install_hook(root: &std::path::Path, name: &str, script: &str) {
+    let dir = root.join(".novel").join("hooks");
+    fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join(name);
+    fs::write(&path, script).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+// 훅이 받은 stdin을 .novel/hooks/<훅>.seen에 남긴다 (.novel은 스냅샷에 들어가지 않는다)
+fn seen(root: &std::path::Path, name: &str) -> String {
+    fs::read_to_string(
+        root.join(".novel")
+            .join("hooks")
+            .join(format!("{}.seen", name)),
+    )
+    .unwrap()
+}
+
+const REJECT_TODO: &str = r#"#!/bin/sh
+status=0
+while read -r path; do
+    if [ -f "$path" ] && grep -q "TODO:" "$path"; then
+        echo "leftover TODO in $path"
+        status=1
+    fi
+done
+exit $status
+"#;
+
+#[test]
+fn pre_snapshot_failure_rejects_commit() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    install_hook(&root, "pre-snapshot", REJECT_TODO);
+
+    write_file(&root, "ch1.md", "TODO: rewrite the opening")?;
+    let err = commit(&root, "draft").unwrap_err();
+    match &err {
+        WorkSpaceError::HookFailed { hook, code, output } => {
+            assert_eq!(hook, "pre-snapshot");
+            assert_eq!(*code, Some(1));
+            assert_eq!(output, "leftover TODO in ch1.md");
+        }
+        other => panic!("expected HookFailed, got {:?}", other),
+    }
+    assert!(err.to_string().contains("leftover TODO in ch1.md"));
+    assert!(log(&root)?.is_empty());
+
+    write_file(&root, "ch1.md", "It was a dark and stormy night.")?;
+    commit(&root, "draft")?;
+    assert_eq!(log(&root)?.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn pre_snapshot_receives_only_changed_files() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    write_file(&root, "a.md", "a")?;
+    write_file(&root, "b.md", "b")?;
+    write_file(&root, "c.md", "c")?;
+    commit(&root, "first")?;
+
+    install_hook(&root, "pre-snapshot", "#!/bin/sh\ncat > \"$0.seen\"\n");
+    write_file(&root, "b.md", "b2")?;
+    fs::remove_file(root.join("c.md"))?;
+    write_file(&root, "d.md", "d")?;
+    commit(&root, "second")?;
+
+    assert_eq!(seen(&root, "pre-snapshot"), "b.md\nc.md\nd.md\n");
+
+    Ok(())
+}
+
+#[test]
+fn post_checkout_runs_after_checkout_and_ignores_exit_code() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    write_file(&root, "a.md", "one")?;
+    write_file(&root, "b.md", "same")?;
+    let first = commit(&root, "first")?;
+    write_file(&root, "a.md", "two")?;
+    write_file(&root, "c.md", "new")?;
+    let second = commit(&root, "second")?;
+
+    install_hook(
+        &root,
+        "post-checkout",
+        "#!/bin/sh\n{ echo \"$1 $2\"; cat; } > \"$0.seen\"\nexit 3\n",
+    );
+    checkout(&root, &first)?;
+
+    assert_eq!(read_file(&root, "a.md")?, "one");
+    assert_eq!(
+        seen(&root, "post-checkout"),
+        format!("{} {}\na.md\nc.md\n", second, first)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::Text;
+use workspace_core::{Result, WorkSpaceError, checkout, commit, init_repo, repo_state};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+#[derive(QueryableByName)]
+struct JournalMode {
+    #[diesel(sql_type = Text)]
+    journal_mode: String,
+}
+
+#[test]
+fn commit_fails_while_another_process_holds_the_lock() -> Result<()> {
+    let (_td, root) = setup();
+
+    init_repo(&root)?;
+    std::fs::write(root.join("a.txt"), "a")?;
+    let first = commit(&root, "first")?;
+
+    // 다른 프로세스처럼 잠금 파일에 OS 잠금을 건다(파일을 따로 열면 같은 프로세스 안에서도 겹친다)
+    let lock_path = root.join(".novel/lock");
+    let held = std::fs::File::create(&lock_path)?;
+    held.lock()?;
+
+    std::fs::write(root.join("a.txt"), "changed")?;
+    let err = commit(&root, "second").unwrap_err();
+    assert!(
+        matches!(err, WorkSpaceError::RepoLocked(ref p) if p == &lock_path.canonicalize().unwrap())
+    );
+    assert!(err.to_string().starts_with("repository is busy"));
+
+    let err = checkout(&root, &first).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::RepoLocked(_)));
+    assert_eq!(std::fs::read_to_string(root.join("a.txt"))?, "changed");
+    assert_eq!(repo_state(&root)?.head, Some(first));
+
+    drop(held);
+    commit(&root, "second")?;
+
+    Ok(())
+}
+
+#[test]
+fn leftover_lock_file_does_not_block() -> Result<()> {
+    let (_td, root) = setup();
+
+    init_repo(&root)?;
+
+    // 죽은 프로세스가 남긴 파일: 내용과 상관없이 OS 잠금이 없으면 잡을 수 있다
+    let lock_path = root.join(".novel/lock");
+    std::fs::write(&lock_path, format!("{}\n0\n", u32::MAX))?;
+
+    std::fs::write(root.join("a.txt"), "a")?;
+    commit(&root, "first")?;
+
+    Ok(())
+}
+
+#[test]
+fn lock_is_released_after_commit() -> Result<()> {
+    let (_td, root) = setup();
+
+    init_repo(&root)?;
+    std::fs::write(root.join("a.txt"), "a")?;
+    commit(&root, "first")?;
+
+    let file = std::fs::File::open(root.join(".novel/lock"))?;
+    assert!(file.try_lock().is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn database_uses_wal_journal() -> Result<()> {
+    let (_td, root) = setup();
+
+    init_repo(&root)?;
+
+    let db_path = root.join(".novel/vcs.db");
+    let mut conn = SqliteConnection::establish(db_path.to_str().unwrap()).unwrap();
+    let mode = sql_query("PRAGMA journal_mode")
+        .get_result::<JournalMode>(&mut conn)
+        .unwrap();
+
+    assert_eq!(mode.journal_mode, "wal");
+
+    Ok(())
+}
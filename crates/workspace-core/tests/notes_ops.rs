@@ -0,0 +1,164 @@
+use workspace_core::{
+    AutosavePolicy, InitOptions, Result, WorkSpaceError, autosave, checkout, commit,
+    forget_repo_key, init_repo, init_repo_with_options, log, node_notes, note_add, note_edit,
+    note_remove, rotate_key, thin_autosaves, unlock_repo,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+#[test]
+fn notes_attach_without_changing_node_ids() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("ch1.md"), "draft")?;
+    let first = commit(&root, "first")?;
+    std::fs::write(root.join("ch1.md"), "second draft")?;
+    let second = commit(&root, "second")?;
+
+    let note = note_add(&root, "HEAD~1", "  reader A: pacing drags in the middle  ")?;
+    assert_eq!(note.node_id, first);
+    assert_eq!(note.text, "reader A: pacing drags in the middle");
+    assert_eq!(note.created_at_unix_ms, note.updated_at_unix_ms);
+    note_add(&root, &first, "reader B: loved the opening")?;
+
+    let nodes = log(&root)?;
+    assert_eq!(nodes[0].id, second);
+    assert!(nodes[0].notes.is_empty());
+    assert_eq!(nodes[1].id, first);
+    assert_eq!(
+        nodes[1]
+            .notes
+            .iter()
+            .map(|n| n.text.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "reader A: pacing drags in the middle",
+            "reader B: loved the opening"
+        ]
+    );
+    assert_eq!(node_notes(&root, &first)?, nodes[1].notes);
+
+    Ok(())
+}
+
+#[test]
+fn edit_and_remove_notes() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("ch1.md"), "draft")?;
+    commit(&root, "first")?;
+
+    let note = note_add(&root, "HEAD", "typo on p.3")?;
+    let edited = note_edit(&root, note.id, "typo on p.3 (fixed in ch1)")?;
+    assert_eq!(edited.created_at_unix_ms, note.created_at_unix_ms);
+    assert!(edited.updated_at_unix_ms >= note.updated_at_unix_ms);
+    assert_eq!(node_notes(&root, "HEAD")?, vec![edited]);
+
+    assert_eq!(
+        note_edit(&root, note.id, "   ").unwrap_err().code(),
+        "invalid_input"
+    );
+    note_remove(&root, note.id)?;
+    assert!(node_notes(&root, "HEAD")?.is_empty());
+    assert_eq!(note_remove(&root, note.id).unwrap_err().code(), "not_found");
+    assert_eq!(
+        note_edit(&root, note.id, "x").unwrap_err().code(),
+        "not_found"
+    );
+    assert!(matches!(
+        note_add(&root, "nope", "x").unwrap_err(),
+        WorkSpaceError::NodeNotFound(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn thin_autosaves_keeps_annotated_nodes() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("ch1.md"), "one")?;
+    let first = commit(&root, "first")?;
+
+    let policy = AutosavePolicy {
+        interval_ms: 0,
+        ..AutosavePolicy::default()
+    };
+    // 버려진 가지 끝의 두 자동 저장. 메모가 없으면 둘 다 솎인다
+    let mut abandoned = Vec::new();
+    for text in ["two", "three"] {
+        std::fs::write(root.join("ch1.md"), text)?;
+        abandoned.push(autosave(&root, &policy)?.expect("autosaved"));
+        checkout(&root, &first)?;
+    }
+    // 같은 밀리초면 시간 구간의 "가장 최근"이 id 순으로 갈린다
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    std::fs::write(root.join("ch1.md"), "four")?;
+    autosave(&root, &policy)?.expect("autosaved");
+    note_add(&root, &abandoned[0], "keep this one")?;
+
+    let summary = thin_autosaves(&root)?;
+    assert_eq!(summary.removed_nodes, vec![abandoned[1].clone()]);
+    assert_eq!(node_notes(&root, &abandoned[0])?.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn notes_are_encrypted_and_survive_key_rotation() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo_with_options(
+        &root,
+        &InitOptions {
+            passphrase: Some("old secret".to_string()),
+        },
+    )?;
+    std::fs::write(root.join("ch1.md"), "draft")?;
+    commit(&root, "first")?;
+    note_add(&root, "HEAD", "the twist is too early")?;
+
+    let db = std::fs::read(root.join(".novel").join("vcs.db"))?;
+    assert!(!String::from_utf8_lossy(&db).contains("twist is too early"));
+
+    rotate_key(&root, "old secret", "new secret")?;
+    forget_repo_key(&root)?;
+    assert_eq!(
+        node_notes(&root, "HEAD").unwrap_err().code(),
+        "key_required"
+    );
+    unlock_repo(&root, "new secret")?;
+    assert_eq!(node_notes(&root, "HEAD")?[0].text, "the twist is too early");
+
+    Ok(())
+}
+
+#[test]
+fn note_changes_wait_for_the_repository_lock() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("ch1.md"), "draft")?;
+    commit(&root, "first")?;
+    let note = note_add(&root, "HEAD", "typo on p.3")?;
+
+    // 키 교체 중인 다른 프로세스처럼 잠금 파일에 OS 잠금을 건다
+    let held = std::fs::File::create(root.join(".novel/lock"))?;
+    held.lock()?;
+    for err in [
+        note_add(&root, "HEAD", "x").unwrap_err(),
+        note_edit(&root, note.id, "x").unwrap_err(),
+        note_remove(&root, note.id).unwrap_err(),
+    ] {
+        assert!(matches!(err, WorkSpaceError::RepoLocked(_)));
+    }
+
+    drop(held);
+    note_remove(&root, note.id)?;
+    assert!(node_notes(&root, "HEAD")?.is_empty());
+
+    Ok(())
+}
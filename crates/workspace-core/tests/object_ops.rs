@@ -0,0 +1,166 @@
+use diesel::prelude::*;
+use workspace_core::schema::{blobs, nodes};
+use workspace_core::{
+    AutosavePolicy, DEFAULT_LARGE_BLOB_THRESHOLD, DiffKind, Result, autosave, bundle_create,
+    bundle_unbundle, checkout, commit, diff_nodes, gc, init_repo, large_blob_threshold,
+    set_large_blob_threshold, thin_autosaves,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+fn connect(root: &std::path::Path) -> SqliteConnection {
+    let db = root.join(".novel").join("vcs.db");
+    SqliteConnection::establish(db.to_str().unwrap()).unwrap()
+}
+
+// (외부 blob 수, 내용이 DB에 있는 blob 수)
+fn blob_counts(root: &std::path::Path) -> (usize, usize) {
+    let rows = blobs::table
+        .select((blobs::external, blobs::content))
+        .load::<(bool, Vec<u8>)>(&mut connect(root))
+        .unwrap();
+
+    let external = rows.iter().filter(|(external, _)| *external).count();
+    assert!(
+        rows.iter()
+            .all(|(external, content)| !external || content.is_empty())
+    );
+    (external, rows.len() - external)
+}
+
+fn object_files(root: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let dir = root.join(".novel").join("objects");
+    if !dir.exists() {
+        return Vec::new();
+    }
+
+    let mut files = Vec::new();
+    for fan_out in std::fs::read_dir(dir).unwrap() {
+        for entry in std::fs::read_dir(fan_out.unwrap().path()).unwrap() {
+            files.push(entry.unwrap().path());
+        }
+    }
+    files.sort();
+    files
+}
+
+#[test]
+fn large_blobs_are_stored_outside_the_database() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    assert_eq!(large_blob_threshold(&root)?, DEFAULT_LARGE_BLOB_THRESHOLD);
+    set_large_blob_threshold(&root, 16)?;
+    assert_eq!(large_blob_threshold(&root)?, 16);
+
+    std::fs::write(root.join("notes.txt"), "short")?;
+    std::fs::write(root.join("map.png"), vec![7u8; 64])?;
+    let first = commit(&root, "assets")?;
+
+    assert_eq!(blob_counts(&root), (1, 1));
+    assert_eq!(object_files(&root).len(), 1);
+
+    std::fs::write(root.join("map.png"), vec![9u8; 64])?;
+    let second = commit(&root, "new map")?;
+
+    let diff = diff_nodes(&root, &first, &second)?;
+    assert_eq!(diff.files.len(), 1);
+    assert_eq!(diff.files[0].path, "map.png");
+    assert_eq!(diff.files[0].kind, DiffKind::Modified);
+
+    checkout(&root, &first)?;
+    assert_eq!(std::fs::read(root.join("map.png"))?, vec![7u8; 64]);
+    assert_eq!(std::fs::read_to_string(root.join("notes.txt"))?, "short");
+
+    Ok(())
+}
+
+#[test]
+fn corrupt_object_is_rejected_on_read() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    set_large_blob_threshold(&root, 16)?;
+
+    std::fs::write(root.join("map.png"), vec![7u8; 64])?;
+    let first = commit(&root, "map")?;
+    std::fs::remove_file(root.join("map.png"))?;
+    commit(&root, "drop map")?;
+
+    std::fs::write(&object_files(&root)[0], vec![0u8; 64])?;
+    let err = checkout(&root, &first).unwrap_err();
+    assert!(err.to_string().contains("corrupt object"));
+    // 작업 트리는 건드리지 않는다
+    assert!(!root.join("map.png").exists());
+
+    Ok(())
+}
+
+#[test]
+fn gc_removes_unreferenced_objects() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    set_large_blob_threshold(&root, 16)?;
+    let policy = AutosavePolicy {
+        interval_ms: 0,
+        ..AutosavePolicy::default()
+    };
+
+    std::fs::write(root.join("map.png"), vec![0u8; 64])?;
+    let base = commit(&root, "base")?;
+    std::fs::write(root.join("map.png"), vec![1u8; 64])?;
+    autosave(&root, &policy)?.expect("first autosave");
+    // 첫 자동 저장을 버려진 가지로 만든다
+    checkout(&root, &base)?;
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    std::fs::write(root.join("map.png"), vec![2u8; 64])?;
+    autosave(&root, &policy)?.expect("second autosave");
+
+    // 두 자동 저장을 같은 시간 구간으로 옮겨 오래된 쪽이 솎이게 한다
+    diesel::update(nodes::table)
+        .set(nodes::created_at_unix_ms.eq(nodes::created_at_unix_ms - 3 * 60 * 60 * 1000))
+        .execute(&mut connect(&root))
+        .unwrap();
+    assert_eq!(thin_autosaves(&root)?.removed_nodes.len(), 1);
+
+    // 롤백된 커밋이 남긴 것 같은 파일
+    let stray = root.join(".novel").join("objects").join("ff").join("00");
+    std::fs::create_dir_all(stray.parent().unwrap())?;
+    std::fs::write(&stray, "stray")?;
+
+    let summary = gc(&root)?;
+    assert_eq!(summary.removed_blobs, 1);
+    assert_eq!(summary.removed_objects, 2);
+    assert_eq!(blob_counts(&root), (2, 0));
+    assert_eq!(object_files(&root).len(), 2);
+    assert_eq!(std::fs::read(root.join("map.png"))?, vec![2u8; 64]);
+
+    Ok(())
+}
+
+#[test]
+fn bundles_move_blobs_between_storage_kinds() -> Result<()> {
+    let (_src_td, src) = setup();
+    let (_dst_td, dst) = setup();
+    let (_out_td, out) = setup();
+    let bundle_path = out.join("assets.bundle");
+
+    init_repo(&src)?;
+    set_large_blob_threshold(&src, 16)?;
+    std::fs::write(src.join("map.png"), vec![7u8; 64])?;
+    let head = commit(&src, "map")?;
+    bundle_create(&src, &bundle_path, &["HEAD"])?;
+
+    // 받는 쪽 기준(기본 1MiB)으로는 작은 blob이므로 DB 안에 들어간다
+    init_repo(&dst)?;
+    bundle_unbundle(&dst, &bundle_path)?;
+    assert_eq!(blob_counts(&dst), (0, 1));
+
+    checkout(&dst, &head)?;
+    assert_eq!(std::fs::read(dst.join("map.png"))?, vec![7u8; 64]);
+
+    Ok(())
+}
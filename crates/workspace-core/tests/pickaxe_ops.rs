@@ -0,0 +1,152 @@
+use diesel::prelude::*;
+use workspace_core::schema::node_parents;
+use workspace_core::{
+    InitOptions, PickaxeHit, Result, WorkSpaceError, checkout, commit, init_repo,
+    init_repo_with_options, pickaxe,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+fn hit(node_id: &str, path: &str, before_count: usize, after_count: usize) -> PickaxeHit {
+    PickaxeHit {
+        node_id: node_id.to_string(),
+        path: path.to_string(),
+        before_count,
+        after_count,
+    }
+}
+
+#[test]
+fn finds_where_a_sentence_appeared_and_was_cut() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("ch1.md"), "비가 내렸다.")?;
+    let first = commit(&root, "first")?;
+    std::fs::write(root.join("ch1.md"), "비가 내렸다. 그는 우산을 접었다.")?;
+    let added = commit(&root, "add umbrella")?;
+    std::fs::write(root.join("ch1.md"), "비가 그쳤다. 그는 우산을 접었다.")?;
+    commit(&root, "unrelated edit")?;
+    std::fs::write(root.join("ch1.md"), "비가 그쳤다.")?;
+    let cut = commit(&root, "cut umbrella")?;
+
+    let hits = pickaxe(&root, "우산을 접었다", None)?;
+    assert_eq!(hits.len(), 2);
+    assert!(hits.contains(&hit(&added, "ch1.md", 0, 1)));
+    assert!(hits.contains(&hit(&cut, "ch1.md", 1, 0)));
+
+    // 루트 노드는 빈 스냅샷과 비교한다
+    let hits = pickaxe(&root, "비가 내렸다", None)?;
+    assert_eq!(hits.len(), 2);
+    assert!(hits.contains(&hit(&first, "ch1.md", 0, 1)));
+
+    Ok(())
+}
+
+#[test]
+fn counts_changes_and_file_deletion() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("a.md"), "echo")?;
+    commit(&root, "first")?;
+    std::fs::write(root.join("a.md"), "echo echo echo")?;
+    let more = commit(&root, "more")?;
+    std::fs::remove_file(root.join("a.md"))?;
+    let removed = commit(&root, "remove")?;
+
+    let hits = pickaxe(&root, "echo", None)?;
+    assert_eq!(hits.len(), 3);
+    assert!(hits.contains(&hit(&more, "a.md", 1, 3)));
+    assert!(hits.contains(&hit(&removed, "a.md", 3, 0)));
+
+    Ok(())
+}
+
+#[test]
+fn path_filter_limits_to_file_or_directory() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::create_dir_all(root.join("drafts"))?;
+    std::fs::write(root.join("drafts/ch1.md"), "the lighthouse")?;
+    std::fs::write(root.join("drafts-old.md"), "the lighthouse")?;
+    std::fs::write(root.join("notes.md"), "the lighthouse")?;
+    let first = commit(&root, "first")?;
+
+    let hits = pickaxe(&root, "lighthouse", Some("drafts"))?;
+    assert_eq!(hits, vec![hit(&first, "drafts/ch1.md", 0, 1)]);
+
+    let hits = pickaxe(&root, "lighthouse", Some("./notes.md"))?;
+    assert_eq!(hits, vec![hit(&first, "notes.md", 0, 1)]);
+
+    assert_eq!(pickaxe(&root, "lighthouse", None)?.len(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn merge_reports_only_changes_it_introduced() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("a.md"), "base")?;
+    let base = commit(&root, "base")?;
+    std::fs::write(root.join("a.md"), "base twist")?;
+    let branch = commit(&root, "twist on branch")?;
+
+    checkout(&root, &base)?;
+    std::fs::write(root.join("b.md"), "side")?;
+    commit(&root, "side")?;
+    // 병합 결과: 가지의 a.md를 그대로 가져온다
+    std::fs::write(root.join("a.md"), "base twist")?;
+    let merge = commit(&root, "merge")?;
+
+    let db = root.join(".novel").join("vcs.db");
+    let mut conn = SqliteConnection::establish(db.to_str().unwrap()).unwrap();
+    diesel::insert_into(node_parents::table)
+        .values((
+            node_parents::node_id.eq(&merge),
+            node_parents::parent_id.eq(&branch),
+            node_parents::ord.eq(1),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+
+    let hits = pickaxe(&root, "twist", None)?;
+    assert_eq!(hits, vec![hit(&branch, "a.md", 0, 1)]);
+
+    Ok(())
+}
+
+#[test]
+fn works_on_encrypted_repositories() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo_with_options(
+        &root,
+        &InitOptions {
+            passphrase: Some("secret".to_string()),
+        },
+    )?;
+    std::fs::write(root.join("ch1.md"), "hidden plot twist")?;
+    let first = commit(&root, "first")?;
+
+    assert_eq!(
+        pickaxe(&root, "plot", None)?,
+        vec![hit(&first, "ch1.md", 0, 1)]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn empty_phrase_is_rejected() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+
+    let err = pickaxe(&root, "", None).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::InvalidInput(_)));
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use std::sync::{Arc, Mutex};
+
+use workspace_core::{
+    CancelToken, CommitOptions, Progress, ProgressEvent, ProgressPhase, Result, WorkSpaceError,
+    bundle_create_with_progress, bundle_unbundle_with_progress, checkout_with_progress, commit,
+    commit_with_progress, gc_with_progress, init_repo, log, repo_state, set_large_blob_threshold,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+// 받은 이벤트를 모아 두는 Progress
+fn recorder() -> (Progress, Arc<Mutex<Vec<ProgressEvent>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let progress = Progress::new(
+        move |event: &ProgressEvent| sink.lock().unwrap().push(event.clone()),
+        CancelToken::new(),
+    );
+
+    (progress, events)
+}
+
+// phase 이벤트 중 마지막 것
+fn last(events: &[ProgressEvent], phase: ProgressPhase) -> ProgressEvent {
+    events
+        .iter()
+        .rev()
+        .find(|e| e.phase == phase)
+        .cloned()
+        .expect("phase reported")
+}
+
+// 첫 이벤트를 받으면 스스로 취소하는 Progress
+fn cancel_on_first_event() -> Progress {
+    let token = CancelToken::new();
+    let trigger = token.clone();
+    Progress::new(move |_: &ProgressEvent| trigger.cancel(), token)
+}
+
+#[test]
+fn commit_reports_scanned_and_stored_files() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("a.md"), "abc")?;
+    std::fs::write(root.join("b.md"), "defgh")?;
+
+    let (progress, events) = recorder();
+    commit_with_progress(&root, "first", CommitOptions::default(), &progress)?;
+
+    let events = events.lock().unwrap();
+    let scan = last(&events, ProgressPhase::Scan);
+    assert_eq!((scan.done, scan.total, scan.bytes), (2, 2, 8));
+    let store = last(&events, ProgressPhase::Store);
+    assert_eq!((store.done, store.total, store.bytes), (2, 2, 8));
+
+    Ok(())
+}
+
+#[test]
+fn cancelled_commit_writes_nothing() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("a.md"), "abc")?;
+    std::fs::write(root.join("b.md"), "defgh")?;
+
+    let err = commit_with_progress(
+        &root,
+        "first",
+        CommitOptions::default(),
+        &cancel_on_first_event(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, WorkSpaceError::Cancelled));
+    assert!(log(&root)?.is_empty());
+
+    // 이미 취소된 토큰은 시작하자마자 멈춘다
+    let token = CancelToken::new();
+    token.cancel();
+    let err = commit_with_progress(
+        &root,
+        "first",
+        CommitOptions::default(),
+        &Progress::cancellable(token),
+    )
+    .unwrap_err();
+    assert_eq!(err.code(), "cancelled");
+
+    Ok(())
+}
+
+#[test]
+fn cancelled_checkout_leaves_working_tree_and_head() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("a.md"), "first a")?;
+    std::fs::write(root.join("b.md"), "first b")?;
+    let first = commit(&root, "first")?;
+    std::fs::write(root.join("a.md"), "second a")?;
+    std::fs::write(root.join("b.md"), "second b")?;
+    let second = commit(&root, "second")?;
+
+    let err = checkout_with_progress(&root, &first, &cancel_on_first_event()).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::Cancelled));
+    assert_eq!(repo_state(&root)?.head, Some(second));
+    assert_eq!(std::fs::read_to_string(root.join("a.md"))?, "second a");
+    assert_eq!(std::fs::read_to_string(root.join("b.md"))?, "second b");
+
+    let (progress, events) = recorder();
+    checkout_with_progress(&root, &first, &progress)?;
+    let write = last(&events.lock().unwrap(), ProgressPhase::Write);
+    assert_eq!((write.done, write.total), (2, 2));
+    assert_eq!(std::fs::read_to_string(root.join("a.md"))?, "first a");
+
+    Ok(())
+}
+
+#[test]
+fn gc_reports_swept_objects() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    set_large_blob_threshold(&root, 16)?;
+    std::fs::write(root.join("map.png"), vec![7u8; 64])?;
+    commit(&root, "map")?;
+
+    let stray = root.join(".novel").join("objects").join("ff").join("00");
+    std::fs::create_dir_all(stray.parent().unwrap())?;
+    std::fs::write(&stray, "stray")?;
+
+    let (progress, events) = recorder();
+    let summary = gc_with_progress(&root, &progress)?;
+    assert_eq!(summary.removed_objects, 1);
+
+    let sweep = last(&events.lock().unwrap(), ProgressPhase::Sweep);
+    assert_eq!((sweep.done, sweep.total, sweep.bytes), (2, 2, 64 + 5));
+
+    Ok(())
+}
+
+#[test]
+fn bundle_progress_and_cancelled_unbundle() -> Result<()> {
+    let (_src_td, src) = setup();
+    let (_dst_td, dst) = setup();
+    let (_out_td, out) = setup();
+    let bundle_path = out.join("book.bundle");
+
+    init_repo(&src)?;
+    std::fs::write(src.join("a.md"), "a")?;
+    std::fs::write(src.join("b.md"), "bb")?;
+    commit(&src, "first")?;
+
+    let (progress, events) = recorder();
+    bundle_create_with_progress(&src, &bundle_path, &["HEAD"], None, &progress)?;
+    let pack = last(&events.lock().unwrap(), ProgressPhase::Pack);
+    assert_eq!((pack.done, pack.total, pack.bytes), (2, 2, 3));
+
+    init_repo(&dst)?;
+    let err =
+        bundle_unbundle_with_progress(&dst, &bundle_path, &cancel_on_first_event()).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::Cancelled));
+    assert!(log(&dst)?.is_empty());
+
+    let (progress, events) = recorder();
+    let summary = bundle_unbundle_with_progress(&dst, &bundle_path, &progress)?;
+    assert_eq!(summary.imported_blobs, 2);
+    let store = last(&events.lock().unwrap(), ProgressPhase::Store);
+    assert_eq!((store.done, store.total), (2, 2));
+
+    Ok(())
+}
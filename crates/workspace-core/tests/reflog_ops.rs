@@ -0,0 +1,90 @@
+use workspace_core::{
+    Result, WorkSpaceError, checkout, commit, init_repo, reflog, repo_state, undo_last_head_move,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+#[test]
+fn commit_and_checkout_are_recorded() -> Result<()> {
+    let (_td, root) = setup();
+
+    init_repo(&root)?;
+    std::fs::write(root.join("a.txt"), "a")?;
+    let first = commit(&root, "first")?;
+    std::fs::write(root.join("a.txt"), "b")?;
+    let second = commit(&root, "second")?;
+    checkout(&root, &first)?;
+
+    let entries = reflog(&root)?;
+    let reasons = entries
+        .iter()
+        .map(|e| e.reason.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(reasons, vec!["checkout", "commit", "commit"]);
+
+    assert_eq!(entries[0].ref_name, "HEAD");
+    assert_eq!(entries[0].old_node_id, Some(second.clone()));
+    assert_eq!(entries[0].new_node_id, Some(first.clone()));
+    assert_eq!(entries[1].message, "second");
+    assert_eq!(entries[1].old_node_id, Some(first.clone()));
+    assert_eq!(entries[2].old_node_id, None);
+    assert_eq!(entries[2].new_node_id, Some(first));
+
+    Ok(())
+}
+
+#[test]
+fn undo_restores_head_after_wrong_checkout() -> Result<()> {
+    let (_td, root) = setup();
+
+    init_repo(&root)?;
+    std::fs::write(root.join("chapter.txt"), "old draft")?;
+    let first = commit(&root, "first")?;
+    std::fs::write(root.join("chapter.txt"), "newest draft")?;
+    let newest = commit(&root, "newest")?;
+
+    checkout(&root, &first)?;
+    assert_eq!(
+        std::fs::read_to_string(root.join("chapter.txt"))?,
+        "old draft"
+    );
+
+    let restored = undo_last_head_move(&root)?;
+    assert_eq!(restored, newest);
+    assert_eq!(repo_state(&root)?.head, Some(newest.clone()));
+    assert_eq!(
+        std::fs::read_to_string(root.join("chapter.txt"))?,
+        "newest draft"
+    );
+
+    let entries = reflog(&root)?;
+    assert_eq!(entries[0].reason, "undo");
+    assert_eq!(entries[0].old_node_id, Some(first.clone()));
+    assert_eq!(entries[0].new_node_id, Some(newest));
+
+    // undo를 다시 undo 하면 되돌리기 전 위치로 간다
+    assert_eq!(undo_last_head_move(&root)?, first);
+
+    Ok(())
+}
+
+#[test]
+fn undo_without_previous_head_fails() -> Result<()> {
+    let (_td, root) = setup();
+
+    init_repo(&root)?;
+    let err = undo_last_head_move(&root).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::InvalidInput(_)));
+
+    std::fs::write(root.join("a.txt"), "a")?;
+    commit(&root, "first")?;
+    let err = undo_last_head_move(&root).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::InvalidInput(_)));
+
+    Ok(())
+}
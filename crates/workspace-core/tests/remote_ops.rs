@@ -0,0 +1,230 @@
+use workspace_core::{
+    Result, WorkSpaceError, checkout, commit, fetch, init_repo, log, pull, push, remote_add,
+    remote_remove, remotes, repo_state,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+#[test]
+fn remote_add_and_remove() -> Result<()> {
+    let (_td, root) = setup();
+    let (_nas_td, nas) = setup();
+
+    init_repo(&root)?;
+    remote_add(&root, "nas", nas.to_str().unwrap())?;
+
+    let err = remote_add(&root, "nas", nas.to_str().unwrap()).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::Conflict(_)));
+
+    let listed = remotes(&root)?;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].name, "nas");
+    assert!(listed[0].head.is_none());
+
+    remote_remove(&root, "nas")?;
+    assert!(remotes(&root)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn push_then_pull_into_another_clone() -> Result<()> {
+    let (_laptop_td, laptop) = setup();
+    let (_desktop_td, desktop) = setup();
+    let (_nas_td, nas) = setup();
+
+    init_repo(&nas)?;
+    init_repo(&laptop)?;
+    init_repo(&desktop)?;
+    remote_add(&laptop, "nas", nas.to_str().unwrap())?;
+    remote_add(&desktop, "nas", nas.to_str().unwrap())?;
+
+    std::fs::write(laptop.join("chapter1.txt"), "draft")?;
+    let first = commit(&laptop, "first")?;
+
+    let pushed = push(&laptop, "nas", false)?;
+    assert_eq!(pushed.old_head, None);
+    assert_eq!(pushed.new_head, first);
+    assert_eq!(pushed.pushed_nodes, 1);
+    assert_eq!(pushed.pushed_blobs, 1);
+    assert_eq!(repo_state(&nas)?.head, Some(first.clone()));
+    assert_eq!(remotes(&laptop)?[0].head, Some(first.clone()));
+
+    let pulled = pull(&desktop, "nas")?;
+    assert_eq!(pulled.new_head, Some(first.clone()));
+    assert_eq!(
+        std::fs::read_to_string(desktop.join("chapter1.txt"))?,
+        "draft"
+    );
+
+    std::fs::write(laptop.join("chapter1.txt"), "revised")?;
+    let second = commit(&laptop, "second")?;
+
+    // 두 번째 push는 원격에 없는 노드/blob만 보낸다
+    let pushed = push(&laptop, "nas", false)?;
+    assert_eq!(pushed.pushed_nodes, 1);
+    assert_eq!(pushed.pushed_blobs, 1);
+
+    let pulled = pull(&desktop, "nas")?;
+    assert_eq!(pulled.old_head, Some(first));
+    assert_eq!(pulled.new_head, Some(second.clone()));
+    assert_eq!(
+        std::fs::read_to_string(desktop.join("chapter1.txt"))?,
+        "revised"
+    );
+    assert_eq!(repo_state(&desktop)?.head, Some(second));
+
+    Ok(())
+}
+
+#[test]
+fn fetch_updates_tracking_head_without_touching_workspace() -> Result<()> {
+    let (_local_td, local) = setup();
+    let (_nas_td, nas) = setup();
+
+    init_repo(&nas)?;
+    std::fs::write(nas.join("a.txt"), "a")?;
+    let remote_head = commit(&nas, "remote work")?;
+
+    init_repo(&local)?;
+    remote_add(&local, "nas", nas.to_str().unwrap())?;
+
+    let fetched = fetch(&local, "nas")?;
+    assert_eq!(fetched.head, Some(remote_head.clone()));
+    assert_eq!(fetched.imported_nodes, 1);
+
+    assert!(repo_state(&local)?.head.is_none());
+    assert!(!local.join("a.txt").exists());
+    assert_eq!(remotes(&local)?[0].head, Some(remote_head.clone()));
+    assert_eq!(log(&local)?.len(), 1);
+
+    let again = fetch(&local, "nas")?;
+    assert_eq!(again.imported_nodes, 0);
+    assert_eq!(again.imported_blobs, 0);
+
+    checkout(&local, &remote_head)?;
+    assert_eq!(std::fs::read_to_string(local.join("a.txt"))?, "a");
+
+    Ok(())
+}
+
+#[test]
+fn pull_refuses_to_overwrite_uncommitted_changes() -> Result<()> {
+    let (_laptop_td, laptop) = setup();
+    let (_desktop_td, desktop) = setup();
+    let (_nas_td, nas) = setup();
+
+    init_repo(&nas)?;
+    init_repo(&laptop)?;
+    init_repo(&desktop)?;
+    remote_add(&laptop, "nas", nas.to_str().unwrap())?;
+    remote_add(&desktop, "nas", nas.to_str().unwrap())?;
+
+    std::fs::write(laptop.join("chapter1.txt"), "draft")?;
+    let first = commit(&laptop, "first")?;
+    push(&laptop, "nas", false)?;
+    pull(&desktop, "nas")?;
+
+    std::fs::write(laptop.join("chapter1.txt"), "revised")?;
+    let second = commit(&laptop, "second")?;
+    push(&laptop, "nas", false)?;
+
+    std::fs::write(desktop.join("chapter1.txt"), "local edit")?;
+    let err = pull(&desktop, "nas").unwrap_err();
+    assert!(
+        matches!(err, WorkSpaceError::DirtyWorkingTree(ref paths) if paths == &["chapter1.txt"])
+    );
+    assert_eq!(err.code(), "dirty_working_tree");
+
+    // fetch는 끝났으므로 노드는 받아 두고, 작업 트리와 HEAD는 그대로다
+    assert_eq!(remotes(&desktop)?[0].head, Some(second.clone()));
+    assert_eq!(repo_state(&desktop)?.head, Some(first));
+    assert_eq!(
+        std::fs::read_to_string(desktop.join("chapter1.txt"))?,
+        "local edit"
+    );
+
+    std::fs::write(desktop.join("chapter1.txt"), "draft")?;
+    assert_eq!(pull(&desktop, "nas")?.new_head, Some(second));
+
+    Ok(())
+}
+
+#[test]
+fn non_fast_forward_push_is_rejected_unless_forced() -> Result<()> {
+    let (_a_td, a) = setup();
+    let (_b_td, b) = setup();
+    let (_nas_td, nas) = setup();
+
+    init_repo(&nas)?;
+    init_repo(&a)?;
+    init_repo(&b)?;
+    remote_add(&a, "nas", nas.to_str().unwrap())?;
+    remote_add(&b, "nas", nas.to_str().unwrap())?;
+
+    std::fs::write(a.join("a.txt"), "from a")?;
+    commit(&a, "a")?;
+    push(&a, "nas", false)?;
+
+    std::fs::write(b.join("b.txt"), "from b")?;
+    let b_head = commit(&b, "b")?;
+
+    let err = push(&b, "nas", false).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NonFastForward(_)));
+
+    let err = pull(&b, "nas").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NonFastForward(_)));
+
+    let forced = push(&b, "nas", true)?;
+    assert!(forced.forced);
+    assert_eq!(repo_state(&nas)?.head, Some(b_head));
+
+    Ok(())
+}
+
+#[test]
+fn fetch_from_missing_repository_fails() -> Result<()> {
+    let (_local_td, local) = setup();
+    let (_empty_td, empty) = setup();
+
+    init_repo(&local)?;
+    remote_add(&local, "nas", empty.to_str().unwrap())?;
+
+    let err = fetch(&local, "nas").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NotARepository(_)));
+    assert!(!empty.join(".novel").exists());
+
+    Ok(())
+}
+
+#[test]
+fn push_to_checked_out_local_remote_is_refused() -> Result<()> {
+    let (_laptop_td, laptop) = setup();
+    let (_desktop_td, desktop) = setup();
+
+    init_repo(&desktop)?;
+    std::fs::write(desktop.join("chapter1.txt"), "desk draft")?;
+    let desktop_head = commit(&desktop, "desk")?;
+
+    init_repo(&laptop)?;
+    remote_add(&laptop, "desktop", desktop.to_str().unwrap())?;
+    pull(&laptop, "desktop")?;
+    std::fs::write(laptop.join("chapter1.txt"), "laptop draft")?;
+    commit(&laptop, "laptop")?;
+
+    // desktop의 HEAD만 옮기면 그쪽 작업 트리가 HEAD와 어긋난다
+    let err = push(&laptop, "desktop", false).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::Conflict(_)));
+    assert_eq!(repo_state(&desktop)?.head, Some(desktop_head));
+    assert_eq!(
+        std::fs::read_to_string(desktop.join("chapter1.txt"))?,
+        "desk draft"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use workspace_core::{Repository, Result, WorkSpaceError, log};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+#[test]
+fn handle_methods_match_free_functions() -> Result<()> {
+    let (_td, root) = setup();
+    let repo = Repository::init(&root)?;
+    assert_eq!(repo.root(), root.canonicalize()?);
+
+    std::fs::write(root.join("ch1.md"), "draft")?;
+    let first = repo.commit("first")?;
+    std::fs::write(root.join("ch1.md"), "second draft")?;
+    let second = repo.commit("second")?;
+
+    assert_eq!(repo.resolve_revision("HEAD~1")?, first);
+    assert_eq!(repo.repo_state()?.head, Some(second.clone()));
+    // 같은 저장소를 자유 함수로 열어도 같은 내용이 보인다(같은 밀리초의 노드는 순서가 정해지지 않는다)
+    let mut ids = log(&root)?.into_iter().map(|n| n.id).collect::<Vec<_>>();
+    ids.sort();
+    let mut expected = vec![second, first.clone()];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    repo.checkout(&first)?;
+    assert_eq!(std::fs::read_to_string(root.join("ch1.md"))?, "draft");
+    // undo는 안에서 checkout을 다시 부른다
+    repo.undo_last_head_move()?;
+    assert_eq!(
+        std::fs::read_to_string(root.join("ch1.md"))?,
+        "second draft"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn handle_is_shared_across_threads() -> Result<()> {
+    let (_td, root) = setup();
+    let repo = Arc::new(Repository::init(&root)?);
+    std::fs::write(root.join("ch1.md"), "draft")?;
+    repo.commit("first")?;
+
+    // 캐시된 연결을 다른 스레드가 쓰는 중이면 새 연결로 처리한다
+    let handles = (0..4)
+        .map(|_| {
+            let repo = repo.clone();
+            std::thread::spawn(move || -> Result<usize> {
+                let mut total = 0;
+                for _ in 0..10 {
+                    total += repo.log()?.len();
+                }
+                Ok(total)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap()?, 10);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn open_existing_requires_a_repository() -> Result<()> {
+    let (_td, root) = setup();
+
+    let err = Repository::open_existing(&root).err().unwrap();
+    assert!(matches!(err, WorkSpaceError::NotARepository(_)));
+
+    Repository::init(&root)?;
+    Repository::open_existing(&root)?;
+
+    Ok(())
+}
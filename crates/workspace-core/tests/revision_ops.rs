@@ -0,0 +1,263 @@
+use diesel::prelude::*;
+use workspace_core::schema::{node_parents, nodes, reflog};
+use workspace_core::{
+    Result, WorkSpaceError, checkout, commit, diff_nodes, init_repo, repo_state, resolve_revision,
+    tag_create, tag_delete, tags,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+fn connect(root: &std::path::Path) -> SqliteConnection {
+    let db = root.join(".novel").join("vcs.db");
+    SqliteConnection::establish(db.to_str().unwrap()).unwrap()
+}
+
+// 파일 내용만 바꿔 가며 n개 노드를 만든다 (오래된 것부터)
+fn commit_chain(root: &std::path::Path, n: usize) -> Result<Vec<String>> {
+    let mut ids = Vec::new();
+    for i in 0..n {
+        std::fs::write(root.join("chapter.txt"), format!("draft {}", i))?;
+        ids.push(commit(root, &format!("draft {}", i))?);
+    }
+    Ok(ids)
+}
+
+fn insert_node(conn: &mut SqliteConnection, id: &str, parents: &[&str]) {
+    diesel::insert_into(nodes::table)
+        .values((
+            nodes::id.eq(id),
+            nodes::message.eq("inserted"),
+            nodes::created_at_unix_ms.eq(0i64),
+        ))
+        .execute(conn)
+        .unwrap();
+
+    for (ord, parent) in parents.iter().enumerate() {
+        diesel::insert_into(node_parents::table)
+            .values((
+                node_parents::node_id.eq(id),
+                node_parents::parent_id.eq(*parent),
+                node_parents::ord.eq(ord as i32),
+            ))
+            .execute(conn)
+            .unwrap();
+    }
+}
+
+#[test]
+fn head_relative_revisions() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    let ids = commit_chain(&root, 3)?;
+
+    assert_eq!(resolve_revision(&root, "HEAD")?, ids[2]);
+    assert_eq!(resolve_revision(&root, "@")?, ids[2]);
+    assert_eq!(resolve_revision(&root, "HEAD~")?, ids[1]);
+    assert_eq!(resolve_revision(&root, "HEAD~2")?, ids[0]);
+    assert_eq!(resolve_revision(&root, "@^")?, ids[1]);
+    assert_eq!(resolve_revision(&root, "HEAD^^")?, ids[0]);
+    assert_eq!(resolve_revision(&root, "HEAD^0")?, ids[2]);
+    assert_eq!(resolve_revision(&root, &ids[2][..8])?, ids[2]);
+    assert_eq!(
+        resolve_revision(&root, &format!("{}~1", &ids[2][..8]))?,
+        ids[1]
+    );
+
+    let err = resolve_revision(&root, "HEAD~3").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NodeNotFound(_)));
+
+    let err = resolve_revision(&root, "HEAD~x").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::InvalidRevision { .. }));
+
+    let err = resolve_revision(&root, "no-such-thing").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NodeNotFound(_)));
+
+    // checkout / diff_nodes도 리비전을 받는다
+    let diff = diff_nodes(&root, "HEAD~2", "HEAD")?;
+    assert_eq!(diff.from, ids[0]);
+    assert_eq!(diff.to, ids[2]);
+    checkout(&root, "HEAD~1")?;
+    assert_eq!(repo_state(&root)?.head, Some(ids[1].clone()));
+
+    Ok(())
+}
+
+#[test]
+fn ambiguous_prefix_lists_candidates() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+
+    let a = format!("abcd1{}", "0".repeat(59));
+    let b = format!("abcd2{}", "0".repeat(59));
+    let mut conn = connect(&root);
+    insert_node(&mut conn, &a, &[]);
+    insert_node(&mut conn, &b, &[]);
+
+    let err = resolve_revision(&root, "abcd").unwrap_err();
+    match err {
+        WorkSpaceError::AmbiguousRevision {
+            revision,
+            candidates,
+        } => {
+            assert_eq!(revision, "abcd");
+            assert_eq!(candidates, vec![a.clone(), b.clone()]);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    assert_eq!(resolve_revision(&root, "abcd1")?, a);
+    assert_eq!(resolve_revision(&root, "ABCD2")?, b);
+
+    Ok(())
+}
+
+#[test]
+fn caret_selects_merge_parent() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+
+    let left = format!("1ef7{}", "0".repeat(60));
+    let right = format!("5167{}", "0".repeat(60));
+    let merge = format!("3e59{}", "0".repeat(60));
+    let mut conn = connect(&root);
+    insert_node(&mut conn, &left, &[]);
+    insert_node(&mut conn, &right, &[]);
+    insert_node(&mut conn, &merge, &[&left, &right]);
+
+    assert_eq!(resolve_revision(&root, &format!("{}^1", merge))?, left);
+    assert_eq!(resolve_revision(&root, "3e59^2")?, right);
+    assert!(resolve_revision(&root, "3e59^3").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn tags_name_nodes() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    let ids = commit_chain(&root, 2)?;
+
+    let tag = tag_create(&root, "first-draft", "HEAD~1")?;
+    assert_eq!(tag.node_id, ids[0]);
+    tag_create(&root, "v2", "HEAD")?;
+
+    let err = tag_create(&root, "v2", "HEAD~1").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::Conflict(_)));
+    assert!(tag_create(&root, "bad~name", "HEAD").is_err());
+
+    let listed = tags(&root)?;
+    assert_eq!(
+        listed.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(),
+        vec!["first-draft", "v2"]
+    );
+
+    assert_eq!(resolve_revision(&root, "first-draft")?, ids[0]);
+    assert_eq!(resolve_revision(&root, "tags/v2~1")?, ids[0]);
+
+    checkout(&root, "first-draft")?;
+    assert_eq!(
+        std::fs::read_to_string(root.join("chapter.txt"))?,
+        "draft 0"
+    );
+
+    tag_delete(&root, "first-draft")?;
+    assert!(resolve_revision(&root, "first-draft").is_err());
+    assert!(tag_delete(&root, "first-draft").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn date_revisions_use_reflog() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    let ids = commit_chain(&root, 2)?;
+
+    // 첫 커밋이 2026-01-01 00:00 UTC에 기록된 것으로 바꾼다
+    let mut conn = connect(&root);
+    let first_entry = reflog::table
+        .select(reflog::id)
+        .order(reflog::id.asc())
+        .first::<i32>(&mut conn)
+        .unwrap();
+    diesel::update(reflog::table.filter(reflog::id.eq(first_entry)))
+        .set(reflog::created_at_unix_ms.eq(1_767_225_600_000i64))
+        .execute(&mut conn)
+        .unwrap();
+
+    assert_eq!(resolve_revision(&root, "HEAD@{2026-01-02}")?, ids[0]);
+    assert_eq!(resolve_revision(&root, "@{2026-01-01 00:00:00}")?, ids[0]);
+    assert_eq!(resolve_revision(&root, "HEAD@{now}")?, ids[1]);
+    assert_eq!(resolve_revision(&root, "HEAD@{0 seconds ago}")?, ids[1]);
+    assert!(resolve_revision(&root, "HEAD@{2025-12-31}").is_err());
+
+    let err = resolve_revision(&root, "HEAD@{next tuesday}").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::InvalidRevision { .. }));
+
+    Ok(())
+}
+
+#[test]
+fn numeric_revisions_index_the_reflog() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    let ids = commit_chain(&root, 3)?;
+    checkout(&root, &ids[0])?;
+
+    // reflog: checkout(ids[0]), commit(ids[2]), commit(ids[1]), commit(ids[0])
+    assert_eq!(resolve_revision(&root, "HEAD@{0}")?, ids[0]);
+    assert_eq!(resolve_revision(&root, "HEAD@{1}")?, ids[2]);
+    assert_eq!(resolve_revision(&root, "@{2}")?, ids[1]);
+    assert_eq!(resolve_revision(&root, "HEAD@{1}~1")?, ids[1]);
+
+    let err = resolve_revision(&root, "HEAD@{4}").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NodeNotFound(_)));
+
+    Ok(())
+}
+
+#[test]
+fn reflog_revisions_reject_nodes_that_are_gone() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    commit_chain(&root, 3)?;
+
+    // 가장 오래된 기록이 지워진 노드를 가리키게 한다
+    let mut conn = connect(&root);
+    let first_entry = reflog::table
+        .select(reflog::id)
+        .order(reflog::id.asc())
+        .first::<i32>(&mut conn)
+        .unwrap();
+    diesel::update(reflog::table.filter(reflog::id.eq(first_entry)))
+        .set((
+            reflog::new_node_id.eq("deadbeef"),
+            reflog::created_at_unix_ms.eq(1_767_225_600_000i64),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+
+    for revision in ["HEAD@{2}", "HEAD@{2026-01-02}"] {
+        let err = resolve_revision(&root, revision).unwrap_err();
+        assert!(
+            matches!(&err, WorkSpaceError::NodeNotFound(id) if id == "deadbeef"),
+            "{}: {:?}",
+            revision,
+            err
+        );
+
+        // 체크아웃은 작업 트리를 건드리지 않고 실패한다
+        assert!(checkout(&root, revision).is_err());
+        assert_eq!(
+            std::fs::read_to_string(root.join("chapter.txt"))?,
+            "draft 2"
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use workspace_core::{
+    InitOptions, Result, WorkSpaceError, commit, gc, init_repo, init_repo_with_options,
+    search_history, set_large_blob_threshold, stage, unstage,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+fn indexed(root: &std::path::Path) -> i64 {
+    let db = root.join(".novel").join("vcs.db");
+    let mut conn = SqliteConnection::establish(db.to_str().unwrap()).unwrap();
+    diesel::select(sql::<BigInt>("(SELECT COUNT(*) FROM blob_search)"))
+        .get_result(&mut conn)
+        .unwrap()
+}
+
+#[test]
+fn finds_text_in_deleted_scenes() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("ch1.md"), "첫 장면. 주인공이 등대에 도착한다.")?;
+    std::fs::write(root.join("ch2.md"), "두 번째 장면")?;
+    let first = commit(&root, "first")?;
+
+    std::fs::remove_file(root.join("ch1.md"))?;
+    let second = commit(&root, "drop the lighthouse scene")?;
+
+    // 2글자 한국어 검색어도 찾는다
+    let hits = search_history(&root, "등대")?;
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].path, "ch1.md");
+    assert_eq!(hits[0].nodes, vec![first.clone()]);
+    assert!(hits[0].snippet.contains("등대에 도착"));
+
+    let hits = search_history(&root, "두 번째")?;
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].path, "ch2.md");
+    assert_eq!(hits[0].nodes, vec![second, first]);
+
+    assert!(search_history(&root, "없는 문장")?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn hits_are_ordered_by_newest_node() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("ch1.md"), "The Storm began at dusk.")?;
+    commit(&root, "first")?;
+    std::fs::write(root.join("ch1.md"), "The storm ended at dawn.")?;
+    let second = commit(&root, "second")?;
+
+    // ASCII 대소문자는 구분하지 않는다
+    let hits = search_history(&root, "STORM")?;
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0].nodes, vec![second]);
+    assert_eq!(hits[0].snippet, "The storm ended at dawn.");
+    assert_eq!(hits[1].snippet, "The Storm began at dusk.");
+
+    // LIKE 특수 문자는 글자 그대로 찾는다
+    std::fs::write(root.join("ch2.md"), "sale: 50% off")?;
+    commit(&root, "third")?;
+    let hits = search_history(&root, "50%")?;
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].path, "ch2.md");
+    assert!(search_history(&root, "5_%")?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn snippet_is_trimmed_around_the_match() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    let text = format!("{}\n한가운데 문장\n{}", "가".repeat(100), "나".repeat(100));
+    std::fs::write(root.join("ch1.md"), &text)?;
+    commit(&root, "first")?;
+
+    let hits = search_history(&root, "가운데")?;
+    assert_eq!(hits.len(), 1);
+    let snippet = &hits[0].snippet;
+    assert!(snippet.starts_with('…'));
+    assert!(snippet.ends_with('…'));
+    assert!(snippet.contains(" 한가운데 문장 "));
+    assert!(snippet.chars().count() < 100);
+
+    Ok(())
+}
+
+#[test]
+fn searches_large_blobs_and_skips_binary() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    set_large_blob_threshold(&root, 16)?;
+    std::fs::write(
+        root.join("ch1.md"),
+        "a long chapter stored outside the database",
+    )?;
+    std::fs::write(root.join("cover.png"), b"\x89PNG\x00\x00 outside")?;
+    commit(&root, "first")?;
+
+    let hits = search_history(&root, "outside")?;
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].path, "ch1.md");
+
+    // 마이그레이션은 objects 파일을 읽지 못하므로 검색할 때 색인한다
+    let db = root.join(".novel").join("vcs.db");
+    let mut conn = SqliteConnection::establish(db.to_str().unwrap()).unwrap();
+    diesel::sql_query("DELETE FROM blob_search")
+        .execute(&mut conn)
+        .unwrap();
+    let hits = search_history(&root, "outside")?;
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].path, "ch1.md");
+    assert_eq!(indexed(&root), 1);
+
+    Ok(())
+}
+
+#[test]
+fn gc_drops_unreachable_blobs_from_index() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("ch1.md"), "base")?;
+    commit(&root, "base")?;
+
+    // stage했다 내린 내용은 어느 노드에도 없다
+    std::fs::write(root.join("ch1.md"), "abandoned draft")?;
+    stage(&root, &["ch1.md"])?;
+    unstage(&root, &["ch1.md"])?;
+    assert_eq!(indexed(&root), 2);
+    assert!(search_history(&root, "abandoned")?.is_empty());
+
+    assert_eq!(gc(&root)?.removed_blobs, 1);
+    assert_eq!(indexed(&root), 1);
+    assert_eq!(search_history(&root, "base")?.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn encrypted_repositories_are_not_indexed() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo_with_options(
+        &root,
+        &InitOptions {
+            passphrase: Some("secret".to_string()),
+        },
+    )?;
+    std::fs::write(root.join("ch1.md"), "hidden plot twist")?;
+    commit(&root, "first")?;
+    assert_eq!(indexed(&root), 0);
+
+    let err = search_history(&root, "plot").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::InvalidInput(_)));
+
+    Ok(())
+}
+
+#[test]
+fn empty_query_is_rejected() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+
+    let err = search_history(&root, "   ").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::InvalidInput(_)));
+
+    Ok(())
+}
+
+#[test]
+fn results_are_limited_to_the_newest_matches() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    for i in 0..105 {
+        std::fs::write(
+            root.join(format!("scene{:03}.md", i)),
+            format!("needle {}", i),
+        )?;
+    }
+    commit(&root, "many scenes")?;
+    for i in 0..105 {
+        std::fs::remove_file(root.join(format!("scene{:03}.md", i)))?;
+    }
+    std::fs::write(root.join("final.md"), "needle newest")?;
+    let newest = commit(&root, "one scene")?;
+
+    let hits = search_history(&root, "needle")?;
+    assert_eq!(hits.len(), 100);
+    assert_eq!(hits[0].path, "final.md");
+    assert_eq!(hits[0].nodes, vec![newest]);
+    assert_eq!(hits[0].snippet, "needle newest");
+
+    Ok(())
+}
@@ -0,0 +1,236 @@
+use workspace_core::{
+    DiffKind, Result, StagedFile, WorkSpaceError, commit, commit_staged, diff_workdir, gc,
+    init_repo, log, stage, staged, unstage,
+};
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+fn staged_file(path: &str, kind: DiffKind) -> StagedFile {
+    StagedFile {
+        path: path.to_string(),
+        kind,
+    }
+}
+
+// ch1.md, notes.md, old.md가 들어 있는 첫 노드
+fn first_snapshot(root: &std::path::Path) -> Result<String> {
+    init_repo(root)?;
+    std::fs::write(root.join("ch1.md"), "chapter one")?;
+    std::fs::write(root.join("notes.md"), "research")?;
+    std::fs::write(root.join("old.md"), "cut scene")?;
+    commit(root, "first")
+}
+
+// 바뀐 (경로, 종류) 목록
+fn workdir_changes(root: &std::path::Path) -> Result<Vec<(String, DiffKind)>> {
+    Ok(diff_workdir(root, None)?
+        .files
+        .into_iter()
+        .map(|f| (f.path, f.kind))
+        .collect())
+}
+
+#[test]
+fn commit_staged_takes_only_staged_changes() -> Result<()> {
+    let (_td, root) = setup();
+    first_snapshot(&root)?;
+
+    std::fs::write(root.join("ch1.md"), "chapter one, revised")?;
+    std::fs::write(root.join("notes.md"), "research in flux")?;
+    std::fs::write(root.join("ch2.md"), "chapter two")?;
+    std::fs::remove_file(root.join("old.md"))?;
+
+    stage(&root, &["ch1.md", "./ch2.md", "old.md"])?;
+    assert_eq!(
+        staged(&root)?,
+        vec![
+            staged_file("ch1.md", DiffKind::Modified),
+            staged_file("ch2.md", DiffKind::Added),
+            staged_file("old.md", DiffKind::Removed),
+        ]
+    );
+
+    // stage 뒤에 바뀐 내용은 들어가지 않는다
+    std::fs::write(root.join("ch2.md"), "chapter two, later")?;
+    commit_staged(&root, "revise chapter one")?;
+    assert!(staged(&root)?.is_empty());
+    assert_eq!(log(&root)?[0].message, "revise chapter one");
+
+    assert_eq!(
+        workdir_changes(&root)?,
+        vec![
+            ("ch2.md".to_string(), DiffKind::Modified),
+            ("notes.md".to_string(), DiffKind::Modified),
+        ]
+    );
+    let diff = diff_workdir(&root, None)?;
+    assert_eq!(diff.files[0].before_text.as_deref(), Some("chapter two"));
+
+    Ok(())
+}
+
+#[test]
+fn commit_staged_handles_file_replaced_by_directory() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("notes"), "flat notes")?;
+    std::fs::create_dir(root.join("drafts"))?;
+    std::fs::write(root.join("drafts/a.md"), "draft")?;
+    commit(&root, "first")?;
+
+    // 파일 -> 디렉토리
+    std::fs::remove_file(root.join("notes"))?;
+    std::fs::create_dir(root.join("notes"))?;
+    std::fs::write(root.join("notes/a.md"), "split notes")?;
+    stage(&root, &["notes/a.md"])?;
+    commit_staged(&root, "split notes")?;
+    assert!(workdir_changes(&root)?.is_empty());
+
+    // 디렉토리 -> 파일
+    std::fs::remove_dir_all(root.join("drafts"))?;
+    std::fs::write(root.join("drafts"), "merged drafts")?;
+    stage(&root, &["drafts"])?;
+    commit_staged(&root, "merge drafts")?;
+    assert!(workdir_changes(&root)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn unstage_and_restage_replace_entries() -> Result<()> {
+    let (_td, root) = setup();
+    first_snapshot(&root)?;
+
+    std::fs::write(root.join("ch1.md"), "draft a")?;
+    std::fs::write(root.join("notes.md"), "more research")?;
+    stage(&root, &["ch1.md", "notes.md"])?;
+    unstage(&root, &["notes.md"])?;
+    assert_eq!(
+        staged(&root)?,
+        vec![staged_file("ch1.md", DiffKind::Modified)]
+    );
+
+    // 되돌려 HEAD와 같아진 파일은 목록에서 빠지고, 그것만 올려 두면 커밋할 것이 없다
+    std::fs::write(root.join("ch1.md"), "chapter one")?;
+    stage(&root, &["ch1.md"])?;
+    assert!(staged(&root)?.is_empty());
+    assert!(matches!(
+        commit_staged(&root, "noop").unwrap_err(),
+        WorkSpaceError::NothingToCommit(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn stage_rejects_bad_paths_and_empty_index() -> Result<()> {
+    let (_td, root) = setup();
+    first_snapshot(&root)?;
+
+    assert_eq!(
+        commit_staged(&root, "nothing").unwrap_err().code(),
+        "invalid_input"
+    );
+    assert!(matches!(
+        stage(&root, &["../outside.md"]).unwrap_err(),
+        WorkSpaceError::PathEscape(_)
+    ));
+    assert_eq!(
+        stage(&root, &[".novel/vcs.db"]).unwrap_err().code(),
+        "invalid_input"
+    );
+
+    // 하나라도 틀리면 아무것도 올리지 않는다
+    std::fs::write(root.join("ch1.md"), "changed")?;
+    assert_eq!(
+        stage(&root, &["ch1.md", "missing.md"]).unwrap_err().code(),
+        "not_found"
+    );
+    assert!(staged(&root)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn stage_directory_takes_files_under_it() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::create_dir_all(root.join("drafts/old"))?;
+    std::fs::write(root.join("drafts/a.md"), "a")?;
+    std::fs::write(root.join("drafts/old/b.md"), "b")?;
+    std::fs::write(root.join("ch1.md"), "one")?;
+    commit(&root, "first")?;
+
+    std::fs::write(root.join("drafts/a.md"), "a2")?;
+    std::fs::remove_file(root.join("drafts/old/b.md"))?;
+    std::fs::write(root.join("drafts/c.md"), "c")?;
+    std::fs::write(root.join("ch1.md"), "changed")?;
+
+    stage(&root, &["drafts/"])?;
+    assert_eq!(
+        staged(&root)?,
+        vec![
+            staged_file("drafts/a.md", DiffKind::Modified),
+            staged_file("drafts/c.md", DiffKind::Added),
+            staged_file("drafts/old/b.md", DiffKind::Removed),
+        ]
+    );
+
+    // 디스크에서 사라진 디렉토리도 HEAD에 있던 파일을 삭제로 올린다
+    unstage(&root, &["drafts/old/b.md"])?;
+    std::fs::remove_dir(root.join("drafts/old"))?;
+    stage(&root, &["drafts/old"])?;
+    assert!(staged(&root)?.contains(&staged_file("drafts/old/b.md", DiffKind::Removed)));
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn stage_rejects_symbolic_links() -> Result<()> {
+    let (_td, root) = setup();
+    let (_outside_td, outside) = setup();
+    first_snapshot(&root)?;
+    std::fs::write(outside.join("secret.md"), "outside the workspace")?;
+
+    std::os::unix::fs::symlink(outside.join("secret.md"), root.join("link.md"))?;
+    std::os::unix::fs::symlink(&outside, root.join("linked"))?;
+
+    for path in ["link.md", "linked", "linked/secret.md"] {
+        assert_eq!(stage(&root, &[path]).unwrap_err().code(), "invalid_input");
+    }
+    assert!(staged(&root)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn plain_commit_clears_index_and_gc_keeps_staged_blobs() -> Result<()> {
+    let (_td, root) = setup();
+    first_snapshot(&root)?;
+
+    std::fs::write(root.join("ch1.md"), "staged version")?;
+    stage(&root, &["ch1.md"])?;
+    std::fs::write(root.join("ch1.md"), "working version")?;
+
+    assert_eq!(gc(&root)?.removed_blobs, 0);
+    commit_staged(&root, "staged")?;
+    assert_eq!(
+        workdir_changes(&root)?,
+        vec![("ch1.md".to_string(), DiffKind::Modified)]
+    );
+
+    stage(&root, &["notes.md"])?;
+    std::fs::write(root.join("notes.md"), "edited after staging")?;
+    stage(&root, &["notes.md"])?;
+    commit(&root, "everything")?;
+    assert!(staged(&root)?.is_empty());
+    assert!(workdir_changes(&root)?.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,337 @@
+use std::path::Path;
+
+use workspace_core::storage::{self, FsStorage, MemoryStorage, ObjectStore, SqliteStorage, Tree};
+use workspace_core::{DiffKind, NodeKind, Result, StoredNode, WorkSpaceError};
+
+// 백엔드마다 같은 테스트를 돌린다. open은 (작업 트리 루트, 백엔드 자리 디렉터리)를 받는다
+macro_rules! conformance {
+    ($backend:ident, $open:expr) => {
+        mod $backend {
+            use super::*;
+
+            fn setup() -> (tempfile::TempDir, std::path::PathBuf, Box<dyn ObjectStore>) {
+                let td = tempfile::tempdir().unwrap();
+                let root = td.path().join("work");
+                std::fs::create_dir_all(&root).unwrap();
+                let open: fn(&Path, &Path) -> Result<Box<dyn ObjectStore>> = $open;
+                let store = open(&root, &td.path().join("store")).unwrap();
+
+                (td, root, store)
+            }
+
+            #[test]
+            fn commit_and_log() -> Result<()> {
+                let (_td, root, mut store) = setup();
+                commit_and_log_case(&root, store.as_mut())
+            }
+
+            #[test]
+            fn commit_rejects_empty_message_and_unchanged_tree() -> Result<()> {
+                let (_td, root, mut store) = setup();
+                commit_rejections_case(&root, store.as_mut())
+            }
+
+            #[test]
+            fn checkout_restores_files() -> Result<()> {
+                let (_td, root, mut store) = setup();
+                checkout_case(&root, store.as_mut())
+            }
+
+            #[test]
+            fn diff_nodes_reports_changes() -> Result<()> {
+                let (_td, root, mut store) = setup();
+                diff_case(&root, store.as_mut())
+            }
+
+            #[test]
+            fn blobs_and_refs_round_trip() -> Result<()> {
+                let (_td, root, mut store) = setup();
+                blobs_and_refs_case(&root, store.as_mut())
+            }
+
+            #[test]
+            fn put_node_requires_parents_and_blobs() -> Result<()> {
+                let (_td, _root, mut store) = setup();
+                put_node_checks_case(store.as_mut())
+            }
+        }
+    };
+}
+
+conformance!(memory, |_root, _dir| Ok(Box::new(MemoryStorage::new())));
+conformance!(fs, |_root, dir| Ok(Box::new(FsStorage::open(dir)?)));
+conformance!(sqlite, |root, _dir| Ok(Box::new(SqliteStorage::open(
+    root
+)?)));
+
+fn commit_and_log_case(root: &Path, store: &mut dyn ObjectStore) -> Result<()> {
+    assert!(storage::log(store)?.is_empty());
+    assert_eq!(store.head()?, None);
+
+    std::fs::write(root.join("ch1.md"), "draft")?;
+    let first = storage::commit(store, root, "first")?;
+    std::fs::write(root.join("ch1.md"), "second draft")?;
+    let second = storage::commit(store, root, "  second  ")?;
+
+    assert_eq!(store.head()?, Some(second.clone()));
+
+    let log = storage::log(store)?;
+    assert_eq!(log.len(), 2);
+    let second_node = log.iter().find(|n| n.id == second).unwrap();
+    assert_eq!(second_node.message, "second");
+    assert_eq!(second_node.parents, vec![first.clone()]);
+    assert_eq!(second_node.kind, NodeKind::Manual);
+    let first_node = log.iter().find(|n| n.id == first).unwrap();
+    assert!(first_node.parents.is_empty());
+
+    Ok(())
+}
+
+fn commit_rejections_case(root: &Path, store: &mut dyn ObjectStore) -> Result<()> {
+    std::fs::write(root.join("ch1.md"), "draft")?;
+
+    let err = storage::commit(store, root, "   ").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::EmptyMessage));
+
+    let first = storage::commit(store, root, "first")?;
+    let err = storage::commit(store, root, "again").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NothingToCommit(id) if id == first));
+
+    Ok(())
+}
+
+fn checkout_case(root: &Path, store: &mut dyn ObjectStore) -> Result<()> {
+    std::fs::create_dir_all(root.join("drafts"))?;
+    std::fs::write(root.join("drafts/ch1.md"), "draft")?;
+    let first = storage::commit(store, root, "first")?;
+
+    std::fs::write(root.join("drafts/ch1.md"), "rewrite")?;
+    std::fs::write(root.join("ch2.md"), "new chapter")?;
+    let second = storage::commit(store, root, "second")?;
+
+    storage::checkout(store, root, &first)?;
+    assert_eq!(
+        std::fs::read_to_string(root.join("drafts/ch1.md"))?,
+        "draft"
+    );
+    assert!(!root.join("ch2.md").exists());
+    assert_eq!(store.head()?, Some(first));
+
+    storage::checkout(store, root, &second)?;
+    assert_eq!(std::fs::read_to_string(root.join("ch2.md"))?, "new chapter");
+
+    let err = storage::checkout(store, root, "0000").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NodeNotFound(_)));
+
+    Ok(())
+}
+
+fn diff_case(root: &Path, store: &mut dyn ObjectStore) -> Result<()> {
+    std::fs::write(root.join("a.md"), "one\n")?;
+    std::fs::write(root.join("b.md"), "gone\n")?;
+    let first = storage::commit(store, root, "first")?;
+    std::fs::write(root.join("a.md"), "one\ntwo\n")?;
+    std::fs::remove_file(root.join("b.md"))?;
+    std::fs::write(root.join("c.md"), "new\n")?;
+    let second = storage::commit(store, root, "second")?;
+
+    let diff = storage::diff_nodes(store, &first, &second)?;
+    let kinds = diff
+        .files
+        .iter()
+        .map(|f| (f.path.as_str(), f.kind.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            ("a.md", DiffKind::Modified),
+            ("b.md", DiffKind::Removed),
+            ("c.md", DiffKind::Added),
+        ]
+    );
+    assert!(diff.files[0].unified.as_deref().unwrap().contains("+two"));
+
+    assert!(
+        storage::diff_nodes(store, &second, &second)?
+            .files
+            .is_empty()
+    );
+
+    Ok(())
+}
+
+fn blobs_and_refs_case(root: &Path, store: &mut dyn ObjectStore) -> Result<()> {
+    let blob_id = "ab".repeat(32);
+    assert!(!store.has_blob(&blob_id)?);
+    assert_eq!(store.blob(&blob_id)?, None);
+    store.put_blob(&blob_id, b"content")?;
+    // 같은 id는 다시 쓰지 않는다
+    store.put_blob(&blob_id, b"other")?;
+    assert!(store.has_blob(&blob_id)?);
+    assert_eq!(store.blob(&blob_id)?, Some(b"content".to_vec()));
+
+    std::fs::write(root.join("ch1.md"), "draft")?;
+    let node = storage::commit(store, root, "first")?;
+
+    store.set_ref("tags/v1", &node)?;
+    assert_eq!(
+        store.refs()?.into_iter().collect::<Vec<_>>(),
+        vec![("tags/v1".to_string(), node.clone())]
+    );
+    assert!(store.delete_ref("tags/v1")?);
+    assert!(!store.delete_ref("tags/v1")?);
+    assert!(store.refs()?.is_empty());
+
+    // 점이 든 이름끼리 임시 파일이 겹치지 않는다
+    store.set_ref("tags/v1.0", &node)?;
+    store.set_ref("tags/v1.5", &node)?;
+    assert_eq!(
+        store.refs()?.into_keys().collect::<Vec<_>>(),
+        vec!["tags/v1.0".to_string(), "tags/v1.5".to_string()]
+    );
+    assert!(store.delete_ref("tags/v1.0")?);
+    assert!(store.delete_ref("tags/v1.5")?);
+
+    let err = store.set_ref("tags/v2", "0000").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NodeNotFound(_)));
+    let err = store.set_head("0000").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NodeNotFound(_)));
+
+    Ok(())
+}
+
+fn put_node_checks_case(store: &mut dyn ObjectStore) -> Result<()> {
+    let node = StoredNode {
+        id: "1".repeat(64),
+        parents: vec!["2".repeat(64)],
+        message: "orphan".to_string(),
+        created_at_unix_ms: 1,
+        tree_id: "3".repeat(64),
+        kind: NodeKind::Manual,
+    };
+    let err = store.put_node(&node, &Tree::new()).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NodeNotFound(_)));
+
+    let root_node = StoredNode {
+        parents: Vec::new(),
+        ..node
+    };
+    let tree = Tree::from([("ch1.md".to_string(), "4".repeat(64))]);
+    let err = store.put_node(&root_node, &tree).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NotFound(_)));
+    assert_eq!(store.node(&root_node.id)?, None);
+
+    store.put_blob(&"4".repeat(64), b"draft")?;
+    store.put_node(&root_node, &tree)?;
+    assert_eq!(store.node(&root_node.id)?, Some(root_node.clone()));
+    assert_eq!(store.tree(&root_node.id)?, tree);
+
+    Ok(())
+}
+
+#[test]
+fn sqlite_backend_shares_tables_with_repository() -> Result<()> {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path();
+    workspace_core::init_repo(root)?;
+
+    std::fs::write(root.join("ch1.md"), "draft")?;
+    let first = workspace_core::commit(root, "from repository")?;
+
+    let mut store = SqliteStorage::open(root)?;
+    assert_eq!(store.head()?, Some(first.clone()));
+    std::fs::write(root.join("ch1.md"), "second draft")?;
+    let second = storage::commit(&mut store, root, "from storage")?;
+
+    let log = workspace_core::log(root)?;
+    assert_eq!(log.len(), 2);
+    let node = log.iter().find(|n| n.id == second).unwrap();
+    assert_eq!(node.message, "from storage");
+    assert_eq!(node.parents, vec![first.clone()]);
+    assert_eq!(
+        workspace_core::diff_nodes(root, &first, &second)?
+            .files
+            .len(),
+        1
+    );
+
+    Ok(())
+}
+
+#[test]
+fn sqlite_backend_works_on_encrypted_repositories() -> Result<()> {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path();
+    workspace_core::init_repo_with_options(
+        root,
+        &workspace_core::InitOptions {
+            passphrase: Some("secret".to_string()),
+        },
+    )?;
+
+    let mut store = SqliteStorage::open(root)?;
+    std::fs::write(root.join("ch1.md"), "hidden")?;
+    let node = storage::commit(&mut store, root, "sealed message")?;
+
+    assert_eq!(workspace_core::log(root)?[0].message, "sealed message");
+    assert_eq!(storage::log(&mut store)?[0].id, node);
+
+    Ok(())
+}
+
+#[test]
+fn sqlite_backend_writes_take_the_repository_lock() -> Result<()> {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path();
+    workspace_core::init_repo(root)?;
+
+    std::fs::write(root.join("ch1.md"), "draft")?;
+    let mut store = SqliteStorage::open(root)?;
+    let first = storage::commit(&mut store, root, "first")?;
+
+    // 다른 프로세스처럼 잠금 파일에 OS 잠금을 건다
+    let held = std::fs::File::create(root.join(".novel/lock"))?;
+    held.lock()?;
+
+    assert_eq!(store.head()?, Some(first.clone()));
+    let err = store.set_ref("tags/v1", &first).unwrap_err();
+    assert!(matches!(err, WorkSpaceError::RepoLocked(_)));
+    std::fs::write(root.join("ch1.md"), "second draft")?;
+    let err = storage::commit(&mut store, root, "second").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::RepoLocked(_)));
+
+    drop(held);
+    let second = storage::commit(&mut store, root, "second")?;
+    storage::checkout(&mut store, root, &first)?;
+    assert_eq!(std::fs::read_to_string(root.join("ch1.md"))?, "draft");
+    assert!(!root.join(".novel/checkout").exists());
+    assert_eq!(workspace_core::reflog(root)?[0].new_node_id, Some(first));
+    assert_ne!(second, store.head()?.unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn sqlite_backend_records_node_stats() -> Result<()> {
+    use diesel::prelude::*;
+    use workspace_core::schema::node_stats;
+
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path();
+    workspace_core::init_repo(root)?;
+
+    std::fs::write(root.join("ch1.md"), "three short words")?;
+    let mut store = SqliteStorage::open(root)?;
+    let node = storage::commit(&mut store, root, "first")?;
+
+    let db = root.join(".novel").join("vcs.db");
+    let mut conn = SqliteConnection::establish(db.to_str().unwrap()).unwrap();
+    let words = node_stats::table
+        .filter(node_stats::node_id.eq(&node))
+        .select(node_stats::word_count)
+        .first::<i64>(&mut conn)
+        .unwrap();
+    assert_eq!(words, 3);
+
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use diesel::prelude::*;
+use workspace_core::schema::{node_stats, nodes};
+use workspace_core::{
+    ProgressBucket, ProgressPoint, Result, checkout, commit, init_repo, progress_series,
+};
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+// 2026-03-02 00:00 UTC, 월요일
+const MONDAY_MS: i64 = 1_772_409_600_000;
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+    let td = tempfile::tempdir().unwrap();
+    let root = td.path().to_path_buf();
+
+    (td, root)
+}
+
+fn connect(root: &std::path::Path) -> SqliteConnection {
+    let db = root.join(".novel").join("vcs.db");
+    SqliteConnection::establish(db.to_str().unwrap()).unwrap()
+}
+
+// 같은 밀리초에 커밋되지 않도록 시각을 직접 정한다
+fn set_created_at(root: &std::path::Path, node_id: &str, at: i64) {
+    diesel::update(nodes::table.filter(nodes::id.eq(node_id)))
+        .set(nodes::created_at_unix_ms.eq(at))
+        .execute(&mut connect(root))
+        .unwrap();
+}
+
+fn point(
+    start: i64,
+    date: &str,
+    node_id: &str,
+    words: i64,
+    chars: i64,
+    snapshots: usize,
+) -> ProgressPoint {
+    ProgressPoint {
+        bucket_start_unix_ms: start,
+        date: date.to_string(),
+        node_id: node_id.to_string(),
+        words,
+        chars,
+        snapshots,
+    }
+}
+
+#[test]
+fn daily_series_keeps_the_last_snapshot_of_each_day() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("ch1.md"), "비가 내렸다.")?;
+    let first = commit(&root, "first")?;
+    std::fs::write(root.join("ch1.md"), "비가 내렸다. 그는 우산을 접었다.")?;
+    let second = commit(&root, "second")?;
+    std::fs::write(root.join("ch2.md"), "다음 날")?;
+    let third = commit(&root, "third")?;
+
+    set_created_at(&root, &first, MONDAY_MS + 1000);
+    set_created_at(&root, &second, MONDAY_MS + 2000);
+    set_created_at(&root, &third, MONDAY_MS + DAY_MS + 1000);
+
+    assert_eq!(
+        progress_series(&root, ProgressBucket::Day)?,
+        vec![
+            point(MONDAY_MS, "2026-03-02", &second, 5, 15, 2),
+            point(MONDAY_MS + DAY_MS, "2026-03-03", &third, 7, 18, 1),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn weeks_start_on_monday() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("ch1.md"), "one")?;
+    let sunday = commit(&root, "sunday")?;
+    std::fs::write(root.join("ch1.md"), "one two")?;
+    let monday = commit(&root, "monday")?;
+
+    set_created_at(&root, &sunday, MONDAY_MS + 6 * DAY_MS + 1000);
+    set_created_at(&root, &monday, MONDAY_MS + 7 * DAY_MS + 1000);
+
+    assert_eq!(
+        progress_series(&root, ProgressBucket::Week)?,
+        vec![
+            point(MONDAY_MS, "2026-03-02", &sunday, 1, 3, 1),
+            point(MONDAY_MS + 7 * DAY_MS, "2026-03-09", &monday, 2, 6, 1),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn follows_first_parents_from_head() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("ch1.md"), "base")?;
+    let base = commit(&root, "base")?;
+    std::fs::write(root.join("ch1.md"), "abandoned branch text")?;
+    let branch = commit(&root, "branch")?;
+    checkout(&root, &base)?;
+    std::fs::write(root.join("ch1.md"), "base grows")?;
+    let main = commit(&root, "main")?;
+
+    set_created_at(&root, &base, MONDAY_MS);
+    set_created_at(&root, &branch, MONDAY_MS + DAY_MS);
+    set_created_at(&root, &main, MONDAY_MS + 2 * DAY_MS);
+
+    let series = progress_series(&root, ProgressBucket::Day)?;
+    assert_eq!(
+        series.iter().map(|p| p.node_id.clone()).collect::<Vec<_>>(),
+        vec![base, main]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn missing_stats_are_counted_and_binary_files_ignored() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+    std::fs::write(root.join("ch1.md"), "hello world")?;
+    std::fs::write(root.join("cover.png"), [0u8, 159, 146, 150, 0, 1])?;
+    let first = commit(&root, "first")?;
+
+    // 통계 테이블 이전에 만든 노드처럼 만든다
+    diesel::delete(node_stats::table)
+        .execute(&mut connect(&root))
+        .unwrap();
+
+    let series = progress_series(&root, ProgressBucket::Day)?;
+    assert_eq!(series.len(), 1);
+    assert_eq!(
+        (series[0].node_id.as_str(), series[0].words, series[0].chars),
+        (first.as_str(), 2, 10)
+    );
+
+    // 읽기 경로라 센 값을 저장하지 않는다
+    let cached = node_stats::table
+        .count()
+        .get_result::<i64>(&mut connect(&root))
+        .unwrap();
+    assert_eq!(cached, 0);
+
+    Ok(())
+}
+
+#[test]
+fn empty_repository_has_no_points() -> Result<()> {
+    let (_td, root) = setup();
+    init_repo(&root)?;
+
+    assert!(progress_series(&root, ProgressBucket::Week)?.is_empty());
+
+    Ok(())
+}
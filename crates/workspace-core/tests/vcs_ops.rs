@@ -1,4 +1,9 @@
-use workspace_core::{DiffKind, Result, checkout, commit, diff_nodes, init_repo, log, repo_state};
+use workspace_core::{
+    CommitOptions, DiffKind, Result, WorkSpaceError, checkout, commit, commit_with_options,
+    diff_nodes, init_repo, log, repo_state,
+};
+
+const ALLOW_EMPTY: CommitOptions = CommitOptions { allow_empty: true };
 
 fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
     let td = tempfile::tempdir().unwrap();
@@ -44,7 +49,7 @@ fn second_commit_links_first_as_parent() -> Result<()> {
     init_repo(&root)?;
 
     let first_commit_id = commit(&root, "initial commit")?;
-    let second_commit_id = commit(&root, "second commit")?;
+    let second_commit_id = commit_with_options(&root, "second commit", ALLOW_EMPTY)?;
 
     let nodes = log(&root)?;
 
@@ -63,9 +68,11 @@ fn commit_rejects_empty_message() -> Result<()> {
 
     let first_commit_err = commit(&root, "").unwrap_err();
 
-    assert!(
-        matches!(first_commit_err, workspace_core::WorkSpaceError::Io(ref e) if e.kind() == std::io::ErrorKind::InvalidInput)
-    );
+    assert!(matches!(
+        first_commit_err,
+        workspace_core::WorkSpaceError::EmptyMessage
+    ));
+    assert_eq!(first_commit_err.code(), "empty_message");
 
     Ok(())
 }
@@ -209,7 +216,7 @@ fn diff_nodes_return_no_diff() -> Result<()> {
 
     let first_commit_id = commit(&root, "initial commit")?;
 
-    let second_commit_id = commit(&root, "second commit")?;
+    let second_commit_id = commit_with_options(&root, "second commit", ALLOW_EMPTY)?;
 
     let diff = diff_nodes(&root, &first_commit_id, &second_commit_id)?;
 
@@ -218,6 +225,71 @@ fn diff_nodes_return_no_diff() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn commit_rejects_unchanged_tree() -> Result<()> {
+    let (_td, root) = setup();
+
+    init_repo(&root)?;
+    std::fs::write(root.join("hello.txt"), "hello world")?;
+    let first_commit_id = commit(&root, "initial commit")?;
+
+    let err = commit(&root, "nothing changed").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NothingToCommit(ref head) if *head == first_commit_id));
+    assert_eq!(repo_state(&root)?.head, Some(first_commit_id.clone()));
+    assert_eq!(log(&root)?.len(), 1);
+
+    // 예전 내용으로 되돌리는 커밋은 HEAD와 트리가 다르므로 받아들인다. 트리는 첫 커밋과 같다
+    std::fs::write(root.join("hello.txt"), "changed")?;
+    commit(&root, "change")?;
+    std::fs::write(root.join("hello.txt"), "hello world")?;
+    let reverted = commit(&root, "revert")?;
+
+    let diff = diff_nodes(&root, &first_commit_id, &reverted)?;
+    assert!(diff.files.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn tree_ids_are_backfilled_for_old_nodes() -> Result<()> {
+    use diesel::prelude::*;
+    use workspace_core::schema::nodes::dsl as nodes_dsl;
+
+    let (_td, root) = setup();
+
+    init_repo(&root)?;
+    std::fs::create_dir(root.join("part1"))?;
+    std::fs::write(root.join("part1").join("chapter.txt"), "draft")?;
+    commit(&root, "initial commit")?;
+
+    // tree_id 컬럼이 생기기 전에 만들어진 노드처럼 비워 둔다
+    let db = root.join(".novel").join("vcs.db");
+    let mut conn = SqliteConnection::establish(db.to_str().unwrap()).unwrap();
+    diesel::update(nodes_dsl::nodes)
+        .set(nodes_dsl::tree_id.eq(None::<String>))
+        .execute(&mut conn)
+        .unwrap();
+
+    // 읽기(log 등)는 계산만 하고 채우지 않는다: 잠금 없이 쓰지 않도록
+    assert_eq!(log(&root)?.len(), 1);
+    let stored = nodes_dsl::nodes
+        .select(nodes_dsl::tree_id)
+        .load::<Option<String>>(&mut conn)
+        .unwrap();
+    assert!(stored.iter().all(Option::is_none));
+
+    let err = commit(&root, "nothing changed").unwrap_err();
+    assert!(matches!(err, WorkSpaceError::NothingToCommit(_)));
+
+    let stored = nodes_dsl::nodes
+        .select(nodes_dsl::tree_id)
+        .load::<Option<String>>(&mut conn)
+        .unwrap();
+    assert!(stored.iter().all(Option::is_some));
+
+    Ok(())
+}
+
 #[test]
 fn diff_nodes_rejects_nonexistent_to_node() -> Result<()> {
     let (_td, root) = setup();
@@ -228,9 +300,10 @@ fn diff_nodes_rejects_nonexistent_to_node() -> Result<()> {
 
     let diff_error = diff_nodes(&root, &first_commit_id, "nonexistent").unwrap_err();
 
-    assert!(
-        matches!(diff_error, workspace_core::WorkSpaceError::Io(ref e) if e.kind() == std::io::ErrorKind::NotFound)
-    );
+    assert!(matches!(
+        diff_error,
+        workspace_core::WorkSpaceError::NodeNotFound(_)
+    ));
 
     Ok(())
 }
@@ -245,9 +318,10 @@ fn diff_nodes_rejects_nonexistent_from_node() -> Result<()> {
 
     let diff_error = diff_nodes(&root, "nonexistent", &first_commit_id).unwrap_err();
 
-    assert!(
-        matches!(diff_error, workspace_core::WorkSpaceError::Io(ref e) if e.kind() == std::io::ErrorKind::NotFound)
-    );
+    assert!(matches!(
+        diff_error,
+        workspace_core::WorkSpaceError::NodeNotFound(_)
+    ));
 
     Ok(())
 }
@@ -259,11 +333,11 @@ fn binary_file_diff_is_detected() -> Result<()> {
     init_repo(&root)?;
 
     let file_path = root.join("binary.bin");
-    std::fs::write(&file_path, &[0u8, 1, 2, 3])?;
+    std::fs::write(&file_path, [0u8, 1, 2, 3])?;
 
     let first_commit_id = commit(&root, "initial commit")?;
 
-    std::fs::write(&file_path, &[0u8, 1, 2, 3, 4])?;
+    std::fs::write(&file_path, [0u8, 1, 2, 3, 4])?;
 
     let second_commit_id = commit(&root, "second commit")?;
 